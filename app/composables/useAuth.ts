@@ -12,6 +12,19 @@ const tauriTokens = ref<{
   expiresAt: number
 } | null>(null)
 
+// Keep the Rust Graph client's token in step with ours
+if (isTauri) {
+  watch(tauriTokens, async (tokens) => {
+    const { invoke } = await import('@tauri-apps/api/core')
+    if (tokens) {
+      await invoke('graph_set_token', { accessToken: tokens.accessToken, expiresAt: tokens.expiresAt })
+    }
+    else {
+      await invoke('graph_clear_token')
+    }
+  })
+}
+
 export function useAuth() {
   const nuxtApp = useNuxtApp()
 
@@ -53,6 +66,8 @@ export function useAuth() {
     if (isTauri) {
       tauriTokens.value = null
       currentAccount.value = null
+      // Don't leave the previous account's messages on disk
+      await useLocalCache().clear()
     }
     else {
       const msal = getMsal()
@@ -89,12 +104,19 @@ export function useAuth() {
     }
   }
 
+  // Called when the Rust Graph client had its token rejected
+  async function forceTokenRefresh(): Promise<void> {
+    if (!isTauri || !currentAccount.value) return
+    await getTauriAccessToken(true)
+  }
+
   return {
     loggedIn,
     account,
     login,
     logout,
     getAccessToken,
+    forceTokenRefresh,
     initialize,
   }
 }
@@ -259,13 +281,13 @@ async function refreshAccessToken(): Promise<TokenResponse> {
 // Guard against concurrent refresh requests (token stampede prevention)
 let pendingRefresh: Promise<string> | null = null
 
-async function getTauriAccessToken(): Promise<string> {
+async function getTauriAccessToken(forceRefresh = false): Promise<string> {
   if (!tauriTokens.value) {
     throw new Error('No tokens. Please sign in.')
   }
 
   // Return cached token if not expired (with 5 min buffer)
-  if (tauriTokens.value.expiresAt > Date.now() + 5 * 60 * 1000) {
+  if (!forceRefresh && tauriTokens.value.expiresAt > Date.now() + 5 * 60 * 1000) {
     return tauriTokens.value.accessToken
   }
 
@@ -2,6 +2,8 @@ import type { Channel, ChannelMessage, PendingImage, Team, UseChannelsReturn } f
 
 export function useChannels(): UseChannelsReturn {
   const { graphFetchAll, graphFetch, graphFetchPage } = useGraph()
+  const localCache = useLocalCache()
+  const outbox = useOutbox()
 
   const teams = ref<Team[]>([])
   const channels = ref<Map<string, Channel[]>>(new Map())
@@ -52,11 +54,19 @@ export function useChannels(): UseChannelsReturn {
   }
 
   async function fetchChannels(teamId: string) {
+    if (!channels.value.has(teamId)) {
+      const stored = await localCache.getChannels(teamId)
+      if (stored.length > 0) {
+        channels.value.set(teamId, stored)
+        channels.value = new Map(channels.value)
+      }
+    }
     try {
       const result = await graphFetchAll<Channel>(`/teams/${teamId}/channels`)
       channels.value.set(teamId, result)
       // Trigger reactivity on the Map
       channels.value = new Map(channels.value)
+      localCache.putChannels(teamId, result)
     }
     catch (err: any) {
       console.error(`[useChannels] fetchChannels(${teamId}) failed:`, err)
@@ -85,6 +95,10 @@ export function useChannels(): UseChannelsReturn {
     images: PendingImage[] = [],
   ) {
     const payload = await buildMessagePayload(content, images)
+    if (outbox.available) {
+      await outbox.enqueue(`${teamId}:${channelId}`, `/teams/${teamId}/channels/${channelId}/messages`, payload)
+      return
+    }
     await graphFetch(`/teams/${teamId}/channels/${channelId}/messages`, {
       method: 'POST',
       body: JSON.stringify(payload),
@@ -18,7 +18,8 @@ const sectionChatIds = new Set<string>()
 const sectionChatsById = new Map<string, Chat>()
 
 export function useChats(): UseChatsReturn {
-  const { graphFetch, graphFetchPage } = useGraph()
+  const { graphFetchPage, graphBatch } = useGraph()
+  const localCache = useLocalCache()
 
   const chats = ref<Chat[]>([])
   const loading = ref(false)
@@ -38,6 +39,12 @@ export function useChats(): UseChatsReturn {
   async function fetchChats() {
     loading.value = true
     error.value = null
+    // Show the last known list straight away while the network catches up
+    const stored = await localCache.getChats()
+    if (stored.length > 0 && chats.value.length === 0) {
+      chats.value = sortChatsByLastMessage(filterVisibleChats(mergeWithSectionChats(stored)))
+      loading.value = false
+    }
     try {
       // Fetch a single page of recent chats (not all pages — users can have thousands).
       // Graph API does not support $orderby combined with $expand=members,
@@ -49,6 +56,7 @@ export function useChats(): UseChatsReturn {
         },
       })
       chats.value = sortChatsByLastMessage(filterVisibleChats(mergeWithSectionChats(page.value)))
+      localCache.putChats(page.value)
     }
     catch (err: any) {
       console.error('[useChats] fetchChats failed:', err)
@@ -68,6 +76,7 @@ export function useChats(): UseChatsReturn {
         },
       })
       chats.value = sortChatsByLastMessage(filterVisibleChats(mergeWithSectionChats(page.value)))
+      localCache.putChats(page.value)
     }
     catch (err: any) {
       console.error('[useChats] refreshChats failed:', err)
@@ -84,20 +93,29 @@ export function useChats(): UseChatsReturn {
     const missing = requiredChatIds.filter(id => !loadedIds.has(id) && !sectionChatIds.has(id))
     if (missing.length === 0) return
 
+    // One $batch round trip per 20 chats instead of a request each
     const fetched: Chat[] = []
-    await Promise.allSettled(missing.map(async (chatId) => {
-      try {
-        const chat = await graphFetch<Chat>(`/me/chats/${chatId}`, {
-          params: { $expand: 'lastMessagePreview,members' },
-        })
-        fetched.push(chat)
-        sectionChatIds.add(chatId)
-        sectionChatsById.set(chatId, chat)
-      }
-      catch (err) {
-        console.warn(`[useChats] Failed to fetch section chat ${chatId}:`, err)
+    try {
+      const responses = await graphBatch(missing.map(chatId => ({
+        id: chatId,
+        method: 'GET',
+        url: `/me/chats/${chatId}?$expand=lastMessagePreview,members`,
+      })))
+      for (const response of responses) {
+        if (response.status >= 200 && response.status < 300) {
+          const chat = response.body as Chat
+          fetched.push(chat)
+          sectionChatIds.add(response.id)
+          sectionChatsById.set(response.id, chat)
+        }
+        else {
+          console.warn(`[useChats] Failed to fetch section chat ${response.id}:`, response.body?.error ?? response.status)
+        }
       }
-    }))
+    }
+    catch (err) {
+      console.warn('[useChats] Failed to fetch section chats:', err)
+    }
 
     if (fetched.length > 0) {
       chats.value = sortChatsByLastMessage([...chats.value, ...fetched])
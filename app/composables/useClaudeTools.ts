@@ -1,4 +1,4 @@
-import type { ClaudeToolDefinition } from '~/types/claude'
+import type { ClaudeToolDefinition, FileContent } from '~/types/claude'
 import type { PluginLogEntry } from '~/types/plugin'
 
 const isTauri = typeof window !== 'undefined' && '__TAURI__' in window
@@ -87,11 +87,15 @@ const PLUGIN_TOOLS: ClaudeToolDefinition[] = [
 const FS_TOOLS: ClaudeToolDefinition[] = [
   {
     name: 'read_file',
-    description: 'Read the contents of a source file. Path is relative to the project root.',
+    description: 'Read the contents of a source file. Path is relative to the project root. Large files are truncated; use offset and limit to page through them. Binary files return no content unless binary_format is set.',
     input_schema: {
       type: 'object',
       properties: {
         path: { type: 'string', description: 'File path relative to project root (e.g. "app/composables/useClaude.ts")' },
+        offset: { type: 'number', description: 'Line to start reading from (1-based, default 1)' },
+        limit: { type: 'number', description: 'Maximum number of lines to return' },
+        max_bytes: { type: 'number', description: 'Maximum bytes of content to return (default 262144)' },
+        binary_format: { type: 'string', enum: ['hex', 'base64'], description: 'How to encode the content of a binary file' },
       },
       required: ['path'],
     },
@@ -110,17 +114,47 @@ const FS_TOOLS: ClaudeToolDefinition[] = [
   },
   {
     name: 'edit_file',
-    description: 'Find and replace text in a file. The old_text must appear exactly once in the file. Use this for all modifications to existing files.',
+    description: 'Find and replace text in a file. The old_text must appear exactly once in the file unless occurrence is given. Use this for single modifications to existing files.',
     input_schema: {
       type: 'object',
       properties: {
         path: { type: 'string', description: 'File path relative to project root' },
-        old_text: { type: 'string', description: 'Exact text to find (must be unique in the file)' },
+        old_text: { type: 'string', description: 'Exact text to find (must be unique in the file unless occurrence is set)' },
         new_text: { type: 'string', description: 'Text to replace it with' },
+        occurrence: { type: 'number', description: 'Replace only the nth match (1-based) when old_text appears more than once' },
       },
       required: ['path', 'old_text', 'new_text'],
     },
   },
+  {
+    name: 'multi_edit_file',
+    description: 'Apply several edits to one file atomically — either all succeed or the file is left untouched. Edits run in order, so line numbers refer to the file as changed by earlier edits. Prefer this over repeated edit_file calls for refactors.',
+    input_schema: {
+      type: 'object',
+      properties: {
+        path: { type: 'string', description: 'File path relative to project root' },
+        edits: {
+          type: 'array',
+          description: 'Edits to apply in order',
+          items: {
+            type: 'object',
+            properties: {
+              type: { type: 'string', enum: ['replace', 'replace_lines', 'insert_at_line'], description: 'Kind of edit' },
+              old_text: { type: 'string', description: 'replace: exact text to find' },
+              new_text: { type: 'string', description: 'replace / replace_lines: replacement text' },
+              occurrence: { type: 'number', description: 'replace: nth match to replace (1-based); omit to require a unique match' },
+              start_line: { type: 'number', description: 'replace_lines: first line to replace (1-based)' },
+              end_line: { type: 'number', description: 'replace_lines: last line to replace (inclusive)' },
+              line: { type: 'number', description: 'insert_at_line: insert before this line (1-based); one past the last line appends' },
+              text: { type: 'string', description: 'insert_at_line: text to insert' },
+            },
+            required: ['type'],
+          },
+        },
+      },
+      required: ['path', 'edits'],
+    },
+  },
   {
     name: 'list_directory',
     description: 'List files and subdirectories. Returns name, path, type, and size for each entry.',
@@ -148,9 +182,55 @@ const FS_TOOLS: ClaudeToolDefinition[] = [
   },
 ]
 
+const GIT_TOOLS: ClaudeToolDefinition[] = [
+  {
+    name: 'git_status',
+    description: 'Show the current branch and changed, staged and untracked files in the project repository.',
+    input_schema: {
+      type: 'object',
+      properties: {},
+    },
+  },
+  {
+    name: 'git_diff',
+    description: 'Show the unified diff of uncommitted changes, for the whole working tree or a single file.',
+    input_schema: {
+      type: 'object',
+      properties: {
+        path: { type: 'string', description: 'File path relative to project root (default: all changes)' },
+        staged: { type: 'boolean', description: 'Diff staged changes instead of the working tree (default false)' },
+      },
+    },
+  },
+  {
+    name: 'git_log',
+    description: 'List recent commits, optionally only those touching a path.',
+    input_schema: {
+      type: 'object',
+      properties: {
+        path: { type: 'string', description: 'File path relative to project root' },
+        limit: { type: 'number', description: 'Maximum number of commits (default 20)' },
+      },
+    },
+  },
+  {
+    name: 'git_blame',
+    description: 'Show which commit and author last changed each line in a line range of a file.',
+    input_schema: {
+      type: 'object',
+      properties: {
+        path: { type: 'string', description: 'File path relative to project root' },
+        start_line: { type: 'number', description: 'First line (1-based)' },
+        end_line: { type: 'number', description: 'Last line (inclusive)' },
+      },
+      required: ['path', 'start_line', 'end_line'],
+    },
+  },
+]
+
 export function useClaudeTools() {
   function getToolDefinitions(): ClaudeToolDefinition[] {
-    return [...PLUGIN_TOOLS, ...(isTauri ? FS_TOOLS : [])]
+    return [...PLUGIN_TOOLS, ...(isTauri ? [...FS_TOOLS, ...GIT_TOOLS] : [])]
   }
 
   async function executeTool(name: string, input: Record<string, unknown>): Promise<{ result: string; isError: boolean }> {
@@ -176,10 +256,21 @@ export function useClaudeTools() {
           return await handleWriteFile(input)
         case 'edit_file':
           return await handleEditFile(input)
+        case 'multi_edit_file':
+          return await handleMultiEditFile(input)
         case 'list_directory':
           return await handleListDirectory(input)
         case 'search_files':
           return await handleSearchFiles(input)
+        // Git tools
+        case 'git_status':
+          return await handleGitStatus()
+        case 'git_diff':
+          return await handleGitDiff(input)
+        case 'git_log':
+          return await handleGitLog(input)
+        case 'git_blame':
+          return await handleGitBlame(input)
         default:
           return { result: `Unknown tool: ${name}`, isError: true }
       }
@@ -314,15 +405,49 @@ export function useClaudeTools() {
   // --- Filesystem tool handlers ---
 
   async function handleReadFile(input: Record<string, unknown>): Promise<{ result: string; isError: boolean }> {
-    const { path } = input as { path: string }
+    const { path, offset, limit, max_bytes, binary_format } = input as {
+      path: string
+      offset?: number
+      limit?: number
+      max_bytes?: number
+      binary_format?: 'hex' | 'base64'
+    }
     if (!path) {
       return { result: 'Missing required field: path', isError: true }
     }
 
     const { invoke } = await import('@tauri-apps/api/core')
     const projectRoot = await getProjectRoot()
-    const content = await invoke<string>('fs_read_file', { projectRoot, path })
-    return { result: content, isError: false }
+    const file = await invoke<FileContent>('fs_read_file', {
+      projectRoot,
+      path,
+      offset: offset ?? null,
+      limit: limit ?? null,
+      maxBytes: max_bytes ?? null,
+      binaryFormat: binary_format ?? null,
+    })
+
+    const { size, encoding, line_count } = file.metadata
+    if (file.format === 'none') {
+      return { result: `(binary file, ${size} bytes — set binary_format to read it)`, isError: false }
+    }
+    if (file.format !== 'text') {
+      const suffix = file.truncated ? `\n... (truncated, file is ${size} bytes)` : ''
+      return { result: file.content + suffix, isError: false }
+    }
+
+    const notes: string[] = []
+    if (encoding !== 'utf-8') notes.push(`encoding: ${encoding}`)
+    if (file.truncated || (file.start_line ?? 1) > 1 || (file.end_line ?? 0) < (line_count ?? 0)) {
+      notes.push(file.end_line
+        ? `showing lines ${file.start_line}-${file.end_line} of ${line_count}`
+        : `no lines in range (file has ${line_count} lines)`)
+    }
+    if (file.partial_line) notes.push(`line ${file.end_line} cut off at the size limit`)
+    else if (file.truncated) notes.push('truncated by size limit')
+
+    const suffix = notes.length ? `\n... (${notes.join(', ')})` : ''
+    return { result: file.content + suffix, isError: false }
   }
 
   async function handleWriteFile(input: Record<string, unknown>): Promise<{ result: string; isError: boolean }> {
@@ -338,17 +463,29 @@ export function useClaudeTools() {
   }
 
   async function handleEditFile(input: Record<string, unknown>): Promise<{ result: string; isError: boolean }> {
-    const { path, old_text, new_text } = input as { path: string; old_text: string; new_text: string }
+    const { path, old_text, new_text, occurrence } = input as { path: string; old_text: string; new_text: string; occurrence?: number }
     if (!path || !old_text || new_text === undefined) {
       return { result: 'Missing required fields: path, old_text, new_text', isError: true }
     }
 
     const { invoke } = await import('@tauri-apps/api/core')
     const projectRoot = await getProjectRoot()
-    await invoke('fs_edit_file', { projectRoot, path, oldText: old_text, newText: new_text })
+    await invoke('fs_edit_file', { projectRoot, path, oldText: old_text, newText: new_text, occurrence: occurrence ?? null })
     return { result: `File edited: ${path}`, isError: false }
   }
 
+  async function handleMultiEditFile(input: Record<string, unknown>): Promise<{ result: string; isError: boolean }> {
+    const { path, edits } = input as { path: string; edits: Array<Record<string, unknown>> }
+    if (!path || !Array.isArray(edits) || edits.length === 0) {
+      return { result: 'Missing required fields: path, edits', isError: true }
+    }
+
+    const { invoke } = await import('@tauri-apps/api/core')
+    const projectRoot = await getProjectRoot()
+    await invoke('fs_multi_edit_file', { projectRoot, path, edits })
+    return { result: `Applied ${edits.length} edit(s) to ${path}`, isError: false }
+  }
+
   async function handleListDirectory(input: Record<string, unknown>): Promise<{ result: string; isError: boolean }> {
     const { path, recursive } = input as { path: string; recursive?: boolean }
     if (!path) {
@@ -395,6 +532,69 @@ export function useClaudeTools() {
     return { result: lines.join('\n') + suffix, isError: false }
   }
 
+  // --- Git tool handlers ---
+
+  async function handleGitStatus(): Promise<{ result: string; isError: boolean }> {
+    const { invoke } = await import('@tauri-apps/api/core')
+    const projectRoot = await getProjectRoot()
+    const status = await invoke<{ branch: string | null; entries: Array<{ path: string; status: string; orig_path: string | null }> }>('git_status', { projectRoot })
+
+    const header = `On branch ${status.branch ?? '(detached)'}`
+    if (status.entries.length === 0) {
+      return { result: `${header}\nWorking tree clean.`, isError: false }
+    }
+
+    const lines = status.entries.map(e => e.orig_path ? `${e.status} ${e.orig_path} -> ${e.path}` : `${e.status} ${e.path}`)
+    return { result: `${header}\n${lines.join('\n')}`, isError: false }
+  }
+
+  async function handleGitDiff(input: Record<string, unknown>): Promise<{ result: string; isError: boolean }> {
+    const { path, staged } = input as { path?: string; staged?: boolean }
+
+    const { invoke } = await import('@tauri-apps/api/core')
+    const projectRoot = await getProjectRoot()
+    const diff = await invoke<string>('git_diff', { projectRoot, path: path || null, staged: staged || false })
+    return { result: diff || 'No changes.', isError: false }
+  }
+
+  async function handleGitLog(input: Record<string, unknown>): Promise<{ result: string; isError: boolean }> {
+    const { path, limit } = input as { path?: string; limit?: number }
+
+    const { invoke } = await import('@tauri-apps/api/core')
+    const projectRoot = await getProjectRoot()
+    const commits = await invoke<Array<{ hash: string; author: string; date: string; subject: string }>>('git_log', {
+      projectRoot,
+      path: path || null,
+      limit: limit ?? null,
+    })
+
+    if (commits.length === 0) {
+      return { result: 'No commits found.', isError: false }
+    }
+
+    const lines = commits.map(c => `${c.hash.slice(0, 8)} ${c.date} ${c.author}: ${c.subject}`)
+    return { result: lines.join('\n'), isError: false }
+  }
+
+  async function handleGitBlame(input: Record<string, unknown>): Promise<{ result: string; isError: boolean }> {
+    const { path, start_line, end_line } = input as { path: string; start_line: number; end_line: number }
+    if (!path || !start_line || !end_line) {
+      return { result: 'Missing required fields: path, start_line, end_line', isError: true }
+    }
+
+    const { invoke } = await import('@tauri-apps/api/core')
+    const projectRoot = await getProjectRoot()
+    const lines = await invoke<Array<{ line: number; hash: string; author: string; summary: string; content: string }>>('git_blame', {
+      projectRoot,
+      path,
+      startLine: start_line,
+      endLine: end_line,
+    })
+
+    const result = lines.map(l => `${l.hash.slice(0, 8)} (${l.author}) ${l.line}: ${l.content}`)
+    return { result: result.join('\n'), isError: false }
+  }
+
   return {
     getToolDefinitions,
     executeTool,
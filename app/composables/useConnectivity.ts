@@ -0,0 +1,38 @@
+import type { ConnectivityState, ConnectivityStatus } from '~/types/connectivity'
+
+// Module-level shared state — mirrors the Rust connectivity monitor
+const status = ref<ConnectivityStatus | null>(null)
+let listening = false
+
+async function startListening() {
+  if (listening || import.meta.server || !(window as Record<string, unknown>).__TAURI_INTERNALS__) return
+  listening = true
+
+  const { invoke } = await import('@tauri-apps/api/core')
+  const { listen } = await import('@tauri-apps/api/event')
+
+  await listen<ConnectivityStatus>('connectivity:changed', (event) => {
+    status.value = event.payload
+  })
+  status.value = await invoke<ConnectivityStatus>('connectivity_status')
+
+  // The OS usually knows first; have Rust re-probe instead of waiting for its timer
+  const recheck = () => invoke('connectivity_check').catch(() => {})
+  window.addEventListener('online', recheck)
+  window.addEventListener('offline', recheck)
+}
+
+export function useConnectivity() {
+  startListening()
+
+  /** `online` outside Tauri, where there is no monitor. */
+  const state = computed<ConnectivityState>(() => status.value?.state ?? 'online')
+  /** Whether requests can be expected to succeed. */
+  const isUsable = computed(() => state.value === 'online' || state.value === 'degraded')
+
+  return {
+    status: readonly(status),
+    state,
+    isUsable,
+  }
+}
@@ -0,0 +1,59 @@
+import type { EditPolicy, EditProposal } from '~/types/claude'
+
+const isTauri = typeof window !== 'undefined' && '__TAURI__' in window
+
+// Module-level shared state — the Rust side holds the source of truth
+const pendingEdits = ref<EditProposal[]>([])
+let listening = false
+
+async function startListening() {
+  if (listening || !isTauri) return
+  listening = true
+
+  const { invoke } = await import('@tauri-apps/api/core')
+  const { listen } = await import('@tauri-apps/api/event')
+
+  await listen<EditProposal>('fs:edit-proposed', (event) => {
+    pendingEdits.value = [...pendingEdits.value, event.payload]
+  })
+  await listen<{ id: string; approved: boolean }>('fs:edit-resolved', (event) => {
+    pendingEdits.value = pendingEdits.value.filter(e => e.id !== event.payload.id)
+  })
+
+  // Pick up proposals emitted before we started listening
+  pendingEdits.value = await invoke<EditProposal[]>('fs_list_pending_edits')
+}
+
+export function useEditApprovals() {
+  startListening()
+
+  async function approveEdit(id: string): Promise<void> {
+    const { invoke } = await import('@tauri-apps/api/core')
+    await invoke('fs_approve_edit', { id })
+  }
+
+  async function rejectEdit(id: string, reason?: string): Promise<void> {
+    const { invoke } = await import('@tauri-apps/api/core')
+    await invoke('fs_reject_edit', { id, reason: reason ?? null })
+  }
+
+  async function getEditPolicy(): Promise<EditPolicy> {
+    if (!isTauri) return 'read-only'
+    const { invoke } = await import('@tauri-apps/api/core')
+    return invoke<EditPolicy>('fs_get_edit_policy')
+  }
+
+  async function setEditPolicy(policy: EditPolicy): Promise<void> {
+    if (!isTauri) return
+    const { invoke } = await import('@tauri-apps/api/core')
+    await invoke('fs_set_edit_policy', { policy })
+  }
+
+  return {
+    pendingEdits: readonly(pendingEdits),
+    approveEdit,
+    rejectEdit,
+    getEditPolicy,
+    setEditPolicy,
+  }
+}
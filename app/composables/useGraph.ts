@@ -1,13 +1,22 @@
-import type { GraphApiError, PaginatedResponse } from '~/types/graph'
+import type { GraphApiError, GraphBatchRequest, GraphBatchResponse, PaginatedResponse } from '~/types/graph'
 
 interface GraphFetchOptions extends Omit<RequestInit, 'headers'> {
   headers?: Record<string, string>
   params?: Record<string, string>
 }
 
+// Bodies cross the IPC boundary tagged with how Rust should send them
+interface NativeBody {
+  type: 'json' | 'text' | 'bytes'
+  data: unknown
+}
+
 const MAX_RETRIES = 3
+const MAX_BATCH_SIZE = 20
 const BASE_DELAY_MS = 1000
 
+const isTauri = typeof window !== 'undefined' && '__TAURI__' in window
+
 export function useGraph() {
   const baseUrl = 'https://graph.microsoft.com/v1.0'
 
@@ -19,15 +28,76 @@ export function useGraph() {
     return err
   }
 
-  async function graphFetch<T>(path: string, options: GraphFetchOptions = {}): Promise<T> {
-    const accessToken = await getToken()
-
+  function buildUrl(path: string, params?: Record<string, string>): URL {
     const url = new URL(path.startsWith('http') ? path : `${baseUrl}${path}`)
-    if (options.params) {
-      for (const [key, value] of Object.entries(options.params)) {
+    if (params) {
+      for (const [key, value] of Object.entries(params)) {
         url.searchParams.set(key, value)
       }
     }
+    return url
+  }
+
+  async function toNativeBody(body: BodyInit | null | undefined, headers: Record<string, string>): Promise<NativeBody | null> {
+    if (body == null)
+      return null
+
+    const contentTypeKey = Object.keys(headers).find(key => key.toLowerCase() === 'content-type')
+    if (typeof body === 'string') {
+      const contentType = contentTypeKey ? headers[contentTypeKey] : undefined
+      if (!contentType || contentType.includes('json')) {
+        try {
+          return { type: 'json', data: JSON.parse(body) }
+        }
+        catch {
+          // Not JSON after all; send it as it is
+        }
+      }
+      return { type: 'text', data: body }
+    }
+    if (body instanceof Blob) {
+      if (!contentTypeKey && body.type)
+        headers['Content-Type'] = body.type
+      return { type: 'bytes', data: Array.from(new Uint8Array(await body.arrayBuffer())) }
+    }
+    if (body instanceof ArrayBuffer)
+      return { type: 'bytes', data: Array.from(new Uint8Array(body)) }
+    if (ArrayBuffer.isView(body))
+      return { type: 'bytes', data: Array.from(new Uint8Array(body.buffer, body.byteOffset, body.byteLength)) }
+
+    // FormData, URLSearchParams and streams have no equivalent on the Rust side
+    throw createGraphError('unsupported_body', `Unsupported request body: ${Object.prototype.toString.call(body)}`)
+  }
+
+  // On desktop, route through the Rust client so retries, throttling and
+  // token refresh are shared with the background sync
+  async function graphFetchNative<T>(path: string, options: GraphFetchOptions): Promise<T> {
+    const { invoke } = await import('@tauri-apps/api/core')
+    const url = buildUrl(path, options.params)
+    const headers = { ...options.headers }
+    const body = await toNativeBody(options.body, headers)
+
+    try {
+      return await invoke<T>('graph_fetch', {
+        method: options.method ?? 'GET',
+        path: url.toString(),
+        body,
+        headers,
+      })
+    }
+    catch (err: any) {
+      throw createGraphError(err?.code ?? 'UNKNOWN', err?.message ?? String(err))
+    }
+  }
+
+  async function graphFetch<T>(path: string, options: GraphFetchOptions = {}): Promise<T> {
+    if (isTauri) {
+      return graphFetchNative<T>(path, options)
+    }
+
+    const accessToken = await getToken()
+
+    const url = buildUrl(path, options.params)
 
     const { params: _, headers: extraHeaders, ...fetchOptions } = options
 
@@ -119,9 +189,45 @@ export function useGraph() {
     return graphFetch<PaginatedResponse<T>>(path, options)
   }
 
+  /**
+   * Send several requests as JSON batches. Each response carries its own
+   * status; a failed item doesn't fail the others. On desktop the Rust client
+   * also keeps `dependsOn` chains within one batch and retries throttled items.
+   */
+  async function graphBatch(requests: GraphBatchRequest[]): Promise<GraphBatchResponse[]> {
+    if (isTauri) {
+      const { invoke } = await import('@tauri-apps/api/core')
+      try {
+        return await invoke<GraphBatchResponse[]>('graph_batch', { requests })
+      }
+      catch (err: any) {
+        throw createGraphError(err?.code ?? 'UNKNOWN', err?.message ?? String(err))
+      }
+    }
+
+    const responses = new Map<string, GraphBatchResponse>()
+    for (let i = 0; i < requests.length; i += MAX_BATCH_SIZE) {
+      const chunk = requests.slice(i, i + MAX_BATCH_SIZE).map(r => ({
+        ...r,
+        headers: r.body ? { 'Content-Type': 'application/json', ...r.headers } : r.headers,
+      }))
+      const result = await graphFetch<{ responses: GraphBatchResponse[] }>('/$batch', {
+        method: 'POST',
+        body: JSON.stringify({ requests: chunk }),
+      })
+      for (const response of result.responses) responses.set(response.id, response)
+    }
+    return requests.map(r => responses.get(r.id) ?? {
+      id: r.id,
+      status: 502,
+      body: { error: { code: 'missingResponse', message: 'No response for batched request' } },
+    })
+  }
+
   return {
     graphFetch,
     graphFetchAll,
     graphFetchPage,
+    graphBatch,
   }
 }
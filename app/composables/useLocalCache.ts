@@ -0,0 +1,36 @@
+import type { Channel, Chat, ChatMessage } from '~/types/graph'
+
+// Thin wrapper over the encrypted SQLite cache in the Rust backend.
+// Every call is a no-op (or resolves empty) outside Tauri or when the cache
+// is unavailable, so callers can always fall through to the network.
+
+function hasNativeCache(): boolean {
+  return !import.meta.server && !!(window as Record<string, unknown>).__TAURI_INTERNALS__
+}
+
+async function call<T>(command: string, args: Record<string, unknown> = {}): Promise<T | null> {
+  if (!hasNativeCache()) return null
+  try {
+    const { invoke } = await import('@tauri-apps/api/core')
+    return await invoke<T>(command, args)
+  }
+  catch (err) {
+    console.warn(`[useLocalCache] ${command} failed:`, err)
+    return null
+  }
+}
+
+export function useLocalCache() {
+  return {
+    getChats: async () => (await call<Chat[]>('cache_get_chats')) ?? [],
+    putChats: (chats: Chat[]) => call('cache_put_chats', { chats }),
+    getChannels: async (teamId: string) => (await call<Channel[]>('cache_get_channels', { teamId })) ?? [],
+    putChannels: (teamId: string, channels: Channel[]) => call('cache_put_channels', { teamId, channels }),
+    /** Newest cached messages of a chat or `<teamId>:<channelId>`, oldest first. */
+    getMessages: async (conversation: string, before?: string, limit?: number) =>
+      (await call<ChatMessage[]>('cache_get_messages', { conversation, before, limit })) ?? [],
+    putMessages: (conversation: string, messages: ChatMessage[]) =>
+      call('cache_put_messages', { conversation, messages }),
+    clear: () => call('cache_clear'),
+  }
+}
@@ -18,8 +18,65 @@ function evictOldest() {
   }
 }
 
+// --- Native delta sync (Tauri) ---
+// When the Rust sync engine delivers chat message deltas, open chats skip
+// their own polling and merge `sync:messages` instead.
+
+interface SyncMessageChanges {
+  key: string
+  chatId: string | null
+  messages: ChatMessage[]
+  removed: string[]
+}
+
+const nativeChatMessages = ref(false)
+const deltaSubscribers = new Set<(changes: SyncMessageChanges) => void>()
+let nativeListening = false
+
+function applyChanges(current: ChatMessage[], changes: SyncMessageChanges): ChatMessage[] {
+  const removed = new Set(changes.removed)
+  const byId = new Map(current.filter(m => !removed.has(m.id)).map(m => [m.id, m]))
+  for (const msg of changes.messages) {
+    const existing = byId.get(msg.id)
+    byId.set(msg.id, existing ? { ...existing, ...msg } : msg)
+  }
+  return [...byId.values()].sort((a, b) => a.createdDateTime.localeCompare(b.createdDateTime))
+}
+
+async function ensureNativeListener() {
+  if (nativeListening || import.meta.server || !(window as Record<string, unknown>).__TAURI_INTERNALS__) return
+  nativeListening = true
+
+  const [{ listen }, { invoke }] = await Promise.all([
+    import('@tauri-apps/api/event'),
+    import('@tauri-apps/api/core'),
+  ])
+
+  await listen<{ chatMessages: boolean }>('sync:status', ({ payload }) => {
+    nativeChatMessages.value = payload.chatMessages
+  })
+  await listen<SyncMessageChanges>('sync:messages', ({ payload }) => {
+    if (!payload.chatId) return
+    const cached = messageCache.get(payload.chatId)
+    if (cached) {
+      cached.messages = applyChanges(cached.messages, payload)
+    }
+    for (const notify of deltaSubscribers) notify(payload)
+  })
+
+  try {
+    const status = await invoke<{ chatMessages: boolean }>('sync_status')
+    nativeChatMessages.value = status.chatMessages
+  }
+  catch {
+    nativeChatMessages.value = false
+  }
+}
+
 export function useMessages(chatId: Ref<string | null>): UseMessagesReturn {
   const { graphFetch, graphFetchPage } = useGraph()
+  const localCache = useLocalCache()
+  const outbox = useOutbox()
 
   const messages = ref<ChatMessage[]>([])
   const loading = ref(false)
@@ -29,12 +86,35 @@ export function useMessages(chatId: Ref<string | null>): UseMessagesReturn {
   const nextLink = ref<string | null>(null)
   let pollTimer: ReturnType<typeof setInterval> | null = null
 
+  ensureNativeListener()
+
+  function onDelta(changes: SyncMessageChanges) {
+    if (changes.chatId !== chatId.value) return
+    const cached = messageCache.get(changes.chatId)
+    // The module listener already merged into the cache entry
+    messages.value = cached ? cached.messages : applyChanges(messages.value, changes)
+  }
+  deltaSubscribers.add(onDelta)
+
+  // Messages queued in the native outbox show up here once Graph accepts them
+  const stopOutbox = outbox.onSent(({ item, message }) => {
+    const viewing = chatId.value === item.conversation
+    const cached = messageCache.get(item.conversation)
+    const current = viewing ? messages.value : cached?.messages
+    if (!current || current.some(m => m.id === message.id)) return
+    message.reactions ??= []
+    message.attachments ??= []
+    message.mentions ??= []
+    updateMessages(item.conversation, [...current, message], viewing ? nextLink.value : cached?.nextLink ?? null)
+  })
+
   /** Sync the ref + cache entry for the current chat */
   function updateMessages(id: string, msgs: ChatMessage[], link: string | null) {
     // Update cache (delete + set to refresh LRU order)
     messageCache.delete(id)
     messageCache.set(id, { messages: msgs, nextLink: link })
     evictOldest()
+    localCache.putMessages(id, msgs)
     // Update refs if still viewing this chat
     if (chatId.value === id) {
       messages.value = msgs
@@ -47,10 +127,15 @@ export function useMessages(chatId: Ref<string | null>): UseMessagesReturn {
     if (!chatId.value) return
     const id = chatId.value
 
-    // Only show spinner on cache miss
+    // Only show spinner on cache miss — try the on-disk cache before the network
     const cached = messageCache.get(id)
     if (!cached) {
       loading.value = true
+      const stored = await localCache.getMessages(id)
+      if (stored.length > 0 && chatId.value === id && !messageCache.has(id)) {
+        messages.value = stored
+        loading.value = false
+      }
     }
 
     error.value = null
@@ -96,6 +181,11 @@ export function useMessages(chatId: Ref<string | null>): UseMessagesReturn {
     const id = chatId.value
 
     const payload = await buildMessagePayload(content, images)
+    if (outbox.available) {
+      // Delivered (and appended) asynchronously — see onSent above
+      await outbox.enqueue(id, `/me/chats/${id}/messages`, payload)
+      return
+    }
     const sent = await graphFetch<ChatMessage>(`/me/chats/${id}/messages`, {
       method: 'POST',
       body: JSON.stringify(payload),
@@ -112,7 +202,7 @@ export function useMessages(chatId: Ref<string | null>): UseMessagesReturn {
   }
 
   async function pollForNew() {
-    if (!chatId.value) return
+    if (!chatId.value || nativeChatMessages.value) return
     const id = chatId.value
 
     try {
@@ -173,6 +263,8 @@ export function useMessages(chatId: Ref<string | null>): UseMessagesReturn {
   // Cleanup on unmount
   onUnmounted(() => {
     stopPolling()
+    deltaSubscribers.delete(onDelta)
+    stopOutbox()
   })
 
   return {
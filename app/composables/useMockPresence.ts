@@ -1,4 +1,4 @@
-import type { Presence, UsePresenceReturn } from '~~/types/graph'
+import type { PreferredPresence, Presence, UsePresenceReturn } from '~~/types/graph'
 
 const mockPresenceData: Record<string, Presence> = {
   'user-002': { id: 'user-002', availability: 'Available', activity: 'Available' },
@@ -12,6 +12,7 @@ const mockPresenceData: Record<string, Presence> = {
 
 // Module-level shared state — all callers see the same data
 const presenceMap = ref<Map<string, Presence>>(new Map())
+const myPresence = ref<Presence | null>({ id: 'user-001', availability: 'Available', activity: 'Available' })
 
 export function useMockPresence(): UsePresenceReturn {
 
@@ -38,12 +39,33 @@ export function useMockPresence(): UsePresenceReturn {
     return presenceMap.value.get(userId)
   }
 
+  async function setMyPresence(status: PreferredPresence) {
+    const availability = status === 'AppearAway' ? 'Away' : status
+    myPresence.value = { id: 'user-001', availability, activity: availability }
+  }
+
+  async function clearMyPresence() {
+    myPresence.value = { id: 'user-001', availability: 'Available', activity: 'Available' }
+  }
+
+  async function setStatusMessage(message: string) {
+    if (!myPresence.value) return
+    myPresence.value = {
+      ...myPresence.value,
+      statusMessage: message ? { message: { content: message, contentType: 'text' } } : null,
+    }
+  }
+
   return {
     presenceMap,
     fetchPresence,
     startPolling,
     stopPolling,
     getPresence,
+    myPresence,
+    setMyPresence,
+    clearMyPresence,
+    setStatusMessage,
   }
 }
 
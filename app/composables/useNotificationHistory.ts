@@ -0,0 +1,66 @@
+import type { HistoryQuery, NotificationRecord } from '~/types/notifications'
+
+// Module-level shared state — newest first, mirrors the Rust notification history
+const entries = ref<NotificationRecord[]>([])
+let listening = false
+
+function isTauri(): boolean {
+  return !import.meta.server && !!(window as Record<string, unknown>).__TAURI_INTERNALS__
+}
+
+function markLocal(predicate: (entry: NotificationRecord) => boolean) {
+  entries.value = entries.value.map(e => (!e.seen && predicate(e) ? { ...e, seen: true } : e))
+}
+
+async function startListening() {
+  if (listening || !isTauri()) return
+  listening = true
+
+  const { invoke } = await import('@tauri-apps/api/core')
+  const { listen } = await import('@tauri-apps/api/event')
+
+  await listen<NotificationRecord>('notifications:recorded', ({ payload }) => {
+    entries.value = [payload, ...entries.value]
+  })
+  // Reading a conversation marks its entries seen on the Rust side too
+  await listen<{ key: string }>('notifications:marked-read', ({ payload }) => {
+    markLocal(e => e.conversation === payload.key)
+  })
+
+  entries.value = await invoke<NotificationRecord[]>('notifications_history', { query: {} })
+}
+
+/** Activity feed: every notification shown or held back, with why. */
+export function useNotificationHistory() {
+  startListening()
+
+  const unseenCount = computed(() => entries.value.filter(e => !e.seen).length)
+
+  async function query(query: HistoryQuery): Promise<NotificationRecord[]> {
+    const { invoke } = await import('@tauri-apps/api/core')
+    return invoke<NotificationRecord[]>('notifications_history', { query })
+  }
+
+  /** Append the page before the oldest loaded entry. Returns false when there's nothing more. */
+  async function loadMore(limit = 50): Promise<boolean> {
+    const oldest = entries.value.at(-1)
+    const page = await query({ beforeId: oldest?.id, limit })
+    entries.value = [...entries.value, ...page]
+    return page.length === limit
+  }
+
+  /** Mark the given entries seen, or all of them. */
+  async function markSeen(ids?: number[]): Promise<void> {
+    const { invoke } = await import('@tauri-apps/api/core')
+    await invoke('notifications_mark_seen', { ids: ids ?? null })
+    markLocal(e => !ids || ids.includes(e.id))
+  }
+
+  return {
+    entries: readonly(entries),
+    unseenCount,
+    query,
+    loadMore,
+    markSeen,
+  }
+}
@@ -0,0 +1,104 @@
+import type { NotificationPolicy, NotificationRule, RuleDryRun, SampleMessage } from '~/types/notifications'
+
+/** Where the retired `notification-filter` plugin kept its settings. */
+const FILTER_PLUGIN_SETTINGS = 'teamy-plugin-settings-notification-filter'
+
+/** Do-not-disturb policy and rules applied by the Rust notification manager. */
+export function useNotificationPolicy() {
+  async function getPolicy(): Promise<NotificationPolicy> {
+    const { invoke } = await import('@tauri-apps/api/core')
+    return invoke<NotificationPolicy>('notifications_get_policy')
+  }
+
+  async function setPolicy(policy: NotificationPolicy): Promise<void> {
+    const { invoke } = await import('@tauri-apps/api/core')
+    await invoke('notifications_set_policy', { policy })
+  }
+
+  async function getRules(): Promise<NotificationRule[]> {
+    const { invoke } = await import('@tauri-apps/api/core')
+    return invoke<NotificationRule[]>('notifications_get_rules')
+  }
+
+  async function setRules(rules: NotificationRule[]): Promise<void> {
+    const { invoke } = await import('@tauri-apps/api/core')
+    await invoke('notifications_set_rules', { rules })
+  }
+
+  /** Which rule `sample` would hit — among `rules` if given, else the saved ones. */
+  async function dryRun(sample: SampleMessage, rules?: NotificationRule[]): Promise<RuleDryRun> {
+    const { invoke } = await import('@tauri-apps/api/core')
+    return invoke<RuleDryRun>('notifications_dry_run', { sample, rules: rules ?? null })
+  }
+
+  /**
+   * Turn the `notification-filter` plugin's settings into rules, once: its
+   * VIP list, muteGroupChats and muteMeetingChats. The settings are removed
+   * afterwards; rules with the same IDs are left as they are.
+   */
+  async function migrateFilterPlugin(): Promise<void> {
+    const raw = localStorage.getItem(FILTER_PLUGIN_SETTINGS)
+    if (!raw) return
+    let settings: { vipList?: string, muteGroupChats?: boolean, muteMeetingChats?: boolean }
+    try {
+      settings = JSON.parse(raw)
+    }
+    catch {
+      localStorage.removeItem(FILTER_PLUGIN_SETTINGS)
+      return
+    }
+
+    const migrated: NotificationRule[] = []
+    const vips = (settings.vipList ?? '').split(',').map(e => e.trim()).filter(Boolean)
+    if (vips.length > 0) {
+      // First match wins: VIPs are let through, other 1:1 chats only badge
+      migrated.push(
+        {
+          id: 'vip-direct-chats',
+          name: 'VIP direct chats',
+          when: { chatTypes: ['oneOnOne'], senders: vips },
+          action: 'notify',
+        },
+        {
+          id: 'other-direct-chats',
+          name: 'Other direct chats',
+          when: { chatTypes: ['oneOnOne'] },
+          action: 'badgeOnly',
+        },
+      )
+    }
+    if (settings.muteGroupChats) {
+      migrated.push({
+        id: 'quiet-group-chats',
+        name: 'Quiet group chats',
+        when: { chatTypes: ['group'] },
+        action: 'badgeOnly',
+      })
+    }
+    // The plugin muted meeting chats unless told otherwise
+    if (settings.muteMeetingChats ?? true) {
+      migrated.push({
+        id: 'quiet-meeting-chats',
+        name: 'Quiet meeting chats',
+        when: { chatTypes: ['meeting'] },
+        action: 'badgeOnly',
+      })
+    }
+
+    const rules = await getRules()
+    const added = migrated.filter(rule => !rules.some(r => r.id === rule.id))
+    if (added.length > 0) {
+      await setRules([...rules, ...added])
+    }
+    localStorage.removeItem(FILTER_PLUGIN_SETTINGS)
+  }
+
+  return {
+    getPolicy,
+    setPolicy,
+    getRules,
+    setRules,
+    dryRun,
+    migrateFilterPlugin,
+  }
+}
@@ -0,0 +1,52 @@
+import type { NotificationSound, SoundSettings } from '~/types/notifications'
+
+// Module-level shared state — mirrors the sound settings in the Rust notification module
+const bundled = ref<string[]>([])
+const chatSounds = ref<Record<string, NotificationSound>>({})
+let loaded = false
+
+function isTauri(): boolean {
+  return !import.meta.server && !!(window as Record<string, unknown>).__TAURI_INTERNALS__
+}
+
+async function load() {
+  if (loaded || !isTauri()) return
+  loaded = true
+
+  const { invoke } = await import('@tauri-apps/api/core')
+  const settings = await invoke<SoundSettings>('notifications_get_sounds')
+  bundled.value = settings.bundled
+  chatSounds.value = settings.chats
+}
+
+/** Per-conversation notification sounds (chat ID or `<teamId>:<channelId>`). */
+export function useNotificationSounds() {
+  load()
+
+  const available = isTauri()
+
+  function soundFor(conversation: string): NotificationSound {
+    return chatSounds.value[conversation] ?? 'default'
+  }
+
+  async function setChatSound(conversation: string, sound: NotificationSound): Promise<void> {
+    const { invoke } = await import('@tauri-apps/api/core')
+    await invoke('notifications_set_chat_sound', { conversation, sound })
+    const { [conversation]: _, ...rest } = chatSounds.value
+    chatSounds.value = sound === 'default' ? rest : { ...rest, [conversation]: sound }
+  }
+
+  async function previewSound(sound: NotificationSound): Promise<void> {
+    const { invoke } = await import('@tauri-apps/api/core')
+    await invoke('notifications_preview_sound', { sound })
+  }
+
+  return {
+    available,
+    bundled: readonly(bundled),
+    chatSounds: readonly(chatSounds),
+    soundFor,
+    setChatSound,
+    previewSound,
+  }
+}
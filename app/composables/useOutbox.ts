@@ -0,0 +1,80 @@
+import type { SendMessagePayload } from '~/types/graph'
+import type { OutboxItem, OutboxSent } from '~/types/outbox'
+
+// Module-level shared state — mirrors the Rust outbox queue
+const items = ref<OutboxItem[]>([])
+const sentSubscribers = new Set<(sent: OutboxSent) => void>()
+let listening = false
+
+function isTauri(): boolean {
+  return !import.meta.server && !!(window as Record<string, unknown>).__TAURI_INTERNALS__
+}
+
+function upsert(item: OutboxItem) {
+  const index = items.value.findIndex(i => i.id === item.id)
+  if (index === -1) items.value = [...items.value, item]
+  else items.value = items.value.map(i => (i.id === item.id ? item : i))
+}
+
+function drop(id: string) {
+  items.value = items.value.filter(i => i.id !== id)
+}
+
+async function startListening() {
+  if (listening || !isTauri()) return
+  listening = true
+
+  const { invoke } = await import('@tauri-apps/api/core')
+  const { listen } = await import('@tauri-apps/api/event')
+
+  await listen<OutboxItem>('outbox:queued', ({ payload }) => upsert(payload))
+  await listen<OutboxItem>('outbox:updated', ({ payload }) => upsert(payload))
+  await listen<{ id: string }>('outbox:removed', ({ payload }) => drop(payload.id))
+  await listen<OutboxSent>('outbox:sent', ({ payload }) => {
+    drop(payload.item.id)
+    for (const notify of sentSubscribers) notify(payload)
+  })
+
+  // Flushing when the network returns is handled by the Rust connectivity monitor
+  items.value = await invoke<OutboxItem[]>('outbox_list')
+}
+
+export function useOutbox() {
+  startListening()
+
+  /** Whether sends should go through the native outbox instead of a direct POST. */
+  const available = isTauri()
+
+  async function enqueue(conversation: string, path: string, payload: SendMessagePayload): Promise<OutboxItem> {
+    const { invoke } = await import('@tauri-apps/api/core')
+    const item = await invoke<OutboxItem>('outbox_enqueue', { conversation, path, payload })
+    upsert(item)
+    return item
+  }
+
+  async function resend(id: string): Promise<void> {
+    const { invoke } = await import('@tauri-apps/api/core')
+    upsert(await invoke<OutboxItem>('outbox_resend', { id }))
+  }
+
+  async function discard(id: string): Promise<void> {
+    const { invoke } = await import('@tauri-apps/api/core')
+    await invoke('outbox_discard', { id })
+    drop(id)
+  }
+
+  /** Call `callback` whenever a queued message is delivered. Returns an unsubscribe function. */
+  function onSent(callback: (sent: OutboxSent) => void): () => void {
+    sentSubscribers.add(callback)
+    return () => sentSubscribers.delete(callback)
+  }
+
+  return {
+    available,
+    items: readonly(items),
+    enqueue,
+    resend,
+    discard,
+    onSent,
+  }
+}
@@ -1,4 +1,4 @@
-import type { Presence, UsePresenceReturn } from '~/types/graph'
+import type { PreferredPresence, Presence, UsePresenceReturn } from '~/types/graph'
 
 const PRESENCE_POLL_INTERVAL_MS = 30_000
 
@@ -7,6 +7,44 @@ const presenceMap = ref<Map<string, Presence>>(new Map())
 let pollTimer: ReturnType<typeof setInterval> | null = null
 let trackedUserIds: string[] = []
 let consecutiveFailures = 0
+let nativeListening = false
+
+// The signed-in user's own presence (native only; the web build doesn't poll it)
+const myPresence = ref<Presence | null>(null)
+
+const PREFERRED_PRESENCE: Record<PreferredPresence, { availability: string, activity: string }> = {
+  Available: { availability: 'Available', activity: 'Available' },
+  Busy: { availability: 'Busy', activity: 'Busy' },
+  DoNotDisturb: { availability: 'DoNotDisturb', activity: 'DoNotDisturb' },
+  AppearAway: { availability: 'Away', activity: 'Away' },
+}
+
+function isTauri(): boolean {
+  return !import.meta.server && !!(window as Record<string, unknown>).__TAURI_INTERNALS__
+}
+
+function mergePresences(presences: Presence[]) {
+  for (const presence of presences) {
+    presenceMap.value.set(presence.id, presence)
+  }
+  // Trigger reactivity on the Map
+  presenceMap.value = new Map(presenceMap.value)
+}
+
+// Under Tauri the Rust presence service polls and caches; we only mirror its events
+async function startNativeListening() {
+  if (nativeListening || !isTauri()) return
+  nativeListening = true
+
+  const { invoke } = await import('@tauri-apps/api/core')
+  const { listen } = await import('@tauri-apps/api/event')
+
+  await listen<Presence[]>('presence:updated', ({ payload }) => mergePresences(payload))
+  await listen<Presence>('presence:mine', ({ payload }) => {
+    myPresence.value = payload
+  })
+  myPresence.value = await invoke<Presence | null>('presence_get_mine')
+}
 
 const AVAILABILITY_LABELS: Record<string, string> = {
   Available: 'Available',
@@ -28,9 +66,24 @@ export function getPresenceLabel(userId: string): string | undefined {
 
 export function usePresence(): UsePresenceReturn {
   const { graphFetch } = useGraph()
+  const { isUsable } = useConnectivity()
+  const native = isTauri()
+  startNativeListening()
 
   async function fetchPresence(userIds: string[]) {
-    if (userIds.length === 0) return
+    // Offline or signed out: skip quietly rather than counting failures
+    if (userIds.length === 0 || !isUsable.value) return
+
+    if (native) {
+      try {
+        const { invoke } = await import('@tauri-apps/api/core')
+        mergePresences(await invoke<Presence[]>('presence_get', { userIds }))
+      }
+      catch (err) {
+        console.warn('[usePresence] presence_get failed:', err)
+      }
+      return
+    }
 
     try {
       const response = await graphFetch<{ value: Presence[] }>(
@@ -42,11 +95,7 @@ export function usePresence(): UsePresenceReturn {
       )
 
       consecutiveFailures = 0
-      for (const presence of response.value) {
-        presenceMap.value.set(presence.id, presence)
-      }
-      // Trigger reactivity on the Map
-      presenceMap.value = new Map(presenceMap.value)
+      mergePresences(response.value)
     }
     catch (err) {
       consecutiveFailures++
@@ -61,6 +110,12 @@ export function usePresence(): UsePresenceReturn {
 
   function startPolling(userIds: string[]) {
     trackedUserIds = userIds
+    if (native) {
+      // Rust refreshes tracked users itself and emits presence:updated
+      import('@tauri-apps/api/core').then(({ invoke }) => invoke('presence_track', { userIds }))
+      fetchPresence(userIds)
+      return
+    }
     consecutiveFailures = 0
     stopPolling()
     fetchPresence(userIds)
@@ -68,16 +123,68 @@ export function usePresence(): UsePresenceReturn {
   }
 
   function stopPolling() {
+    if (native) {
+      import('@tauri-apps/api/core').then(({ invoke }) => invoke('presence_track', { userIds: [] }))
+      return
+    }
     if (pollTimer) {
       clearInterval(pollTimer)
       pollTimer = null
     }
   }
 
+  // Polling gives up after repeated failures; pick it back up once the network returns
+  watch(isUsable, (usable) => {
+    if (usable && !native && !pollTimer && trackedUserIds.length > 0) {
+      startPolling(trackedUserIds)
+    }
+  })
+
   function getPresence(userId: string): Presence | undefined {
     return presenceMap.value.get(userId)
   }
 
+  async function setMyPresence(status: PreferredPresence, expirationMinutes?: number) {
+    if (native) {
+      const { invoke } = await import('@tauri-apps/api/core')
+      await invoke('presence_set_preferred', { status, expirationMinutes })
+      return
+    }
+    await graphFetch('/me/presence/setUserPreferredPresence', {
+      method: 'POST',
+      body: JSON.stringify({
+        ...PREFERRED_PRESENCE[status],
+        ...(expirationMinutes ? { expirationDuration: `PT${expirationMinutes}M` } : {}),
+      }),
+    })
+  }
+
+  async function clearMyPresence() {
+    if (native) {
+      const { invoke } = await import('@tauri-apps/api/core')
+      await invoke('presence_clear_preferred')
+      return
+    }
+    await graphFetch('/me/presence/clearUserPreferredPresence', { method: 'POST', body: '{}' })
+  }
+
+  /** Set the status message; an empty message clears it. `expiresAt` is an ISO timestamp. */
+  async function setStatusMessage(message: string, expiresAt?: string | null) {
+    if (native) {
+      const { invoke } = await import('@tauri-apps/api/core')
+      await invoke('presence_set_status_message', { message, expiresAt: expiresAt ?? null })
+      return
+    }
+    const statusMessage: Record<string, unknown> = { message: { content: message, contentType: 'text' } }
+    if (expiresAt) {
+      statusMessage.expiryDateTime = { dateTime: new Date(expiresAt).toISOString().slice(0, 19), timeZone: 'UTC' }
+    }
+    await graphFetch('/me/presence/setStatusMessage', {
+      method: 'POST',
+      body: JSON.stringify({ statusMessage }),
+    })
+  }
+
   onUnmounted(() => {
     stopPolling()
   })
@@ -88,5 +195,9 @@ export function usePresence(): UsePresenceReturn {
     startPolling,
     stopPolling,
     getPresence,
+    myPresence,
+    setMyPresence,
+    clearMyPresence,
+    setStatusMessage,
   }
 }
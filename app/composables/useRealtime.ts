@@ -0,0 +1,53 @@
+import type { RealtimeConfig, RealtimeStatus } from '~/types/realtime'
+
+// Module-level shared state — mirrors the Rust subscription manager
+const status = ref<RealtimeStatus | null>(null)
+let listening = false
+
+async function startListening() {
+  if (listening || import.meta.server || !(window as Record<string, unknown>).__TAURI_INTERNALS__) return
+  listening = true
+
+  const { invoke } = await import('@tauri-apps/api/core')
+  const { listen } = await import('@tauri-apps/api/event')
+
+  await listen<RealtimeStatus>('realtime:status', (event) => {
+    status.value = event.payload
+  })
+  status.value = await invoke<RealtimeStatus>('realtime_status')
+}
+
+export function useRealtime() {
+  startListening()
+
+  async function getConfig(): Promise<RealtimeConfig> {
+    const { invoke } = await import('@tauri-apps/api/core')
+    return invoke<RealtimeConfig>('realtime_get_config')
+  }
+
+  async function setConfig(config: RealtimeConfig): Promise<void> {
+    const { invoke } = await import('@tauri-apps/api/core')
+    await invoke('realtime_set_config', { config })
+  }
+
+  /** Subscribe to Graph change notifications (no-op when disabled in settings). */
+  async function start(): Promise<void> {
+    const { invoke } = await import('@tauri-apps/api/core')
+    status.value = await invoke<RealtimeStatus>('realtime_start', {
+      appId: import.meta.env.VITE_AZURE_CLIENT_ID,
+    })
+  }
+
+  async function stop(): Promise<void> {
+    const { invoke } = await import('@tauri-apps/api/core')
+    await invoke('realtime_stop')
+  }
+
+  return {
+    status: readonly(status),
+    getConfig,
+    setConfig,
+    start,
+    stop,
+  }
+}
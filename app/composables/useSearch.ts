@@ -1,9 +1,12 @@
 import type { PaginatedResponse, Person } from '~/types/graph'
+import type { MessageSearchHit, MessageSearchQuery } from '~/types/search'
 
 export function useSearch() {
   const { graphFetch } = useGraph()
   const results = ref<Person[]>([])
   const loading = ref(false)
+  const messageResults = ref<MessageSearchHit[]>([])
+  const messagesLoading = ref(false)
 
   async function searchPeople(query: string) {
     if (query.length < 2) {
@@ -31,5 +34,27 @@ export function useSearch() {
     }
   }
 
-  return { results, loading, searchPeople }
+  /** Full-text search over the local message cache (Tauri only). */
+  async function searchMessages(query: string | MessageSearchQuery) {
+    const request = typeof query === 'string' ? { text: query } : query
+    if (request.text.trim().length < 2 || !(window as Record<string, unknown>).__TAURI_INTERNALS__) {
+      messageResults.value = []
+      return
+    }
+
+    messagesLoading.value = true
+    try {
+      const { invoke } = await import('@tauri-apps/api/core')
+      messageResults.value = await invoke<MessageSearchHit[]>('search_messages', { query: request })
+    }
+    catch (err: any) {
+      console.error('[useSearch] searchMessages failed:', err)
+      messageResults.value = []
+    }
+    finally {
+      messagesLoading.value = false
+    }
+  }
+
+  return { results, loading, searchPeople, messageResults, messagesLoading, searchMessages }
 }
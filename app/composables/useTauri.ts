@@ -17,6 +17,19 @@ export interface NotificationParams {
   title: string
   body: string
   chatId: string
+  /** Set both for channel messages so clicking opens the channel. */
+  teamId?: string
+  channelId?: string
+  /** The message being announced; enables reply, mark as read and mute. */
+  messageId?: string
+  createdAt?: string
+  /** What notification rules match on; only used along with `messageId`. */
+  senderId?: string
+  senderEmail?: string | null
+  chatType?: 'oneOnOne' | 'group' | 'meeting' | 'channel'
+  /** Full plain-text content; `body` when missing. */
+  text?: string
+  mentionsMe?: boolean
 }
 
 export interface KeychainStoreParams {
@@ -48,6 +61,25 @@ export interface SetTrayUnreadCountParams {
   count: number
 }
 
+/** An unread conversation listed in the tray menu. */
+export interface TrayUnreadChat {
+  /** Chat ID, or `<teamId>:<channelId>` for channels. */
+  chatId: string
+  teamId?: string
+  channelId?: string
+  title: string
+  sender?: string | null
+  preview?: string | null
+}
+
+/** Dock / taskbar badge and attention requests (Rust `DockSettings`). */
+export interface DockSettings {
+  badgeCount: boolean
+  attentionOnMention: boolean
+  /** Messages in 1:1 chats. */
+  attentionOnDirect: boolean
+}
+
 // ── Composable ─────────────────────────────────────────────────────
 
 export function useTauri() {
@@ -64,6 +96,15 @@ export function useTauri() {
       title: params.title,
       body: params.body,
       chatId: params.chatId,
+      teamId: params.teamId,
+      channelId: params.channelId,
+      messageId: params.messageId,
+      createdAt: params.createdAt,
+      senderId: params.senderId,
+      senderEmail: params.senderEmail,
+      chatType: params.chatType,
+      text: params.text,
+      mentionsMe: params.mentionsMe,
     })
   }
 
@@ -123,9 +164,25 @@ export function useTauri() {
 
   // ── System Tray ──
 
-  async function setTrayUnreadCount(count: number): Promise<void> {
+  async function setTrayUnreadCount(count: number, mentions = 0): Promise<void> {
+    if (!isTauri.value) return
+    await invoke('set_tray_unread_count', { count, mentions })
+  }
+
+  /** List unread conversations, most recent first, at the top of the tray menu. */
+  async function setTrayUnreadChats(chats: TrayUnreadChat[]): Promise<void> {
+    if (!isTauri.value) return
+    await invoke('set_tray_unread_chats', { chats })
+  }
+
+  async function getDockSettings(): Promise<DockSettings | null> {
+    if (!isTauri.value) return null
+    return invoke<DockSettings>('get_dock_settings')
+  }
+
+  async function setDockSettings(settings: DockSettings): Promise<void> {
     if (!isTauri.value) return
-    await invoke('set_tray_unread_count', { count })
+    await invoke('set_dock_settings', { settings })
   }
 
   // ── Autostart ──
@@ -175,6 +232,9 @@ export function useTauri() {
 
     // System tray
     setTrayUnreadCount,
+    setTrayUnreadChats,
+    getDockSettings,
+    setDockSettings,
 
     // Autostart
     setAutostart,
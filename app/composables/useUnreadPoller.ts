@@ -1,21 +1,37 @@
+import type { Chat } from '~/types/graph'
+import type { PluginMessage } from '~~/types/plugin'
+
 const CHAT_LIST_INTERVAL = 15_000
 const CHANNEL_PEEK_INTERVAL = 20_000
 const CHANNEL_STAGGER_MS = 500
-const MAX_WATCHED_CHANNELS = 15
+
+interface ChannelRef { teamId: string; channelId: string }
+
+interface SyncNewMessage extends PluginMessage {
+  teamId: string | null
+  channelId: string | null
+}
 
 // Module-level state
-const watchedChannels = ref<Array<{ teamId: string; channelId: string }>>([])
+const watchedChannels = ref<ChannelRef[]>([])
+let syncUnlisteners: Array<() => void> = []
+let syncSession = 0
 
 export function useUnreadPoller() {
   const { refreshChats, chats } = useChats()
   const { peekChannelLatestMessage } = useChannels()
-  const { updateFromChats, updateChannelUnread } = useUnreadStore()
+  const { updateFromChats, updateChannelUnread, applyReadMarker } = useUnreadStore()
   const { currentUserId } = useCurrentUser()
+  const { isTauri } = useTauri()
 
   let chatTimer: ReturnType<typeof setInterval> | null = null
   let channelTimer: ReturnType<typeof setInterval> | null = null
 
   async function pollChats() {
+    if (isTauri.value) {
+      await invokeSync('sync_poll_now')
+      return
+    }
     try {
       await refreshChats()
       updateFromChats(chats.value, currentUserId.value)
@@ -26,7 +42,11 @@ export function useUnreadPoller() {
   }
 
   async function pollChannels() {
-    const channels = watchedChannels.value.slice(0, MAX_WATCHED_CHANNELS)
+    if (isTauri.value) {
+      await invokeSync('sync_poll_now')
+      return
+    }
+    const channels = watchedChannels.value
     for (const ch of channels) {
       try {
         const latest = await peekChannelLatestMessage(ch.teamId, ch.channelId)
@@ -50,12 +70,28 @@ export function useUnreadPoller() {
     }
   }
 
-  function setWatchedChannels(channels: Array<{ teamId: string; channelId: string }>) {
+  function setWatchedChannels(channels: ChannelRef[]) {
     watchedChannels.value = channels
+    if (isTauri.value) {
+      invokeSync('sync_set_watched_channels', {
+        channels: channels.map(c => ({ teamId: c.teamId, channelId: c.channelId })),
+      })
+    }
+  }
+
+  /** Tell the native sync which conversation is open so it doesn't notify for it. */
+  function setActiveConversation(key: string | null) {
+    if (isTauri.value) {
+      invokeSync('sync_set_active_conversation', { key })
+    }
   }
 
   function startPolling() {
     stopPolling()
+    if (isTauri.value) {
+      startNativeSync()
+      return
+    }
     chatTimer = setInterval(pollChats, CHAT_LIST_INTERVAL)
     channelTimer = setInterval(pollChannels, CHANNEL_PEEK_INTERVAL)
   }
@@ -69,6 +105,71 @@ export function useUnreadPoller() {
       clearInterval(channelTimer)
       channelTimer = null
     }
+    if (isTauri.value) {
+      syncSession++
+      for (const unlisten of syncUnlisteners) unlisten()
+      syncUnlisteners = []
+      invokeSync('sync_stop')
+      invokeSync('presence_stop')
+      invokeSync('realtime_stop')
+    }
+  }
+
+  // ── Native sync (Tauri) ──
+  // Polling runs in Rust so it keeps going while the window is hidden; the
+  // webview only mirrors the results into the unread store.
+
+  async function startNativeSync() {
+    const session = ++syncSession
+    const { listen } = await import('@tauri-apps/api/event')
+    const { emitPluginEvent } = usePlugins()
+
+    const unlisteners = await Promise.all([
+      listen<{ chats: Chat[], changed: boolean }>('sync:chats', (event) => {
+        updateFromChats(event.payload.chats, currentUserId.value)
+      }),
+      listen<{ teamId: string, channelId: string, createdDateTime: string, fromUserId: string | null }>(
+        'sync:channel-latest',
+        ({ payload }) => {
+          updateChannelUnread(
+            payload.teamId,
+            payload.channelId,
+            payload.createdDateTime,
+            payload.fromUserId,
+            currentUserId.value,
+          )
+        },
+      ),
+      listen<SyncNewMessage>('sync:new-message', ({ payload }) => {
+        // The native sync has already run it through the notification policy
+        const { teamId: _teamId, channelId: _channelId, ...message } = payload
+        emitPluginEvent('message:received', { ...message, notified: true })
+      }),
+      listen<{ key: string, timestamp: string }>('notifications:marked-read', ({ payload }) => {
+        applyReadMarker(payload.key, payload.timestamp)
+      }),
+      listen<{ source: string, error: { message: string }, retryInMs: number }>('sync:error', ({ payload }) => {
+        console.warn(`[useUnreadPoller] Native ${payload.source} sync failed, retrying in ${payload.retryInMs}ms:`, payload.error.message)
+      }),
+    ])
+
+    // stopPolling() ran while we were subscribing
+    if (session !== syncSession) {
+      for (const unlisten of unlisteners) unlisten()
+      return
+    }
+    syncUnlisteners = unlisteners
+
+    await invokeSync('sync_set_watched_channels', {
+      channels: watchedChannels.value.map(c => ({ teamId: c.teamId, channelId: c.channelId })),
+    })
+    await invokeSync('sync_start', { userId: currentUserId.value })
+    await invokeSync('presence_start', { userId: currentUserId.value })
+
+    // Change notifications only speed the sync up, so failing to subscribe is not fatal
+    useRealtime().start().catch((err) => {
+      console.warn('[useUnreadPoller] Change notifications unavailable:', err)
+    })
   }
 
   return {
@@ -76,7 +177,18 @@ export function useUnreadPoller() {
     startPolling,
     stopPolling,
     setWatchedChannels,
+    setActiveConversation,
     pollChats,
     pollChannels,
   }
 }
+
+async function invokeSync(command: string, args?: Record<string, unknown>) {
+  try {
+    const { invoke } = await import('@tauri-apps/api/core')
+    await invoke(command, args)
+  }
+  catch (err) {
+    console.warn(`[useUnreadPoller] ${command} failed:`, err)
+  }
+}
@@ -181,6 +181,14 @@ function touchReadTimestamp(type: 'chat' | 'channel', id: string, teamId?: strin
   }
   unreadCounts[key] = 0
   schedulePersist()
+  syncMarkRead(key, readTimestamps[key].timestamp)
+}
+
+/** Adopt a read marker the native side already recorded (e.g. "Mark as read" on a notification). */
+function applyReadMarker(key: string, timestamp: string) {
+  readTimestamps[key] = { timestamp, source: 'local' }
+  unreadCounts[key] = 0
+  schedulePersist()
 }
 
 function setExactCount(type: 'chat' | 'channel', id: string, count: number, teamId?: string) {
@@ -203,6 +211,16 @@ function isTauriEnv(): boolean {
   return !!(window as Record<string, unknown>).__TAURI_INTERNALS__
 }
 
+/** Keep the native background sync's read markers in step with ours. */
+function syncMarkRead(key: string, timestamp: string) {
+  if (!isTauriEnv()) return
+  import('@tauri-apps/api/core')
+    .then(({ invoke }) => invoke('sync_mark_read', { key, timestamp }))
+    .catch((err) => {
+      console.warn('[useUnreadStore] sync_mark_read failed:', err)
+    })
+}
+
 async function persist() {
   if (isTauriEnv()) {
     try {
@@ -257,6 +275,7 @@ function markChannelRead(teamId: string, channelId: string) {
   readTimestamps[key] = { timestamp: new Date().toISOString(), source: 'local' }
   unreadCounts[key] = 0
   schedulePersist()
+  syncMarkRead(key, readTimestamps[key].timestamp)
 }
 
 function flush() {
@@ -289,6 +308,7 @@ export function useUnreadStore() {
     readTimestamps[key] = { timestamp: new Date().toISOString(), source: 'local' }
     unreadCounts[key] = 0
     schedulePersist()
+    syncMarkRead(key, readTimestamps[key].timestamp)
 
     // Fire-and-forget Graph API call
     graphFetch(`/chats/${chatId}/markChatReadForUser`, {
@@ -321,6 +341,7 @@ export function useUnreadStore() {
     setExactCount,
     markChatRead,
     markChannelRead,
+    applyReadMarker,
 
     // Lifecycle
     load,
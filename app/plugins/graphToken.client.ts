@@ -0,0 +1,14 @@
+export default defineNuxtPlugin(() => {
+  if (!window.__TAURI__) return
+
+  const { forceTokenRefresh } = useAuth()
+
+  // The Rust Graph client asks for a new token when the current one is rejected or expiring
+  import('@tauri-apps/api/event').then(({ listen }) => {
+    listen('graph:token-refresh-needed', () => {
+      forceTokenRefresh().catch((err) => {
+        console.warn('[graphToken] Token refresh failed:', err)
+      })
+    })
+  })
+})
@@ -1,4 +1,10 @@
 export default defineNuxtPlugin(async () => {
   const { loadBundledPlugins } = usePlugins()
   await loadBundledPlugins()
+
+  const { isTauri } = useTauri()
+  if (isTauri.value) {
+    const { migrateFilterPlugin } = useNotificationPolicy()
+    await migrateFilterPlugin().catch(err => console.error('Failed to migrate notification filters:', err))
+  }
 })
@@ -0,0 +1,304 @@
+//! Log of every notification the app decided on — shown or held back, with
+//! why and which rule fired — for the Activity view. Entries older than
+//! [`RETENTION_DAYS`] are pruned with the first new one after launch, then at
+//! most every [`PRUNE_INTERVAL`].
+
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, SecondsFormat, Utc};
+use rusqlite::types::Value as SqlValue;
+use rusqlite::{params, params_from_iter, Connection, Row};
+use serde::{Deserialize, Serialize};
+
+const DEFAULT_LIMIT: u32 = 50;
+const MAX_LIMIT: u32 = 500;
+const RETENTION_DAYS: i64 = 30;
+const PRUNE_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+static LAST_PRUNED: Mutex<Option<Instant>> = Mutex::new(None);
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NotificationRecord {
+    /// Assigned when recorded; increases with time.
+    pub id: i64,
+    /// RFC 3339, UTC.
+    pub created_at: String,
+    /// Chat ID or `<teamId>:<channelId>`.
+    pub conversation: String,
+    pub chat_id: Option<String>,
+    pub team_id: Option<String>,
+    pub channel_id: Option<String>,
+    pub message_id: Option<String>,
+    pub title: String,
+    pub body: String,
+    pub shown: bool,
+    /// Why it was held back (`quiet_hours`, `chat_muted`, ...).
+    pub reason: Option<String>,
+    /// The notification rule that matched, if any.
+    pub rule_id: Option<String>,
+    pub seen: bool,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct HistoryQuery {
+    /// Chat ID or `<teamId>:<channelId>`.
+    pub conversation: Option<String>,
+    /// Only shown (`true`) or only held back (`false`) notifications.
+    pub shown: Option<bool>,
+    pub unseen_only: bool,
+    /// Entries older than this ID, for paging.
+    pub before_id: Option<i64>,
+    pub limit: Option<u32>,
+}
+
+/// Store `record` (its `id` is ignored) and return it with the assigned ID.
+pub(super) fn record(conn: &Connection, mut record: NotificationRecord) -> rusqlite::Result<NotificationRecord> {
+    conn.execute(
+        "INSERT INTO notification_history
+         (created, conversation, chat_id, team_id, channel_id, message_id, title, body, shown, reason, rule_id, seen)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)",
+        params![
+            record.created_at,
+            record.conversation,
+            record.chat_id,
+            record.team_id,
+            record.channel_id,
+            record.message_id,
+            record.title,
+            record.body,
+            record.shown,
+            record.reason,
+            record.rule_id,
+            record.seen,
+        ],
+    )?;
+    record.id = conn.last_insert_rowid();
+
+    let prune_due = {
+        let mut last = LAST_PRUNED.lock().unwrap();
+        let due = last.is_none_or(|at| at.elapsed() >= PRUNE_INTERVAL);
+        if due {
+            *last = Some(Instant::now());
+        }
+        due
+    };
+    if prune_due {
+        prune(conn, Utc::now())?;
+    }
+    Ok(record)
+}
+
+/// Drop entries older than the retention period as of `now`.
+fn prune(conn: &Connection, now: DateTime<Utc>) -> rusqlite::Result<usize> {
+    let cutoff = now - chrono::Duration::days(RETENTION_DAYS);
+    conn.execute(
+        "DELETE FROM notification_history WHERE created < ?1",
+        params![cutoff.to_rfc3339_opts(SecondsFormat::Secs, true)],
+    )
+}
+
+/// Matching entries, newest first.
+pub(super) fn query(conn: &Connection, query: &HistoryQuery) -> rusqlite::Result<Vec<NotificationRecord>> {
+    let mut filters: Vec<&str> = Vec::new();
+    let mut args: Vec<SqlValue> = Vec::new();
+    if let Some(conversation) = query.conversation.clone().filter(|c| !c.is_empty()) {
+        filters.push("conversation = ?");
+        args.push(SqlValue::Text(conversation));
+    }
+    if let Some(shown) = query.shown {
+        filters.push("shown = ?");
+        args.push(SqlValue::Integer(shown.into()));
+    }
+    if query.unseen_only {
+        filters.push("seen = 0");
+    }
+    if let Some(before_id) = query.before_id {
+        filters.push("id < ?");
+        args.push(SqlValue::Integer(before_id));
+    }
+    args.push(SqlValue::Integer(query.limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT).into()));
+
+    let sql = format!(
+        "SELECT id, created, conversation, chat_id, team_id, channel_id, message_id, title, body, shown, reason,
+                rule_id, seen
+         FROM notification_history {} ORDER BY id DESC LIMIT ?",
+        if filters.is_empty() { String::new() } else { format!("WHERE {}", filters.join(" AND ")) }
+    );
+    let mut stmt = conn.prepare(&sql)?;
+    let rows = stmt.query_map(params_from_iter(args), from_row)?;
+    rows.collect()
+}
+
+/// Mark entries seen: the given IDs, or all of them. Returns how many changed.
+pub(super) fn mark_seen(conn: &Connection, ids: Option<&[i64]>) -> rusqlite::Result<usize> {
+    match ids {
+        Some(ids) => {
+            if ids.is_empty() {
+                return Ok(0);
+            }
+            let placeholders = vec!["?"; ids.len()].join(", ");
+            conn.execute(
+                &format!("UPDATE notification_history SET seen = 1 WHERE seen = 0 AND id IN ({})", placeholders),
+                params_from_iter(ids),
+            )
+        }
+        None => conn.execute("UPDATE notification_history SET seen = 1 WHERE seen = 0", []),
+    }
+}
+
+pub(super) fn mark_conversation_seen(conn: &Connection, conversation: &str) -> rusqlite::Result<usize> {
+    conn.execute(
+        "UPDATE notification_history SET seen = 1 WHERE seen = 0 AND conversation = ?1",
+        params![conversation],
+    )
+}
+
+fn from_row(row: &Row<'_>) -> rusqlite::Result<NotificationRecord> {
+    Ok(NotificationRecord {
+        id: row.get(0)?,
+        created_at: row.get(1)?,
+        conversation: row.get(2)?,
+        chat_id: row.get(3)?,
+        team_id: row.get(4)?,
+        channel_id: row.get(5)?,
+        message_id: row.get(6)?,
+        title: row.get(7)?,
+        body: row.get(8)?,
+        shown: row.get(9)?,
+        reason: row.get(10)?,
+        rule_id: row.get(11)?,
+        seen: row.get(12)?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cache::Cache;
+
+    fn entry(conversation: &str, created_at: &str, shown: bool) -> NotificationRecord {
+        NotificationRecord {
+            id: 0,
+            created_at: created_at.to_string(),
+            conversation: conversation.to_string(),
+            chat_id: Some(conversation.to_string()),
+            team_id: None,
+            channel_id: None,
+            message_id: None,
+            title: "Alice".to_string(),
+            body: "Hello".to_string(),
+            shown,
+            reason: (!shown).then(|| "quiet_hours".to_string()),
+            rule_id: None,
+            seen: false,
+        }
+    }
+
+    /// A history of `count` entries alternating between two chats, with
+    /// every third one held back; returns their IDs, oldest first.
+    fn filled(count: usize) -> (Cache, Vec<i64>) {
+        let cache = Cache::in_memory();
+        let now = Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true);
+        let ids = (0..count)
+            .map(|i| {
+                let conversation = if i % 2 == 0 { "chat-a" } else { "chat-b" };
+                cache.record_notification(entry(conversation, &now, i % 3 != 0)).unwrap().id
+            })
+            .collect();
+        (cache, ids)
+    }
+
+    fn ids(cache: &Cache, query: HistoryQuery) -> Vec<i64> {
+        cache.notification_history(&query).unwrap().into_iter().map(|r| r.id).collect()
+    }
+
+    #[test]
+    fn query_filters_newest_first() {
+        let (cache, all) = filled(6);
+        assert_eq!(ids(&cache, HistoryQuery::default()), all.iter().rev().copied().collect::<Vec<_>>());
+
+        let chat_a = HistoryQuery {
+            conversation: Some("chat-a".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(ids(&cache, chat_a), [all[4], all[2], all[0]]);
+
+        let held_back = HistoryQuery {
+            shown: Some(false),
+            ..Default::default()
+        };
+        assert_eq!(ids(&cache, held_back), [all[3], all[0]]);
+
+        let records = cache.notification_history(&HistoryQuery::default()).unwrap();
+        assert!(records.iter().all(|r| r.shown == r.reason.is_none()));
+    }
+
+    #[test]
+    fn query_pages_with_before_id() {
+        let (cache, all) = filled(5);
+        let page = |before_id| HistoryQuery {
+            before_id,
+            limit: Some(2),
+            ..Default::default()
+        };
+        assert_eq!(ids(&cache, page(None)), [all[4], all[3]]);
+        assert_eq!(ids(&cache, page(Some(all[3]))), [all[2], all[1]]);
+        assert_eq!(ids(&cache, page(Some(all[1]))), [all[0]]);
+        assert!(ids(&cache, page(Some(all[0]))).is_empty());
+    }
+
+    #[test]
+    fn query_clamps_the_limit() {
+        let (cache, _) = filled(MAX_LIMIT as usize + 1);
+        let limit = |limit| HistoryQuery {
+            limit: Some(limit),
+            ..Default::default()
+        };
+        assert_eq!(ids(&cache, limit(0)).len(), 1);
+        assert_eq!(ids(&cache, limit(u32::MAX)).len(), MAX_LIMIT as usize);
+        assert_eq!(ids(&cache, HistoryQuery::default()).len(), DEFAULT_LIMIT as usize);
+    }
+
+    #[test]
+    fn mark_seen_updates_the_given_entries_or_all() {
+        let (cache, all) = filled(4);
+        let unseen = || {
+            ids(
+                &cache,
+                HistoryQuery {
+                    unseen_only: true,
+                    ..Default::default()
+                },
+            )
+        };
+
+        assert_eq!(cache.mark_notifications_seen(Some(&[])).unwrap(), 0);
+        assert_eq!(unseen().len(), 4);
+
+        assert_eq!(cache.mark_notifications_seen(Some(&[all[0], all[2]])).unwrap(), 2);
+        assert_eq!(unseen(), [all[3], all[1]]);
+        // Already seen entries don't count again
+        assert_eq!(cache.mark_notifications_seen(Some(&[all[0], all[1]])).unwrap(), 1);
+
+        assert_eq!(cache.mark_notifications_seen(None).unwrap(), 1);
+        assert!(unseen().is_empty());
+    }
+
+    #[test]
+    fn prune_drops_entries_past_retention() {
+        let cache = Cache::in_memory();
+        let now = Utc::now();
+        let days_ago = |days| (now - chrono::Duration::days(days)).to_rfc3339_opts(SecondsFormat::Secs, true);
+        // Recording may already prune, depending on when it last did
+        let old = cache.record_notification(entry("chat-a", &days_ago(RETENTION_DAYS + 1), true)).unwrap();
+        let kept = cache.record_notification(entry("chat-a", &days_ago(RETENTION_DAYS - 1), true)).unwrap();
+
+        cache.with(|conn| prune(conn, now)).unwrap();
+        let left = ids(&cache, HistoryQuery::default());
+        assert!(!left.contains(&old.id));
+        assert!(left.contains(&kept.id));
+    }
+}
@@ -0,0 +1,485 @@
+//! Encrypted on-disk cache of chats, channels, messages, members and read
+//! markers, plus a full-text index over the messages (see `search`), the
+//! queue of unsent messages (see `crate::outbox`) and the notification
+//! history (see `history`).
+//!
+//! Backed by SQLCipher in the app data dir; the key lives in the keychain, so
+//! the file is useless on its own. Rows keep the full Graph JSON in `data`
+//! plus the handful of columns we query on. The cache is disposable: if it
+//! can't be opened (or the key no longer matches) it is recreated or disabled
+//! and the app falls back to the network.
+
+mod history;
+mod schema;
+mod search;
+
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+use chrono::{DateTime, Utc};
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::Serialize;
+use serde_json::Value;
+use tauri::{AppHandle, Manager};
+
+use crate::commands::keychain::get_or_create_secret;
+
+pub use history::{HistoryQuery, NotificationRecord};
+pub use search::{SearchHit, SearchQuery};
+
+const DB_FILE: &str = "cache.db";
+const KEYCHAIN_KEY: &str = "cache-db-key";
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReadMarker {
+    pub timestamp: String,
+    pub source: String,
+}
+
+/// Handle to the cache database. Cheap to clone; held in managed state.
+#[derive(Clone)]
+pub struct Cache {
+    conn: Option<Arc<Mutex<Connection>>>,
+}
+
+impl Cache {
+    /// Open (or create) the cache. Never fails: problems are logged and the
+    /// returned cache is disabled.
+    pub fn open(app: &AppHandle) -> Self {
+        match Self::try_open(app) {
+            Ok(conn) => Self {
+                conn: Some(Arc::new(Mutex::new(conn))),
+            },
+            Err(e) => {
+                eprintln!("[cache] Disabled: {}", e);
+                Self { conn: None }
+            }
+        }
+    }
+
+    fn try_open(app: &AppHandle) -> Result<Connection, String> {
+        let dir = app
+            .path()
+            .app_data_dir()
+            .map_err(|e| format!("No app data dir: {}", e))?;
+        std::fs::create_dir_all(&dir).map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+        let key = get_or_create_secret(KEYCHAIN_KEY, || {
+            rand::random::<[u8; 32]>().iter().map(|b| format!("{:02x}", b)).collect()
+        })?;
+
+        let path = dir.join(DB_FILE);
+        match open_encrypted(&path, &key) {
+            Ok(conn) => Ok(conn),
+            // Wrong key (e.g. keychain reset) or corrupt file — start over
+            Err(e) => {
+                eprintln!("[cache] Recreating cache after open failed: {}", e);
+                for suffix in ["", "-wal", "-shm"] {
+                    let _ = std::fs::remove_file(format!("{}{}", path.display(), suffix));
+                }
+                open_encrypted(&path, &key)
+            }
+        }
+    }
+
+    pub fn is_available(&self) -> bool {
+        self.conn.is_some()
+    }
+
+    fn with<T>(&self, f: impl FnOnce(&mut Connection) -> rusqlite::Result<T>) -> Result<T, String> {
+        let conn = self.conn.as_ref().ok_or("Cache unavailable")?;
+        let mut conn = conn.lock().unwrap();
+        f(&mut conn).map_err(|e| format!("Cache error: {}", e))
+    }
+
+    // ── Chats ──
+
+    /// Insert or replace chats, and their members when the chat was fetched
+    /// with `$expand=members`.
+    pub fn put_chats(&self, chats: &[Value]) -> Result<(), String> {
+        self.with(|conn| {
+            let tx = conn.transaction()?;
+            for chat in chats {
+                let Some(id) = str_field(chat, "id") else {
+                    continue;
+                };
+                tx.execute(
+                    "INSERT OR REPLACE INTO chats (id, chat_type, topic, last_updated, data)
+                     VALUES (?1, ?2, ?3, ?4, ?5)",
+                    params![
+                        id,
+                        str_field(chat, "chatType").unwrap_or("group"),
+                        str_field(chat, "topic"),
+                        last_activity(chat),
+                        chat.to_string(),
+                    ],
+                )?;
+                if let Some(members) = chat.get("members").and_then(|m| m.as_array()) {
+                    replace_members(&tx, id, members)?;
+                }
+            }
+            tx.commit()
+        })
+    }
+
+    /// Cached chats, most recently active first.
+    pub fn chats(&self) -> Result<Vec<Value>, String> {
+        self.with(|conn| {
+            let mut stmt =
+                conn.prepare("SELECT data FROM chats ORDER BY last_updated DESC NULLS LAST")?;
+            let rows = stmt.query_map([], |row| row.get::<_, String>(0))?;
+            Ok(rows.filter_map(|r| r.ok()).filter_map(|s| serde_json::from_str(&s).ok()).collect())
+        })
+    }
+
+    // ── Channels ──
+
+    /// Replace the cached channel list of a team.
+    pub fn put_channels(&self, team_id: &str, channels: &[Value]) -> Result<(), String> {
+        self.with(|conn| {
+            let tx = conn.transaction()?;
+            tx.execute("DELETE FROM channels WHERE team_id = ?1", params![team_id])?;
+            for channel in channels {
+                let Some(id) = str_field(channel, "id") else {
+                    continue;
+                };
+                tx.execute(
+                    "INSERT INTO channels (team_id, id, display_name, data) VALUES (?1, ?2, ?3, ?4)",
+                    params![team_id, id, str_field(channel, "displayName"), channel.to_string()],
+                )?;
+            }
+            tx.commit()
+        })
+    }
+
+    pub fn channels(&self, team_id: &str) -> Result<Vec<Value>, String> {
+        self.with(|conn| {
+            let mut stmt = conn.prepare(
+                "SELECT data FROM channels WHERE team_id = ?1 ORDER BY display_name COLLATE NOCASE",
+            )?;
+            let rows = stmt.query_map(params![team_id], |row| row.get::<_, String>(0))?;
+            Ok(rows.filter_map(|r| r.ok()).filter_map(|s| serde_json::from_str(&s).ok()).collect())
+        })
+    }
+
+    // ── Members ──
+
+    pub fn members(&self, conversation: &str) -> Result<Vec<Value>, String> {
+        self.with(|conn| {
+            let mut stmt = conn.prepare(
+                "SELECT data FROM members WHERE conversation = ?1 ORDER BY display_name COLLATE NOCASE",
+            )?;
+            let rows = stmt.query_map(params![conversation], |row| row.get::<_, String>(0))?;
+            Ok(rows.filter_map(|r| r.ok()).filter_map(|s| serde_json::from_str(&s).ok()).collect())
+        })
+    }
+
+    // ── Messages ──
+
+    /// Insert or replace messages of a conversation (chat ID or `<teamId>:<channelId>`).
+    pub fn put_messages(&self, conversation: &str, messages: &[Value]) -> Result<(), String> {
+        self.with(|conn| {
+            let tx = conn.transaction()?;
+            for message in messages {
+                let (Some(id), Some(created)) =
+                    (str_field(message, "id"), str_field(message, "createdDateTime"))
+                else {
+                    continue;
+                };
+                let channel = message.get("channelIdentity");
+                tx.execute(
+                    "INSERT OR REPLACE INTO messages
+                        (conversation, id, chat_id, team_id, channel_id, reply_to_id, sender_id,
+                         created, modified, deleted, has_attachments, data)
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)",
+                    params![
+                        conversation,
+                        id,
+                        str_field(message, "chatId"),
+                        channel.and_then(|c| str_field(c, "teamId")),
+                        channel.and_then(|c| str_field(c, "channelId")),
+                        str_field(message, "replyToId"),
+                        message.pointer("/from/user/id").and_then(|v| v.as_str()),
+                        created,
+                        str_field(message, "lastModifiedDateTime"),
+                        str_field(message, "deletedDateTime").is_some(),
+                        search::has_attachments(message),
+                        message.to_string(),
+                    ],
+                )?;
+                search::index_message(&tx, conversation, id, message)?;
+            }
+            tx.commit()
+        })
+    }
+
+    pub fn remove_messages(&self, conversation: &str, ids: &[String]) -> Result<(), String> {
+        if ids.is_empty() {
+            return Ok(());
+        }
+        self.with(|conn| {
+            let tx = conn.transaction()?;
+            for id in ids {
+                tx.execute(
+                    "DELETE FROM messages WHERE conversation = ?1 AND id = ?2",
+                    params![conversation, id],
+                )?;
+                search::unindex_message(&tx, conversation, id)?;
+            }
+            tx.commit()
+        })
+    }
+
+    /// The newest `limit` top-level messages older than `before` (if given),
+    /// returned oldest first like the UI renders them.
+    pub fn messages(
+        &self,
+        conversation: &str,
+        before: Option<&str>,
+        limit: u32,
+    ) -> Result<Vec<Value>, String> {
+        self.with(|conn| {
+            let mut stmt = conn.prepare(
+                "SELECT data FROM messages
+                 WHERE conversation = ?1 AND reply_to_id IS NULL AND (?2 IS NULL OR created < ?2)
+                 ORDER BY created DESC
+                 LIMIT ?3",
+            )?;
+            let rows = stmt.query_map(params![conversation, before, limit], |row| {
+                row.get::<_, String>(0)
+            })?;
+            let mut messages: Vec<Value> = rows
+                .filter_map(|r| r.ok())
+                .filter_map(|s| serde_json::from_str(&s).ok())
+                .collect();
+            messages.reverse();
+            Ok(messages)
+        })
+    }
+
+    /// A single cached message, replies included.
+    pub fn message(&self, conversation: &str, id: &str) -> Result<Option<Value>, String> {
+        self.with(|conn| {
+            let data: Option<String> = conn
+                .query_row(
+                    "SELECT data FROM messages WHERE conversation = ?1 AND id = ?2",
+                    params![conversation, id],
+                    |row| row.get(0),
+                )
+                .optional()?;
+            Ok(data.and_then(|s| serde_json::from_str(&s).ok()))
+        })
+    }
+
+    /// Ranked full-text search over cached messages.
+    pub fn search_messages(&self, query: &SearchQuery) -> Result<Vec<SearchHit>, String> {
+        self.with(|conn| search::search(conn, query))
+    }
+
+    // ── Read markers ──
+
+    /// Record a read marker, keeping whichever timestamp is later. Graph
+    /// timestamps vary in precision, so they are compared parsed rather than
+    /// as strings; unparseable ones sort before everything.
+    pub fn set_read_marker(&self, conversation: &str, timestamp: &str, source: &str) -> Result<(), String> {
+        self.with(|conn| {
+            let current: Option<String> = conn
+                .query_row(
+                    "SELECT timestamp FROM read_markers WHERE conversation = ?1",
+                    params![conversation],
+                    |row| row.get(0),
+                )
+                .optional()?;
+            if current.is_some_and(|current| parse_time(timestamp) <= parse_time(&current)) {
+                return Ok(());
+            }
+            conn.execute(
+                "INSERT OR REPLACE INTO read_markers (conversation, timestamp, source) VALUES (?1, ?2, ?3)",
+                params![conversation, timestamp, source],
+            )?;
+            Ok(())
+        })
+    }
+
+    pub fn read_markers(&self) -> Result<HashMap<String, ReadMarker>, String> {
+        self.with(|conn| {
+            let mut stmt = conn.prepare("SELECT conversation, timestamp, source FROM read_markers")?;
+            let rows = stmt.query_map([], |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    ReadMarker {
+                        timestamp: row.get(1)?,
+                        source: row.get(2)?,
+                    },
+                ))
+            })?;
+            Ok(rows.filter_map(|r| r.ok()).collect())
+        })
+    }
+
+    // ── Outbox ──
+
+    /// Queued outgoing messages, oldest first.
+    pub fn outbox_items(&self) -> Result<Vec<Value>, String> {
+        self.with(|conn| {
+            let mut stmt = conn.prepare("SELECT data FROM outbox ORDER BY created")?;
+            let rows = stmt.query_map([], |row| row.get::<_, String>(0))?;
+            Ok(rows.filter_map(|r| r.ok()).filter_map(|s| serde_json::from_str(&s).ok()).collect())
+        })
+    }
+
+    pub fn put_outbox_item(&self, id: &str, created: &str, item: &Value) -> Result<(), String> {
+        self.with(|conn| {
+            conn.execute(
+                "INSERT OR REPLACE INTO outbox (id, created, data) VALUES (?1, ?2, ?3)",
+                params![id, created, item.to_string()],
+            )?;
+            Ok(())
+        })
+    }
+
+    pub fn remove_outbox_item(&self, id: &str) -> Result<(), String> {
+        self.with(|conn| {
+            conn.execute("DELETE FROM outbox WHERE id = ?1", params![id])?;
+            Ok(())
+        })
+    }
+
+    // ── Notification history ──
+
+    pub fn record_notification(&self, record: NotificationRecord) -> Result<NotificationRecord, String> {
+        self.with(|conn| history::record(conn, record))
+    }
+
+    pub fn notification_history(&self, query: &HistoryQuery) -> Result<Vec<NotificationRecord>, String> {
+        self.with(|conn| history::query(conn, query))
+    }
+
+    /// Mark history entries seen: the given IDs, or all of them.
+    pub fn mark_notifications_seen(&self, ids: Option<&[i64]>) -> Result<usize, String> {
+        self.with(|conn| history::mark_seen(conn, ids))
+    }
+
+    pub fn mark_conversation_notifications_seen(&self, conversation: &str) -> Result<usize, String> {
+        self.with(|conn| history::mark_conversation_seen(conn, conversation))
+    }
+
+    /// Drop everything (e.g. on sign-out).
+    pub fn clear(&self) -> Result<(), String> {
+        self.with(|conn| {
+            conn.execute_batch(
+                "DELETE FROM message_index; DELETE FROM messages; DELETE FROM members; DELETE FROM channels;
+                 DELETE FROM chats; DELETE FROM read_markers; DELETE FROM outbox;
+                 DELETE FROM notification_history; VACUUM;",
+            )
+        })
+    }
+}
+
+fn open_encrypted(path: &Path, key: &str) -> Result<Connection, String> {
+    let mut conn = Connection::open(path).map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+    conn.pragma_update(None, "key", format!("x'{}'", key))
+        .map_err(|e| format!("Failed to set cache key: {}", e))?;
+    // A wrong key only surfaces on first read
+    conn.query_row("SELECT count(*) FROM sqlite_master", [], |row| row.get::<_, i64>(0))
+        .map_err(|e| format!("Cache key rejected: {}", e))?;
+    conn.pragma_update(None, "journal_mode", "WAL")
+        .map_err(|e| format!("Failed to enable WAL: {}", e))?;
+    let previous = schema::migrate(&conn).map_err(|e| format!("Failed to migrate cache: {}", e))?;
+    // Messages cached before the search index existed
+    if previous > 0 && previous < 2 {
+        search::rebuild(&mut conn).map_err(|e| format!("Failed to build search index: {}", e))?;
+    }
+    Ok(conn)
+}
+
+fn replace_members(tx: &rusqlite::Transaction<'_>, conversation: &str, members: &[Value]) -> rusqlite::Result<()> {
+    tx.execute("DELETE FROM members WHERE conversation = ?1", params![conversation])?;
+    for member in members {
+        let Some(id) = str_field(member, "id") else {
+            continue;
+        };
+        tx.execute(
+            "INSERT INTO members (conversation, id, user_id, display_name, data) VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![
+                conversation,
+                id,
+                str_field(member, "userId"),
+                str_field(member, "displayName"),
+                member.to_string(),
+            ],
+        )?;
+    }
+    Ok(())
+}
+
+/// Sort key for a chat: its latest message, falling back to its last update.
+fn last_activity(chat: &Value) -> Option<&str> {
+    chat.pointer("/lastMessagePreview/createdDateTime")
+        .and_then(|v| v.as_str())
+        .or_else(|| str_field(chat, "lastUpdatedDateTime"))
+}
+
+fn str_field<'a>(value: &'a Value, field: &str) -> Option<&'a str> {
+    value.get(field).and_then(|v| v.as_str())
+}
+
+fn parse_time(timestamp: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(timestamp).ok().map(|t| t.with_timezone(&Utc))
+}
+
+#[cfg(test)]
+impl Cache {
+    /// Unencrypted in-memory cache with the current schema.
+    fn in_memory() -> Self {
+        let conn = Connection::open_in_memory().unwrap();
+        schema::migrate(&conn).unwrap();
+        Self {
+            conn: Some(Arc::new(Mutex::new(conn))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn marker(cache: &Cache, conversation: &str) -> String {
+        cache.read_markers().unwrap()[conversation].timestamp.clone()
+    }
+
+    #[test]
+    fn read_marker_keeps_the_later_time_across_precisions() {
+        let cache = Cache::in_memory();
+        cache.set_read_marker("chat", "2026-10-18T09:00:00.500Z", "local").unwrap();
+        // Earlier, but sorts after as a string
+        cache.set_read_marker("chat", "2026-10-18T09:00:00Z", "remote").unwrap();
+        assert_eq!(marker(&cache, "chat"), "2026-10-18T09:00:00.500Z");
+
+        // Later, but sorts before as a string
+        cache.set_read_marker("chat", "2026-10-18T09:00:01Z", "remote").unwrap();
+        assert_eq!(marker(&cache, "chat"), "2026-10-18T09:00:01Z");
+        assert_eq!(cache.read_markers().unwrap()["chat"].source, "remote");
+    }
+
+    #[test]
+    fn read_marker_compares_offsets() {
+        let cache = Cache::in_memory();
+        cache.set_read_marker("chat", "2026-10-18T09:00:00Z", "local").unwrap();
+        cache.set_read_marker("chat", "2026-10-18T10:30:00+02:00", "remote").unwrap();
+        assert_eq!(marker(&cache, "chat"), "2026-10-18T09:00:00Z");
+        cache.set_read_marker("chat", "2026-10-18T05:30:00-04:00", "remote").unwrap();
+        assert_eq!(marker(&cache, "chat"), "2026-10-18T05:30:00-04:00");
+    }
+
+    #[test]
+    fn read_marker_replaces_unparseable_times() {
+        let cache = Cache::in_memory();
+        cache.set_read_marker("chat", "garbage", "local").unwrap();
+        assert_eq!(marker(&cache, "chat"), "garbage");
+        cache.set_read_marker("chat", "2026-10-18T09:00:00Z", "local").unwrap();
+        cache.set_read_marker("chat", "garbage", "local").unwrap();
+        assert_eq!(marker(&cache, "chat"), "2026-10-18T09:00:00Z");
+    }
+}
@@ -0,0 +1,107 @@
+use rusqlite::Connection;
+
+/// Ordered migrations; `PRAGMA user_version` records how many have run.
+const MIGRATIONS: &[&str] = &[
+    // 1: initial schema
+    "
+    CREATE TABLE chats (
+        id              TEXT PRIMARY KEY,
+        chat_type       TEXT NOT NULL,
+        topic           TEXT,
+        last_updated    TEXT,
+        data            TEXT NOT NULL
+    );
+
+    CREATE TABLE channels (
+        team_id         TEXT NOT NULL,
+        id              TEXT NOT NULL,
+        display_name    TEXT,
+        data            TEXT NOT NULL,
+        PRIMARY KEY (team_id, id)
+    );
+
+    CREATE TABLE members (
+        conversation    TEXT NOT NULL,
+        id              TEXT NOT NULL,
+        user_id         TEXT,
+        display_name    TEXT,
+        data            TEXT NOT NULL,
+        PRIMARY KEY (conversation, id)
+    );
+
+    CREATE TABLE messages (
+        conversation    TEXT NOT NULL,
+        id              TEXT NOT NULL,
+        chat_id         TEXT,
+        team_id         TEXT,
+        channel_id      TEXT,
+        reply_to_id     TEXT,
+        sender_id       TEXT,
+        created         TEXT NOT NULL,
+        modified        TEXT,
+        deleted         INTEGER NOT NULL DEFAULT 0,
+        data            TEXT NOT NULL,
+        PRIMARY KEY (conversation, id)
+    );
+    CREATE INDEX messages_by_time ON messages (conversation, created);
+
+    CREATE TABLE read_markers (
+        conversation    TEXT PRIMARY KEY,
+        timestamp       TEXT NOT NULL,
+        source          TEXT NOT NULL
+    );
+    ",
+    // 2: full-text search (populated from Rust, see `search::index_message`)
+    "
+    ALTER TABLE messages ADD COLUMN has_attachments INTEGER NOT NULL DEFAULT 0;
+
+    CREATE VIRTUAL TABLE message_index USING fts5(
+        body,
+        sender,
+        conversation UNINDEXED,
+        message_id UNINDEXED,
+        tokenize = 'unicode61 remove_diacritics 2',
+        prefix = '2 3'
+    );
+    ",
+    // 3: outgoing messages waiting to be sent (see `crate::outbox`)
+    "
+    CREATE TABLE outbox (
+        id              TEXT PRIMARY KEY,
+        created         TEXT NOT NULL,
+        data            TEXT NOT NULL
+    );
+    ",
+    // 4: notifications shown or held back (see `history`)
+    "
+    CREATE TABLE notification_history (
+        id              INTEGER PRIMARY KEY AUTOINCREMENT,
+        created         TEXT NOT NULL,
+        conversation    TEXT NOT NULL,
+        chat_id         TEXT,
+        team_id         TEXT,
+        channel_id      TEXT,
+        message_id      TEXT,
+        title           TEXT NOT NULL,
+        body            TEXT NOT NULL,
+        shown           INTEGER NOT NULL,
+        reason          TEXT,
+        rule_id         TEXT,
+        seen            INTEGER NOT NULL DEFAULT 0
+    );
+    CREATE INDEX notification_history_by_time ON notification_history (created);
+    CREATE INDEX notification_history_by_conversation ON notification_history (conversation, seen);
+    ",
+];
+
+/// Run pending migrations, returning the schema version the file had before.
+pub fn migrate(conn: &Connection) -> rusqlite::Result<usize> {
+    let version: usize = conn.query_row("PRAGMA user_version", [], |row| row.get(0))?;
+    for (index, migration) in MIGRATIONS.iter().enumerate().skip(version) {
+        let tx = conn.unchecked_transaction()?;
+        tx.execute_batch(migration)?;
+        tx.pragma_update(None, "user_version", index + 1)?;
+        tx.commit()?;
+    }
+    Ok(version)
+}
@@ -0,0 +1,465 @@
+//! Full-text search over cached messages.
+//!
+//! `message_index` is an FTS5 table holding the plain-text body and sender
+//! name of every cached user message. The `unicode61 remove_diacritics 2`
+//! tokenizer folds case and accents, and every term is matched as a prefix,
+//! so "cafe pre" finds "Café prêt". Structured filters live on `messages`
+//! and are joined in.
+
+use rusqlite::types::Value as SqlValue;
+use rusqlite::{params, params_from_iter, Connection, Transaction};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::graph::types::strip_html;
+
+const DEFAULT_LIMIT: u32 = 50;
+const MAX_LIMIT: u32 = 200;
+/// Tokens of context around the best match in a snippet.
+const SNIPPET_TOKENS: u32 = 16;
+/// Private-use characters bracketing matches in FTS5 snippets; turned into
+/// `highlights` ranges before anything reaches the UI.
+const MARK_START: char = '\u{E000}';
+const MARK_END: char = '\u{E001}';
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct SearchQuery {
+    /// Free text. Also accepts inline filters: `from:`, `in:`, `after:`,
+    /// `before:` and `has:attachment`; quoted text matches as a phrase.
+    pub text: String,
+    /// Sender user ID, or (a prefix of) their display name.
+    pub sender: Option<String>,
+    /// Chat ID or `<teamId>:<channelId>`.
+    pub conversation: Option<String>,
+    /// Inclusive lower bound, ISO date or date-time.
+    pub after: Option<String>,
+    /// Exclusive upper bound, ISO date or date-time.
+    pub before: Option<String>,
+    pub has_attachment: bool,
+    pub limit: Option<u32>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchHit {
+    pub conversation: String,
+    pub message_id: String,
+    pub chat_id: Option<String>,
+    pub team_id: Option<String>,
+    pub channel_id: Option<String>,
+    pub reply_to_id: Option<String>,
+    pub sender_id: Option<String>,
+    pub sender_name: String,
+    pub created_date_time: String,
+    pub has_attachments: bool,
+    /// Plain-text excerpt around the best match.
+    pub snippet: String,
+    /// `[start, end)` ranges of matched text in `snippet`, in UTF-16 code
+    /// units so they can be used with JS string offsets directly.
+    pub highlights: Vec<[usize; 2]>,
+    /// Relevance, higher is better (0 when the query had no text).
+    pub score: f64,
+}
+
+/// (Re)index one message. System events and deleted messages are dropped
+/// from the index.
+pub(super) fn index_message(
+    tx: &Transaction<'_>,
+    conversation: &str,
+    id: &str,
+    message: &Value,
+) -> rusqlite::Result<()> {
+    unindex_message(tx, conversation, id)?;
+
+    let is_user_message = message.get("messageType").and_then(|v| v.as_str()).unwrap_or("message") == "message";
+    let is_deleted = message.get("deletedDateTime").is_some_and(|v| !v.is_null());
+    if !is_user_message || is_deleted {
+        return Ok(());
+    }
+
+    let content = message.pointer("/body/content").and_then(|v| v.as_str()).unwrap_or("");
+    let is_html = message
+        .pointer("/body/contentType")
+        .and_then(|v| v.as_str())
+        .is_some_and(|t| t.eq_ignore_ascii_case("html"));
+    let body = if is_html { strip_html(content) } else { content.trim().to_string() };
+    let sender = message
+        .pointer("/from/user/displayName")
+        .or_else(|| message.pointer("/from/application/displayName"))
+        .and_then(|v| v.as_str())
+        .unwrap_or("");
+
+    tx.execute(
+        "INSERT INTO message_index (body, sender, conversation, message_id) VALUES (?1, ?2, ?3, ?4)",
+        params![body, sender, conversation, id],
+    )?;
+    Ok(())
+}
+
+pub(super) fn unindex_message(tx: &Transaction<'_>, conversation: &str, id: &str) -> rusqlite::Result<()> {
+    tx.execute(
+        "DELETE FROM message_index WHERE conversation = ?1 AND message_id = ?2",
+        params![conversation, id],
+    )?;
+    Ok(())
+}
+
+/// Rebuild the index from the `messages` table, e.g. after the migration
+/// that introduced it.
+pub(super) fn rebuild(conn: &mut Connection) -> rusqlite::Result<()> {
+    let tx = conn.transaction()?;
+    tx.execute("DELETE FROM message_index", [])?;
+    let rows: Vec<(String, String, String)> = {
+        let mut stmt = tx.prepare("SELECT conversation, id, data FROM messages")?;
+        let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))?;
+        rows.filter_map(|r| r.ok()).collect()
+    };
+    for (conversation, id, data) in rows {
+        let Ok(message) = serde_json::from_str::<Value>(&data) else {
+            continue;
+        };
+        tx.execute(
+            "UPDATE messages SET has_attachments = ?3 WHERE conversation = ?1 AND id = ?2",
+            params![conversation, id, has_attachments(&message)],
+        )?;
+        index_message(&tx, &conversation, &id, &message)?;
+    }
+    tx.commit()
+}
+
+/// Whether a message carries real attachments (quoted replies are
+/// `messageReference` attachments and don't count).
+pub(super) fn has_attachments(message: &Value) -> bool {
+    message
+        .get("attachments")
+        .and_then(|a| a.as_array())
+        .is_some_and(|a| {
+            a.iter()
+                .any(|att| att.get("contentType").and_then(|t| t.as_str()) != Some("messageReference"))
+        })
+}
+
+pub(super) fn search(conn: &Connection, query: &SearchQuery) -> rusqlite::Result<Vec<SearchHit>> {
+    let query = parse_inline_filters(query);
+    let mut terms: Vec<String> = Vec::new();
+    let mut filters: Vec<&str> = Vec::new();
+    let mut args: Vec<SqlValue> = Vec::new();
+
+    let body_terms = fts_terms(&query.text);
+    if !body_terms.is_empty() {
+        terms.push(format!("body : ({})", body_terms.join(" AND ")));
+    }
+    match query.sender.as_deref().map(str::trim).filter(|s| !s.is_empty()) {
+        Some(sender) if looks_like_id(sender) => {
+            filters.push("m.sender_id = ?");
+            args.push(SqlValue::Text(sender.to_string()));
+        }
+        Some(sender) => {
+            let name_terms = fts_terms(sender);
+            if !name_terms.is_empty() {
+                terms.push(format!("sender : ({})", name_terms.join(" AND ")));
+            }
+        }
+        None => {}
+    }
+    if let Some(conversation) = query.conversation.filter(|c| !c.is_empty()) {
+        filters.push("m.conversation = ?");
+        args.push(SqlValue::Text(conversation));
+    }
+    if let Some(after) = query.after.filter(|a| !a.is_empty()) {
+        filters.push("m.created >= ?");
+        args.push(SqlValue::Text(after));
+    }
+    if let Some(before) = query.before.filter(|b| !b.is_empty()) {
+        filters.push("m.created < ?");
+        args.push(SqlValue::Text(before));
+    }
+    if query.has_attachment {
+        filters.push("m.has_attachments = 1");
+    }
+
+    let has_match = !terms.is_empty();
+    if !has_match && filters.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let (snippet, score, order) = if has_match {
+        (
+            format!(
+                "snippet(message_index, 0, char({}), char({}), '…', {})",
+                MARK_START as u32, MARK_END as u32, SNIPPET_TOKENS
+            ),
+            "-bm25(message_index, 1.0, 0.5)",
+            "bm25(message_index, 1.0, 0.5), m.created DESC",
+        )
+    } else {
+        ("substr(message_index.body, 1, 200)".to_string(), "0.0", "m.created DESC")
+    };
+
+    let mut conditions = Vec::new();
+    if has_match {
+        conditions.push("message_index MATCH ?");
+        args.insert(0, SqlValue::Text(terms.join(" AND ")));
+    }
+    conditions.extend(filters);
+    args.push(SqlValue::Integer(query.limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT).into()));
+
+    let sql = format!(
+        "SELECT m.conversation, m.id, m.chat_id, m.team_id, m.channel_id, m.reply_to_id, m.sender_id,
+                message_index.sender, m.created, m.has_attachments, {snippet}, {score}
+         FROM message_index
+         JOIN messages m ON m.conversation = message_index.conversation AND m.id = message_index.message_id
+         WHERE {}
+         ORDER BY {order}
+         LIMIT ?",
+        conditions.join(" AND "),
+    );
+
+    let mut stmt = conn.prepare(&sql)?;
+    let rows = stmt.query_map(params_from_iter(args), |row| {
+        let (snippet, highlights) = extract_highlights(&row.get::<_, String>(10)?);
+        Ok(SearchHit {
+            conversation: row.get(0)?,
+            message_id: row.get(1)?,
+            chat_id: row.get(2)?,
+            team_id: row.get(3)?,
+            channel_id: row.get(4)?,
+            reply_to_id: row.get(5)?,
+            sender_id: row.get(6)?,
+            sender_name: row.get(7)?,
+            created_date_time: row.get(8)?,
+            has_attachments: row.get(9)?,
+            snippet,
+            highlights,
+            score: row.get(11)?,
+        })
+    })?;
+    rows.collect()
+}
+
+/// Pull `from:`, `in:`, `after:`, `before:` and `has:attachment` out of the
+/// free text. Explicit fields on the query win over inline ones.
+fn parse_inline_filters(query: &SearchQuery) -> SearchQuery {
+    let mut parsed = SearchQuery {
+        text: String::new(),
+        ..query.clone()
+    };
+    let mut words = Vec::new();
+    for token in split_tokens(&query.text) {
+        let (key, value) = token.split_once(':').unwrap_or(("", &token));
+        let value = value.trim_matches('"').to_string();
+        match key.to_ascii_lowercase().as_str() {
+            "from" if !value.is_empty() => {
+                parsed.sender.get_or_insert(value);
+            }
+            "in" if !value.is_empty() => {
+                parsed.conversation.get_or_insert(value);
+            }
+            "after" if !value.is_empty() => {
+                parsed.after.get_or_insert(value);
+            }
+            "before" if !value.is_empty() => {
+                parsed.before.get_or_insert(value);
+            }
+            "has" if value.eq_ignore_ascii_case("attachment") || value.eq_ignore_ascii_case("attachments") => {
+                parsed.has_attachment = true;
+            }
+            _ => words.push(token),
+        }
+    }
+    parsed.text = words.join(" ");
+    parsed
+}
+
+/// Split on whitespace, keeping `"quoted phrases"` (and `key:"quoted value"`) together.
+fn split_tokens(text: &str) -> Vec<String> {
+    let mut tokens = Vec::new();
+    let mut current = String::new();
+    let mut quoted = false;
+    for c in text.chars() {
+        match c {
+            '"' => {
+                quoted = !quoted;
+                current.push(c);
+            }
+            c if c.is_whitespace() && !quoted => {
+                if !current.is_empty() {
+                    tokens.push(std::mem::take(&mut current));
+                }
+            }
+            c => current.push(c),
+        }
+    }
+    if !current.is_empty() {
+        tokens.push(current);
+    }
+    tokens
+}
+
+/// Each token becomes an FTS5 prefix phrase (`"term"*`). Tokens without any
+/// letters or digits would be empty phrases to the tokenizer, so they're dropped.
+fn fts_terms(text: &str) -> Vec<String> {
+    split_tokens(text)
+        .into_iter()
+        .map(|t| t.replace('"', " ").trim().to_string())
+        .filter(|t| t.chars().any(char::is_alphanumeric))
+        .map(|t| format!("\"{}\"*", t))
+        .collect()
+}
+
+/// Azure AD object IDs are GUIDs; anything else is treated as a name.
+fn looks_like_id(value: &str) -> bool {
+    value.len() == 36
+        && value
+            .chars()
+            .enumerate()
+            .all(|(i, c)| if matches!(i, 8 | 13 | 18 | 23) { c == '-' } else { c.is_ascii_hexdigit() })
+}
+
+/// Strip the snippet markers, returning the clean text and the UTF-16
+/// ranges they enclosed.
+fn extract_highlights(marked: &str) -> (String, Vec<[usize; 2]>) {
+    let mut text = String::with_capacity(marked.len());
+    let mut highlights = Vec::new();
+    let mut offset = 0;
+    let mut start = None;
+    for c in marked.chars() {
+        match c {
+            MARK_START => start = Some(offset),
+            MARK_END => {
+                if let Some(s) = start.take() {
+                    highlights.push([s, offset]);
+                }
+            }
+            c => {
+                text.push(c);
+                offset += c.len_utf16();
+            }
+        }
+    }
+    (text, highlights)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::cache::Cache;
+
+    const ALICE: &str = "0f8fad5b-d9cb-469f-a165-70867728950e";
+    const BOB: &str = "7c9e6679-7425-40de-944b-e07fc1f90ae7";
+
+    fn message(id: &str, sender_id: &str, sender: &str, html: &str) -> Value {
+        json!({
+            "id": id,
+            "createdDateTime": "2026-10-18T09:00:00Z",
+            "messageType": "message",
+            "body": { "contentType": "html", "content": html },
+            "from": { "user": { "id": sender_id, "displayName": sender } },
+        })
+    }
+
+    fn cache() -> Cache {
+        let cache = Cache::in_memory();
+        cache
+            .put_messages(
+                "chat1",
+                &[
+                    message("1", ALICE, "Alice Martin", "<p>Café prêt à partir</p>"),
+                    message("2", BOB, "Bob Stone", "<div>Weekly <b>report</b> is out</div><p>deployment done</p>"),
+                ],
+            )
+            .unwrap();
+        cache
+            .put_messages("chat2", &[message("3", ALICE, "Alice Martin", "Another report attached")])
+            .unwrap();
+        cache
+    }
+
+    fn ids(cache: &Cache, text: &str) -> Vec<String> {
+        let query = SearchQuery {
+            text: text.to_string(),
+            ..Default::default()
+        };
+        let mut ids: Vec<String> = cache.search_messages(&query).unwrap().into_iter().map(|h| h.message_id).collect();
+        ids.sort();
+        ids
+    }
+
+    #[test]
+    fn folds_case_and_diacritics() {
+        let cache = cache();
+        assert_eq!(ids(&cache, "cafe pret"), ["1"]);
+        assert_eq!(ids(&cache, "CAFÉ PRÊT"), ["1"]);
+    }
+
+    #[test]
+    fn matches_terms_as_prefixes() {
+        let cache = cache();
+        assert_eq!(ids(&cache, "deploy"), ["2"]);
+        assert_eq!(ids(&cache, "rep"), ["2", "3"]);
+        assert!(ids(&cache, "deployments").is_empty());
+    }
+
+    #[test]
+    fn indexes_text_without_markup() {
+        let cache = cache();
+        // Tag names aren't words of the message
+        assert!(ids(&cache, "div").is_empty());
+        assert!(ids(&cache, "b").is_empty());
+        // Block boundaries still separate words
+        assert_eq!(ids(&cache, "out deployment"), ["2"]);
+
+        let query = SearchQuery {
+            text: "weekly".to_string(),
+            ..Default::default()
+        };
+        let hits = cache.search_messages(&query).unwrap();
+        assert!(!hits[0].snippet.contains('<'), "{}", hits[0].snippet);
+    }
+
+    #[test]
+    fn applies_inline_filters() {
+        let cache = cache();
+        assert_eq!(ids(&cache, "report from:alice"), ["3"]);
+        assert_eq!(ids(&cache, "report from:\"bob st\""), ["2"]);
+        assert_eq!(ids(&cache, &format!("report from:{}", BOB)), ["2"]);
+        assert_eq!(ids(&cache, "report in:chat1"), ["2"]);
+        // Filters alone list everything they match
+        assert_eq!(ids(&cache, "in:chat1"), ["1", "2"]);
+    }
+
+    #[test]
+    fn inline_filters_leave_explicit_fields_alone() {
+        let query = parse_inline_filters(&SearchQuery {
+            text: "report from:alice in:chat2 has:attachment".to_string(),
+            conversation: Some("chat1".to_string()),
+            ..Default::default()
+        });
+        assert_eq!(query.text, "report");
+        assert_eq!(query.sender.as_deref(), Some("alice"));
+        assert_eq!(query.conversation.as_deref(), Some("chat1"));
+        assert!(query.has_attachment);
+    }
+
+    #[test]
+    fn highlights_are_utf16_offsets() {
+        let cache = Cache::in_memory();
+        cache
+            .put_messages("chat1", &[message("1", ALICE, "Alice", "😀 Café prêt")])
+            .unwrap();
+        let query = SearchQuery {
+            text: "pret".to_string(),
+            ..Default::default()
+        };
+        let hit = cache.search_messages(&query).unwrap().remove(0);
+        assert_eq!(hit.snippet, "😀 Café prêt");
+        assert_eq!(hit.highlights, [[8, 12]]);
+
+        let units: Vec<u16> = hit.snippet.encode_utf16().collect();
+        let [start, end] = hit.highlights[0];
+        assert_eq!(String::from_utf16(&units[start..end]).unwrap(), "prêt");
+    }
+}
@@ -0,0 +1,79 @@
+use std::collections::HashMap;
+
+use serde_json::Value;
+use tauri::{command, State};
+
+use crate::cache::{Cache, ReadMarker, SearchHit, SearchQuery};
+
+const DEFAULT_MESSAGE_LIMIT: u32 = 50;
+
+#[command]
+pub fn cache_is_available(cache: State<'_, Cache>) -> bool {
+    cache.is_available()
+}
+
+#[command]
+pub async fn cache_get_chats(cache: State<'_, Cache>) -> Result<Vec<Value>, String> {
+    cache.chats()
+}
+
+#[command]
+pub async fn cache_put_chats(cache: State<'_, Cache>, chats: Vec<Value>) -> Result<(), String> {
+    cache.put_chats(&chats)
+}
+
+#[command]
+pub async fn cache_get_channels(cache: State<'_, Cache>, team_id: String) -> Result<Vec<Value>, String> {
+    cache.channels(&team_id)
+}
+
+#[command]
+pub async fn cache_put_channels(
+    cache: State<'_, Cache>,
+    team_id: String,
+    channels: Vec<Value>,
+) -> Result<(), String> {
+    cache.put_channels(&team_id, &channels)
+}
+
+#[command]
+pub async fn cache_get_members(cache: State<'_, Cache>, conversation: String) -> Result<Vec<Value>, String> {
+    cache.members(&conversation)
+}
+
+/// Newest messages of a conversation (chat ID or `<teamId>:<channelId>`),
+/// oldest first. Pass the oldest loaded `createdDateTime` as `before` to page back.
+#[command]
+pub async fn cache_get_messages(
+    cache: State<'_, Cache>,
+    conversation: String,
+    before: Option<String>,
+    limit: Option<u32>,
+) -> Result<Vec<Value>, String> {
+    cache.messages(&conversation, before.as_deref(), limit.unwrap_or(DEFAULT_MESSAGE_LIMIT))
+}
+
+#[command]
+pub async fn cache_put_messages(
+    cache: State<'_, Cache>,
+    conversation: String,
+    messages: Vec<Value>,
+) -> Result<(), String> {
+    cache.put_messages(&conversation, &messages)
+}
+
+#[command]
+pub async fn cache_get_read_markers(cache: State<'_, Cache>) -> Result<HashMap<String, ReadMarker>, String> {
+    cache.read_markers()
+}
+
+#[command]
+pub async fn cache_clear(cache: State<'_, Cache>) -> Result<(), String> {
+    cache.clear()
+}
+
+/// Full-text search over cached messages; see `SearchQuery` for filters.
+#[command]
+pub async fn search_messages(cache: State<'_, Cache>, query: SearchQuery) -> Result<Vec<SearchHit>, String> {
+    cache.search_messages(&query)
+}
@@ -0,0 +1,14 @@
+use tauri::{command, State};
+
+use crate::connectivity::{Connectivity, ConnectivityStatus};
+
+#[command]
+pub fn connectivity_status(connectivity: State<'_, Connectivity>) -> ConnectivityStatus {
+    connectivity.status()
+}
+
+/// Re-probe now (the webview calls this on the browser's `online` event).
+#[command]
+pub fn connectivity_check(connectivity: State<'_, Connectivity>) {
+    connectivity.check_now();
+}
@@ -1,6 +1,15 @@
+use std::collections::HashMap;
 use std::fs;
+use std::io::{BufRead, BufReader, Read};
 use std::path::{Path, PathBuf};
-use tauri::command;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+use base64::Engine;
+use tauri::{command, AppHandle, Emitter, State};
+use tokio::sync::oneshot;
+
+use crate::settings;
 
 #[derive(Debug, serde::Serialize)]
 pub struct FileEntry {
@@ -17,8 +26,70 @@ pub struct SearchResult {
     pub content: String,
 }
 
+/// How Claude's file-modifying tools are allowed to touch the disk.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum EditPolicy {
+    /// Apply writes and edits immediately.
+    AutoApprove,
+    /// Emit a diff and wait for the user to approve or reject it.
+    #[default]
+    AlwaysAsk,
+    /// Refuse all writes and edits.
+    ReadOnly,
+}
+
+/// A pending write or edit, as shown to the user for approval.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct EditProposal {
+    pub id: String,
+    pub path: String,
+    pub diff: String,
+    pub is_new_file: bool,
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+struct EditResolvedPayload {
+    id: String,
+    approved: bool,
+}
+
+enum EditDecision {
+    Approve,
+    Reject(Option<String>),
+}
+
+/// Managed state holding the edit policy and proposals awaiting a decision.
+#[derive(Default)]
+pub struct EditApprovals {
+    policy: Mutex<EditPolicy>,
+    pending: Mutex<HashMap<String, (EditProposal, oneshot::Sender<EditDecision>)>>,
+    next_id: AtomicU64,
+}
+
+impl EditApprovals {
+    /// Create the approval state, restoring the persisted policy if there is one.
+    pub fn load(app: &AppHandle) -> Self {
+        let policy = settings::load(app, EDIT_POLICY_KEY).unwrap_or_default();
+
+        Self {
+            policy: Mutex::new(policy),
+            ..Default::default()
+        }
+    }
+
+    fn policy(&self) -> EditPolicy {
+        *self.policy.lock().unwrap()
+    }
+}
+
+const EDIT_POLICY_KEY: &str = "fsEditPolicy";
+
+/// Proposals left unanswered for this long are treated as rejected.
+const APPROVAL_TIMEOUT: Duration = Duration::from_secs(600);
+
 /// Resolve a relative path against the project root and ensure it stays within bounds.
-fn resolve_safe_path(project_root: &str, relative_path: &str) -> Result<PathBuf, String> {
+pub(crate) fn resolve_safe_path(project_root: &str, relative_path: &str) -> Result<PathBuf, String> {
     let root = Path::new(project_root)
         .canonicalize()
         .map_err(|e| format!("Invalid project root: {}", e))?;
@@ -50,7 +121,7 @@ fn resolve_safe_path(project_root: &str, relative_path: &str) -> Result<PathBuf,
     Ok(resolved)
 }
 
-const SKIP_DIRS: &[&str] = &["node_modules", ".git", "target", "dist", ".nuxt", ".output"];
+pub(crate) const SKIP_DIRS: &[&str] = &["node_modules", ".git", "target", "dist", ".nuxt", ".output"];
 
 #[command]
 pub fn fs_get_project_root() -> Result<String, String> {
@@ -59,54 +130,676 @@ pub fn fs_get_project_root() -> Result<String, String> {
         .map_err(|e| format!("Failed to get project root: {}", e))
 }
 
+/// Default cap on the bytes of content returned by `fs_read_file`.
+const DEFAULT_MAX_READ_BYTES: u64 = 256 * 1024;
+
+/// How many leading bytes are inspected to detect encoding and binary content.
+const SNIFF_BYTES: usize = 8192;
+
+/// UTF-16 files are decoded this many bytes at a time.
+const UTF16_CHUNK_BYTES: usize = 64 * 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BinaryFormat {
+    Hex,
+    Base64,
+}
+
+#[derive(Debug, serde::Serialize)]
+pub struct FileMetadata {
+    pub size: u64,
+    /// Last modification time in milliseconds since the Unix epoch.
+    pub modified: Option<u64>,
+    /// One of `utf-8`, `utf-8-bom`, `utf-16le`, `utf-16be`, `unknown` (decoded lossily) or `binary`.
+    pub encoding: String,
+    /// Total number of lines. `None` for binary files.
+    pub line_count: Option<usize>,
+}
+
+#[derive(Debug, serde::Serialize)]
+pub struct FileContent {
+    /// Text lines, or the hex/base64 encoded bytes of a binary file.
+    pub content: String,
+    /// `text`, `hex`, `base64`, or `none` when a binary file was read without a format.
+    pub format: String,
+    /// Whether the content stops short of the requested range or the file end.
+    pub truncated: bool,
+    /// The only line in `content` was longer than the size limit and is cut
+    /// short; `end_line` is that line.
+    pub partial_line: bool,
+    /// First and last line included in `content` (1-based), for text files.
+    pub start_line: Option<usize>,
+    pub end_line: Option<usize>,
+    pub metadata: FileMetadata,
+}
+
 #[command]
-pub fn fs_read_file(project_root: String, path: String) -> Result<String, String> {
+pub fn fs_read_file(
+    project_root: String,
+    path: String,
+    offset: Option<usize>,
+    limit: Option<usize>,
+    max_bytes: Option<u64>,
+    binary_format: Option<BinaryFormat>,
+) -> Result<FileContent, String> {
     let resolved = resolve_safe_path(&project_root, &path)?;
-    fs::read_to_string(&resolved).map_err(|e| format!("Failed to read file: {}", e))
+    let max_bytes = max_bytes.unwrap_or(DEFAULT_MAX_READ_BYTES);
+
+    let file_meta = fs::metadata(&resolved).map_err(|e| format!("Failed to read file: {}", e))?;
+    if file_meta.is_dir() {
+        return Err("Path is a directory".to_string());
+    }
+    let modified = file_meta
+        .modified()
+        .ok()
+        .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+        .map(|d| d.as_millis() as u64);
+
+    let file = fs::File::open(&resolved).map_err(|e| format!("Failed to read file: {}", e))?;
+    let mut reader = BufReader::new(file);
+    let head = reader
+        .fill_buf()
+        .map_err(|e| format!("Failed to read file: {}", e))?;
+
+    let encoding = detect_encoding(head);
+
+    if encoding == "binary" {
+        let (content, format, truncated) = match binary_format {
+            Some(format) => {
+                // Read only as much as fits in `max_bytes` once encoded
+                let raw_bytes = match format {
+                    BinaryFormat::Hex => max_bytes / 2,
+                    BinaryFormat::Base64 => max_bytes / 4 * 3,
+                };
+                let mut bytes = Vec::new();
+                reader
+                    .take(raw_bytes)
+                    .read_to_end(&mut bytes)
+                    .map_err(|e| format!("Failed to read file: {}", e))?;
+                let content = match format {
+                    BinaryFormat::Hex => bytes.iter().map(|b| format!("{:02x}", b)).collect(),
+                    BinaryFormat::Base64 => base64::engine::general_purpose::STANDARD.encode(&bytes),
+                };
+                let label = match format {
+                    BinaryFormat::Hex => "hex",
+                    BinaryFormat::Base64 => "base64",
+                };
+                (content, label, (bytes.len() as u64) < file_meta.len())
+            }
+            None => (String::new(), "none", file_meta.len() > 0),
+        };
+
+        return Ok(FileContent {
+            content,
+            format: format.to_string(),
+            truncated,
+            partial_line: false,
+            start_line: None,
+            end_line: None,
+            metadata: FileMetadata {
+                size: file_meta.len(),
+                modified,
+                encoding: encoding.to_string(),
+                line_count: None,
+            },
+        });
+    }
+
+    let mut encoding = encoding.to_string();
+    let mut window = LineWindow::new(offset.unwrap_or(1), limit, max_bytes);
+
+    if encoding.starts_with("utf-16") {
+        reader.consume(2);
+        push_utf16_lines(&mut reader, encoding == "utf-16be", &mut window)
+            .map_err(|e| format!("Failed to read file: {}", e))?;
+    } else {
+        if encoding == "utf-8-bom" {
+            reader.consume(3);
+        }
+        let mut buf = Vec::new();
+        loop {
+            buf.clear();
+            let n = reader
+                .read_until(b'\n', &mut buf)
+                .map_err(|e| format!("Failed to read file: {}", e))?;
+            if n == 0 {
+                break;
+            }
+            match std::str::from_utf8(&buf) {
+                Ok(line) => window.push(line),
+                Err(_) => {
+                    encoding = "unknown".to_string();
+                    window.push(&String::from_utf8_lossy(&buf));
+                }
+            }
+        }
+    }
+
+    let LineWindow {
+        start,
+        content,
+        line_count,
+        end_line,
+        truncated,
+        partial_line,
+        ..
+    } = window;
+
+    Ok(FileContent {
+        content,
+        format: "text".to_string(),
+        truncated,
+        partial_line,
+        start_line: end_line.map(|_| start),
+        end_line,
+        metadata: FileMetadata {
+            size: file_meta.len(),
+            modified,
+            encoding,
+            line_count: Some(line_count),
+        },
+    })
+}
+
+/// Collects the requested line range while counting every line of the file.
+struct LineWindow {
+    start: usize,
+    limit: Option<usize>,
+    max_bytes: u64,
+    content: String,
+    line_count: usize,
+    end_line: Option<usize>,
+    truncated: bool,
+    partial_line: bool,
+}
+
+impl LineWindow {
+    fn new(start: usize, limit: Option<usize>, max_bytes: u64) -> Self {
+        Self {
+            start: start.max(1),
+            limit,
+            max_bytes,
+            content: String::new(),
+            line_count: 0,
+            end_line: None,
+            truncated: false,
+            partial_line: false,
+        }
+    }
+
+    fn push(&mut self, line: &str) {
+        self.line_count += 1;
+        if self.line_count < self.start || self.truncated {
+            return;
+        }
+        if let Some(limit) = self.limit {
+            if self.line_count >= self.start + limit {
+                return;
+            }
+        }
+        if (self.content.len() + line.len()) as u64 > self.max_bytes {
+            self.truncated = true;
+            // Rather than nothing at all, return as much of an overlong first line as fits
+            if self.content.is_empty() {
+                let mut end = (self.max_bytes as usize).min(line.len());
+                while !line.is_char_boundary(end) {
+                    end -= 1;
+                }
+                if end > 0 {
+                    self.content.push_str(&line[..end]);
+                    self.end_line = Some(self.line_count);
+                    self.partial_line = true;
+                }
+            }
+            return;
+        }
+        self.content.push_str(line);
+        self.end_line = Some(self.line_count);
+    }
+}
+
+/// Classify a file from its leading bytes.
+fn detect_encoding(head: &[u8]) -> &'static str {
+    if head.starts_with(&[0xEF, 0xBB, 0xBF]) {
+        return "utf-8-bom";
+    }
+    if head.starts_with(&[0xFF, 0xFE]) {
+        return "utf-16le";
+    }
+    if head.starts_with(&[0xFE, 0xFF]) {
+        return "utf-16be";
+    }
+
+    let sample = &head[..head.len().min(SNIFF_BYTES)];
+    if sample.contains(&0) {
+        return "binary";
+    }
+    "utf-8"
+}
+
+/// Decode UTF-16 from `reader` a chunk at a time and feed `window` line by
+/// line, so a large file is never held in memory whole. Lines are only kept up
+/// to the window's size limit, which is all it can return of them anyway.
+fn push_utf16_lines(reader: &mut impl Read, big_endian: bool, window: &mut LineWindow) -> std::io::Result<()> {
+    let mut chunk = vec![0u8; UTF16_CHUNK_BYTES];
+    let mut bytes: Vec<u8> = Vec::new();
+    let mut line = String::new();
+    // A high surrogate at the end of a chunk waits for its pair
+    let mut high_surrogate: Option<u16> = None;
+    loop {
+        let n = reader.read(&mut chunk)?;
+        if n == 0 {
+            break;
+        }
+        bytes.extend_from_slice(&chunk[..n]);
+        let even = bytes.len() & !1;
+        let mut units: Vec<u16> = high_surrogate.take().into_iter().collect();
+        units.extend(bytes[..even].chunks_exact(2).map(|pair| {
+            if big_endian {
+                u16::from_be_bytes([pair[0], pair[1]])
+            } else {
+                u16::from_le_bytes([pair[0], pair[1]])
+            }
+        }));
+        bytes.drain(..even);
+        if units.last().is_some_and(|unit| (0xD800..0xDC00).contains(unit)) {
+            high_surrogate = units.pop();
+        }
+
+        for c in char::decode_utf16(units) {
+            let c = c.unwrap_or(char::REPLACEMENT_CHARACTER);
+            if c == '\n' {
+                line.push(c);
+                window.push(&line);
+                line.clear();
+            } else if (line.len() as u64) <= window.max_bytes {
+                line.push(c);
+            }
+        }
+    }
+    if high_surrogate.is_some() {
+        line.push(char::REPLACEMENT_CHARACTER);
+    }
+    if !line.is_empty() {
+        window.push(&line);
+    }
+    Ok(())
 }
 
 #[command]
-pub fn fs_write_file(
+pub async fn fs_write_file(
+    app: AppHandle,
+    approvals: State<'_, EditApprovals>,
     project_root: String,
     path: String,
     content: String,
 ) -> Result<(), String> {
     let resolved = resolve_safe_path(&project_root, &path)?;
+    let original = fs::read_to_string(&resolved).ok();
 
-    // Create parent directories if needed
-    if let Some(parent) = resolved.parent() {
-        fs::create_dir_all(parent)
-            .map_err(|e| format!("Failed to create directories: {}", e))?;
-    }
-
-    fs::write(&resolved, &content).map_err(|e| format!("Failed to write file: {}", e))
+    apply_change(&app, &approvals, &path, &resolved, original, content).await
 }
 
 #[command]
-pub fn fs_edit_file(
+pub async fn fs_edit_file(
+    app: AppHandle,
+    approvals: State<'_, EditApprovals>,
     project_root: String,
     path: String,
     old_text: String,
     new_text: String,
+    occurrence: Option<usize>,
 ) -> Result<(), String> {
     let resolved = resolve_safe_path(&project_root, &path)?;
 
     let content =
         fs::read_to_string(&resolved).map_err(|e| format!("Failed to read file: {}", e))?;
 
-    let count = content.matches(&old_text).count();
-    if count == 0 {
+    let updated = replace_text(&content, &old_text, &new_text, occurrence)?;
+    apply_change(&app, &approvals, &path, &resolved, Some(content), updated).await
+}
+
+/// A single step of a batch edit. Line numbers are 1-based.
+#[derive(Debug, serde::Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum EditOperation {
+    /// Replace `old_text` with `new_text`. Without `occurrence` the text must
+    /// be unique; with it, the nth match (1-based) is replaced.
+    Replace {
+        old_text: String,
+        new_text: String,
+        occurrence: Option<usize>,
+    },
+    /// Replace lines `start_line..=end_line` with `new_text`.
+    ReplaceLines {
+        start_line: usize,
+        end_line: usize,
+        new_text: String,
+    },
+    /// Insert `text` before `line`. Use one past the last line to append.
+    InsertAtLine { line: usize, text: String },
+}
+
+/// Apply a list of edits to one file, all or nothing.
+///
+/// Operations run in order against the in-memory content, so later line
+/// numbers refer to the file as modified by the earlier operations.
+#[command]
+pub async fn fs_multi_edit_file(
+    app: AppHandle,
+    approvals: State<'_, EditApprovals>,
+    project_root: String,
+    path: String,
+    edits: Vec<EditOperation>,
+) -> Result<(), String> {
+    if edits.is_empty() {
+        return Err("No edits provided".to_string());
+    }
+
+    let resolved = resolve_safe_path(&project_root, &path)?;
+
+    let content =
+        fs::read_to_string(&resolved).map_err(|e| format!("Failed to read file: {}", e))?;
+
+    let updated = apply_edits(&content, &edits)?;
+    apply_change(&app, &approvals, &path, &resolved, Some(content), updated).await
+}
+
+/// Run `edits` in order; the first failure discards them all.
+fn apply_edits(content: &str, edits: &[EditOperation]) -> Result<String, String> {
+    let mut updated = content.to_string();
+    for (i, edit) in edits.iter().enumerate() {
+        updated = apply_operation(&updated, edit)
+            .map_err(|e| format!("Edit {} of {} failed: {}", i + 1, edits.len(), e))?;
+    }
+    Ok(updated)
+}
+
+fn apply_operation(content: &str, edit: &EditOperation) -> Result<String, String> {
+    match edit {
+        EditOperation::Replace {
+            old_text,
+            new_text,
+            occurrence,
+        } => replace_text(content, old_text, new_text, *occurrence),
+        EditOperation::ReplaceLines {
+            start_line,
+            end_line,
+            new_text,
+        } => replace_lines(content, *start_line, *end_line, new_text),
+        EditOperation::InsertAtLine { line, text } => insert_at_line(content, *line, text),
+    }
+}
+
+fn replace_text(
+    content: &str,
+    old_text: &str,
+    new_text: &str,
+    occurrence: Option<usize>,
+) -> Result<String, String> {
+    if old_text.is_empty() {
+        return Err("old_text must not be empty".to_string());
+    }
+
+    let matches: Vec<usize> = content.match_indices(old_text).map(|(i, _)| i).collect();
+    if matches.is_empty() {
         return Err("old_text not found in file".to_string());
     }
-    if count > 1 {
+
+    let start = match occurrence {
+        Some(n) => *matches.get(n.wrapping_sub(1)).ok_or_else(|| {
+            format!(
+                "occurrence {} requested but old_text found {} times",
+                n,
+                matches.len()
+            )
+        })?,
+        None if matches.len() > 1 => {
+            return Err(format!(
+                "old_text found {} times — must be unique. Provide more surrounding context or an occurrence index.",
+                matches.len()
+            ))
+        }
+        None => matches[0],
+    };
+
+    let mut updated = String::with_capacity(content.len() + new_text.len());
+    updated.push_str(&content[..start]);
+    updated.push_str(new_text);
+    updated.push_str(&content[start + old_text.len()..]);
+    Ok(updated)
+}
+
+fn replace_lines(
+    content: &str,
+    start_line: usize,
+    end_line: usize,
+    new_text: &str,
+) -> Result<String, String> {
+    let lines: Vec<&str> = content.split_inclusive('\n').collect();
+
+    if start_line == 0 || end_line < start_line {
+        return Err(format!("Invalid line range {}-{}", start_line, end_line));
+    }
+    if end_line > lines.len() {
+        return Err(format!(
+            "Line range {}-{} is out of bounds (file has {} lines)",
+            start_line,
+            end_line,
+            lines.len()
+        ));
+    }
+
+    let mut updated: String = lines[..start_line - 1].concat();
+    updated.push_str(new_text);
+    // Keep the replaced block's trailing newline so following lines don't merge
+    if !new_text.is_empty() && !new_text.ends_with('\n') && lines[end_line - 1].ends_with('\n') {
+        updated.push_str(line_ending(content));
+    }
+    updated.push_str(&lines[end_line..].concat());
+    Ok(updated)
+}
+
+fn insert_at_line(content: &str, line: usize, text: &str) -> Result<String, String> {
+    let lines: Vec<&str> = content.split_inclusive('\n').collect();
+
+    if line == 0 || line > lines.len() + 1 {
         return Err(format!(
-            "old_text found {} times — must be unique. Provide more surrounding context.",
-            count
+            "Line {} is out of bounds (file has {} lines)",
+            line,
+            lines.len()
         ));
     }
 
-    let updated = content.replacen(&old_text, &new_text, 1);
-    fs::write(&resolved, &updated).map_err(|e| format!("Failed to write file: {}", e))
+    let eol = line_ending(content);
+    let mut updated: String = lines[..line - 1].concat();
+    if !updated.is_empty() && !updated.ends_with('\n') {
+        updated.push_str(eol);
+    }
+    updated.push_str(text);
+    if !text.ends_with('\n') && line <= lines.len() {
+        updated.push_str(eol);
+    }
+    updated.push_str(&lines[line - 1..].concat());
+    Ok(updated)
+}
+
+/// The file's line ending, for lines the edits add: CRLF if it uses any.
+fn line_ending(content: &str) -> &'static str {
+    if content.contains("\r\n") {
+        "\r\n"
+    } else {
+        "\n"
+    }
+}
+
+/// Write `updated` to `resolved`, subject to the current edit policy.
+///
+/// `original` is the file content the change was computed from (`None` for a
+/// new file). In always-ask mode the diff is emitted as `fs:edit-proposed`
+/// and the write only happens once the user approves it.
+async fn apply_change(
+    app: &AppHandle,
+    approvals: &EditApprovals,
+    path: &str,
+    resolved: &Path,
+    original: Option<String>,
+    updated: String,
+) -> Result<(), String> {
+    let diff = || unified_diff(path, original.as_deref(), &updated);
+    if !request_approval(app, approvals, path, diff, original.is_none()).await? {
+        return write_file(resolved, &updated);
+    }
+
+    // Refuse to clobber changes made while the proposal was waiting
+    let current = fs::read_to_string(resolved).ok();
+    if current != original {
+        return Err("File changed on disk since the edit was proposed".to_string());
+    }
+
+    write_file(resolved, &updated)
+}
+
+/// Check a change to `path` against the edit policy, asking the user with
+/// the `diff` it builds in always-ask mode. Returns whether they were asked, in which case
+/// the change should make sure nothing moved underneath it meanwhile.
+pub(crate) async fn request_approval(
+    app: &AppHandle,
+    approvals: &EditApprovals,
+    path: &str,
+    diff: impl FnOnce() -> String,
+    is_new_file: bool,
+) -> Result<bool, String> {
+    match approvals.policy() {
+        EditPolicy::ReadOnly => {
+            return Err("File modifications are disabled (edit policy is read-only)".to_string())
+        }
+        EditPolicy::AutoApprove => return Ok(false),
+        EditPolicy::AlwaysAsk => {}
+    }
+
+    let id = format!("edit-{}", approvals.next_id.fetch_add(1, Ordering::SeqCst) + 1);
+    let proposal = EditProposal {
+        id: id.clone(),
+        path: path.to_string(),
+        diff: diff(),
+        is_new_file,
+    };
+
+    let (tx, rx) = oneshot::channel();
+    approvals
+        .pending
+        .lock()
+        .unwrap()
+        .insert(id.clone(), (proposal.clone(), tx));
+    let _ = app.emit("fs:edit-proposed", &proposal);
+
+    let decision = tokio::time::timeout(APPROVAL_TIMEOUT, rx).await;
+
+    // Whatever happened, the proposal is no longer pending
+    approvals.pending.lock().unwrap().remove(&id);
+
+    let approved = matches!(decision, Ok(Ok(EditDecision::Approve)));
+    let _ = app.emit(
+        "fs:edit-resolved",
+        EditResolvedPayload {
+            id: id.clone(),
+            approved,
+        },
+    );
+
+    match decision {
+        Ok(Ok(EditDecision::Approve)) => Ok(true),
+        Ok(Ok(EditDecision::Reject(reason))) => Err(match reason {
+            Some(reason) if !reason.trim().is_empty() => {
+                format!("Edit rejected by user: {}", reason.trim())
+            }
+            _ => "Edit rejected by user".to_string(),
+        }),
+        Ok(Err(_)) => Err("Edit approval was cancelled".to_string()),
+        Err(_) => Err("Edit approval timed out".to_string()),
+    }
+}
+
+fn write_file(resolved: &Path, content: &str) -> Result<(), String> {
+    // Create parent directories if needed
+    if let Some(parent) = resolved.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create directories: {}", e))?;
+    }
+
+    fs::write(resolved, content).map_err(|e| format!("Failed to write file: {}", e))
+}
+
+/// Render a unified diff between the old and new file contents.
+fn unified_diff(path: &str, original: Option<&str>, updated: &str) -> String {
+    let old_header = match original {
+        Some(_) => format!("a/{}", path),
+        None => "/dev/null".to_string(),
+    };
+
+    similar::TextDiff::from_lines(original.unwrap_or(""), updated)
+        .unified_diff()
+        .context_radius(3)
+        .header(&old_header, &format!("b/{}", path))
+        .to_string()
+}
+
+#[command]
+pub fn fs_get_edit_policy(approvals: State<'_, EditApprovals>) -> EditPolicy {
+    approvals.policy()
+}
+
+#[command]
+pub fn fs_set_edit_policy(
+    app: AppHandle,
+    approvals: State<'_, EditApprovals>,
+    policy: EditPolicy,
+) -> Result<(), String> {
+    *approvals.policy.lock().unwrap() = policy;
+    settings::save(&app, EDIT_POLICY_KEY, &policy)
+}
+
+#[command]
+pub fn fs_list_pending_edits(approvals: State<'_, EditApprovals>) -> Vec<EditProposal> {
+    approvals
+        .pending
+        .lock()
+        .unwrap()
+        .values()
+        .map(|(proposal, _)| proposal.clone())
+        .collect()
+}
+
+#[command]
+pub fn fs_approve_edit(approvals: State<'_, EditApprovals>, id: String) -> Result<(), String> {
+    resolve_proposal(&approvals, &id, EditDecision::Approve)
+}
+
+#[command]
+pub fn fs_reject_edit(
+    approvals: State<'_, EditApprovals>,
+    id: String,
+    reason: Option<String>,
+) -> Result<(), String> {
+    resolve_proposal(&approvals, &id, EditDecision::Reject(reason))
+}
+
+fn resolve_proposal(
+    approvals: &EditApprovals,
+    id: &str,
+    decision: EditDecision,
+) -> Result<(), String> {
+    let (_, tx) = approvals
+        .pending
+        .lock()
+        .unwrap()
+        .remove(id)
+        .ok_or_else(|| format!("No pending edit with id {}", id))?;
+
+    tx.send(decision)
+        .map_err(|_| "Edit is no longer waiting for approval".to_string())
 }
 
 #[command]
@@ -274,3 +967,221 @@ fn search_recursive(
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn replace(old_text: &str, new_text: &str, occurrence: Option<usize>) -> EditOperation {
+        EditOperation::Replace {
+            old_text: old_text.to_string(),
+            new_text: new_text.to_string(),
+            occurrence,
+        }
+    }
+
+    fn replace_lines_op(start_line: usize, end_line: usize, new_text: &str) -> EditOperation {
+        EditOperation::ReplaceLines {
+            start_line,
+            end_line,
+            new_text: new_text.to_string(),
+        }
+    }
+
+    fn insert(line: usize, text: &str) -> EditOperation {
+        EditOperation::InsertAtLine {
+            line,
+            text: text.to_string(),
+        }
+    }
+
+    #[test]
+    fn applies_edits_in_order() {
+        let edits = [replace("two", "2", None), insert(1, "zero"), replace_lines_op(4, 4, "THREE")];
+        assert_eq!(apply_edits("one\ntwo\nthree\n", &edits).unwrap(), "zero\none\n2\nTHREE\n");
+    }
+
+    #[test]
+    fn applies_no_edit_when_one_fails() {
+        let edits = [replace("one", "1", None), replace("missing", "x", None), insert(1, "zero")];
+        let err = apply_edits("one\ntwo\n", &edits).unwrap_err();
+        assert_eq!(err, "Edit 2 of 3 failed: old_text not found in file");
+    }
+
+    #[test]
+    fn replaces_unique_text_or_the_requested_occurrence() {
+        assert_eq!(replace_text("a b a", "b", "c", None).unwrap(), "a c a");
+        assert!(replace_text("a b a", "a", "c", None).unwrap_err().contains("found 2 times"));
+        assert_eq!(replace_text("a b a", "a", "c", Some(2)).unwrap(), "a b c");
+        assert_eq!(
+            replace_text("a b a", "a", "c", Some(3)).unwrap_err(),
+            "occurrence 3 requested but old_text found 2 times"
+        );
+        assert!(replace_text("a b a", "a", "c", Some(0)).is_err());
+        assert!(replace_text("a", "", "c", None).is_err());
+    }
+
+    #[test]
+    fn replaces_lines_by_1_based_range() {
+        let content = "one\ntwo\nthree\n";
+        assert_eq!(replace_lines(content, 1, 1, "ONE").unwrap(), "ONE\ntwo\nthree\n");
+        assert_eq!(replace_lines(content, 2, 3, "end\n").unwrap(), "one\nend\n");
+        // Empty text deletes the lines
+        assert_eq!(replace_lines(content, 2, 2, "").unwrap(), "one\nthree\n");
+        assert_eq!(replace_lines(content, 0, 1, "x").unwrap_err(), "Invalid line range 0-1");
+        assert_eq!(replace_lines(content, 3, 2, "x").unwrap_err(), "Invalid line range 3-2");
+        assert_eq!(
+            replace_lines(content, 3, 4, "x").unwrap_err(),
+            "Line range 3-4 is out of bounds (file has 3 lines)"
+        );
+    }
+
+    #[test]
+    fn inserts_before_a_line_or_at_the_end() {
+        let content = "one\ntwo\n";
+        assert_eq!(insert_at_line(content, 1, "zero").unwrap(), "zero\none\ntwo\n");
+        assert_eq!(insert_at_line(content, 2, "1.5\n").unwrap(), "one\n1.5\ntwo\n");
+        assert_eq!(insert_at_line(content, 3, "three\n").unwrap(), "one\ntwo\nthree\n");
+        // The last line gets its newline before anything is appended
+        assert_eq!(insert_at_line("one\ntwo", 3, "three").unwrap(), "one\ntwo\nthree");
+        assert_eq!(insert_at_line("", 1, "first").unwrap(), "first");
+        assert_eq!(
+            insert_at_line(content, 4, "x").unwrap_err(),
+            "Line 4 is out of bounds (file has 2 lines)"
+        );
+        assert!(insert_at_line(content, 0, "x").is_err());
+    }
+
+    #[test]
+    fn keeps_crlf_line_endings() {
+        let content = "one\r\ntwo\r\nthree";
+        assert_eq!(replace_lines(content, 2, 2, "TWO").unwrap(), "one\r\nTWO\r\nthree");
+        assert_eq!(insert_at_line(content, 2, "1.5").unwrap(), "one\r\n1.5\r\ntwo\r\nthree");
+        assert_eq!(insert_at_line(content, 4, "four").unwrap(), "one\r\ntwo\r\nthree\r\nfour");
+        assert_eq!(replace_text(content, "two", "2", None).unwrap(), "one\r\n2\r\nthree");
+    }
+
+    fn read(content: &[u8], offset: Option<usize>, limit: Option<usize>, max_bytes: Option<u64>) -> FileContent {
+        read_as(content, offset, limit, max_bytes, None)
+    }
+
+    fn read_as(
+        content: &[u8],
+        offset: Option<usize>,
+        limit: Option<usize>,
+        max_bytes: Option<u64>,
+        binary_format: Option<BinaryFormat>,
+    ) -> FileContent {
+        let root = tempfile::tempdir().unwrap();
+        fs::write(root.path().join("file"), content).unwrap();
+        let root_path = root.path().to_string_lossy().to_string();
+        fs_read_file(root_path, "file".to_string(), offset, limit, max_bytes, binary_format).unwrap()
+    }
+
+    #[test]
+    fn reads_a_window_of_lines() {
+        let file = read(b"one\ntwo\nthree\nfour\n", Some(2), Some(2), None);
+        assert_eq!(file.content, "two\nthree\n");
+        assert_eq!((file.start_line, file.end_line), (Some(2), Some(3)));
+        assert_eq!(file.metadata.line_count, Some(4));
+        assert_eq!(file.metadata.encoding, "utf-8");
+        assert!(!file.truncated);
+
+        let past_the_end = read(b"one\n", Some(5), None, None);
+        assert_eq!(past_the_end.content, "");
+        assert_eq!((past_the_end.start_line, past_the_end.end_line), (None, None));
+    }
+
+    #[test]
+    fn truncates_at_the_size_limit() {
+        let file = read(b"one\ntwo\nthree\n", None, None, Some(9));
+        assert_eq!(file.content, "one\ntwo\n");
+        assert_eq!(file.end_line, Some(2));
+        assert!(file.truncated);
+        assert!(!file.partial_line);
+        // Lines past the cut are still counted
+        assert_eq!(file.metadata.line_count, Some(3));
+    }
+
+    #[test]
+    fn returns_part_of_an_overlong_first_line() {
+        let file = read("ééééé\nshort\n".as_bytes(), None, None, Some(5));
+        // Cut at a character boundary
+        assert_eq!(file.content, "éé");
+        assert_eq!((file.start_line, file.end_line), (Some(1), Some(1)));
+        assert!(file.truncated);
+        assert!(file.partial_line);
+    }
+
+    #[test]
+    fn decodes_utf16_with_a_bom() {
+        let text = "héllo\n😀 wörld\n";
+        let mut le = vec![0xFF, 0xFE];
+        le.extend(text.encode_utf16().flat_map(u16::to_le_bytes));
+        let mut be = vec![0xFE, 0xFF];
+        be.extend(text.encode_utf16().flat_map(u16::to_be_bytes));
+
+        for (bytes, encoding) in [(le, "utf-16le"), (be, "utf-16be")] {
+            let file = read(&bytes, None, None, None);
+            assert_eq!(file.metadata.encoding, encoding);
+            assert_eq!(file.content, text);
+            assert_eq!(file.metadata.line_count, Some(2));
+        }
+    }
+
+    #[test]
+    fn keeps_surrogate_pairs_split_across_chunks() {
+        // Put the emoji's high surrogate last in the first chunk
+        let mut text = "a".repeat(UTF16_CHUNK_BYTES / 2 - 1);
+        text.push('😀');
+        let bytes: Vec<u8> = text.encode_utf16().flat_map(u16::to_le_bytes).collect();
+        let mut window = LineWindow::new(1, None, u64::MAX);
+        push_utf16_lines(&mut bytes.as_slice(), false, &mut window).unwrap();
+        assert_eq!(window.content, text);
+    }
+
+    #[test]
+    fn detects_encodings() {
+        assert_eq!(detect_encoding(b"plain"), "utf-8");
+        assert_eq!(detect_encoding(b"\xEF\xBB\xBFbom"), "utf-8-bom");
+        assert_eq!(detect_encoding(b"\xFF\xFEa\x00"), "utf-16le");
+        assert_eq!(detect_encoding(b"\xFE\xFF\x00a"), "utf-16be");
+        assert_eq!(detect_encoding(b"\x89PNG\r\n\x1a\n\x00\x00"), "binary");
+        // Only the sniffed prefix counts
+        let mut late_zero = vec![b'a'; SNIFF_BYTES];
+        late_zero.push(0);
+        assert_eq!(detect_encoding(&late_zero), "utf-8");
+    }
+
+    #[test]
+    fn flags_invalid_utf8_as_unknown() {
+        let file = read(b"caf\xE9\n", None, None, None);
+        assert_eq!(file.metadata.encoding, "unknown");
+        assert_eq!(file.content, "caf\u{FFFD}\n");
+    }
+
+    #[test]
+    fn encodes_binary_files_within_the_size_limit() {
+        let bytes: Vec<u8> = (0..=255).collect();
+
+        let none = read(&bytes, None, None, None);
+        assert_eq!((none.format.as_str(), none.content.as_str()), ("none", ""));
+        assert_eq!(none.metadata.encoding, "binary");
+        assert_eq!(none.metadata.line_count, None);
+        assert!(none.truncated);
+
+        let hex = read_as(&bytes, None, None, Some(10), Some(BinaryFormat::Hex));
+        assert_eq!((hex.format.as_str(), hex.content.as_str()), ("hex", "0001020304"));
+        assert!(hex.truncated);
+
+        let base64 = read_as(&bytes, None, None, Some(10), Some(BinaryFormat::Base64));
+        assert_eq!(base64.format, "base64");
+        assert!(base64.content.len() <= 10);
+        assert_eq!(base64.content, "AAECAwQF");
+        assert!(base64.truncated);
+
+        let whole = read_as(&bytes, None, None, None, Some(BinaryFormat::Hex));
+        assert_eq!(whole.content.len(), 512);
+        assert!(!whole.truncated);
+    }
+}
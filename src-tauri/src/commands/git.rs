@@ -0,0 +1,383 @@
+use std::path::Path;
+use std::process::Command;
+
+use tauri::{command, AppHandle, State};
+
+use super::filesystem::{request_approval, resolve_safe_path, EditApprovals};
+
+#[derive(Debug, serde::Serialize)]
+pub struct GitStatusEntry {
+    pub path: String,
+    /// Two-letter porcelain status code, e.g. ` M`, `A `, `??`.
+    pub status: String,
+    /// Original path for renames and copies.
+    pub orig_path: Option<String>,
+}
+
+#[derive(Debug, serde::Serialize)]
+pub struct GitStatus {
+    pub branch: Option<String>,
+    pub entries: Vec<GitStatusEntry>,
+}
+
+#[derive(Debug, serde::Serialize)]
+pub struct GitCommit {
+    pub hash: String,
+    pub author: String,
+    pub email: String,
+    /// Author date in strict ISO 8601.
+    pub date: String,
+    pub subject: String,
+}
+
+#[derive(Debug, serde::Serialize)]
+pub struct GitBlameLine {
+    pub line: usize,
+    pub hash: String,
+    pub author: String,
+    /// Author time in seconds since the Unix epoch.
+    pub time: i64,
+    pub summary: String,
+    pub content: String,
+}
+
+/// Cap on diff output so a huge change doesn't flood the webview.
+const MAX_DIFF_BYTES: usize = 200_000;
+
+/// Run `git` inside the project root and return stdout.
+fn run_git(project_root: &str, args: &[&str]) -> Result<String, String> {
+    let root = Path::new(project_root)
+        .canonicalize()
+        .map_err(|e| format!("Invalid project root: {}", e))?;
+
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(&root)
+        .args(["-c", "core.quotepath=off", "--no-pager"])
+        .args(args)
+        .output()
+        .map_err(|e| format!("Failed to run git: {}", e))?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("git {} failed: {}", args[0], stderr.trim()));
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Resolve a user-supplied path and make it relative to the project root for git.
+fn git_path(project_root: &str, path: &str) -> Result<String, String> {
+    let resolved = resolve_safe_path(project_root, path)?;
+    let root = Path::new(project_root)
+        .canonicalize()
+        .map_err(|e| format!("Invalid project root: {}", e))?;
+
+    Ok(resolved
+        .strip_prefix(&root)
+        .unwrap_or(&resolved)
+        .to_string_lossy()
+        .to_string())
+}
+
+#[command]
+pub fn git_status(project_root: String) -> Result<GitStatus, String> {
+    let output = run_git(&project_root, &["status", "--porcelain=v2", "-z", "--branch"])?;
+    Ok(parse_status(&output))
+}
+
+/// Parse `git status --porcelain=v2 -z --branch`. Status codes are given in
+/// the v1 style (` M`, `??`), and the branch is `None` on a detached HEAD.
+fn parse_status(output: &str) -> GitStatus {
+    let mut branch = None;
+    let mut entries = Vec::new();
+    let mut records = output.split('\0').filter(|r| !r.is_empty());
+
+    while let Some(record) = records.next() {
+        if let Some(header) = record.strip_prefix("# ") {
+            if let Some(head) = header.strip_prefix("branch.head ") {
+                branch = (head != "(detached)").then(|| head.to_string());
+            }
+            continue;
+        }
+
+        // Changed entries carry mode and hash fields before the path
+        let (status, path, orig_path) = match record.as_bytes()[0] {
+            b'1' => match record.splitn(9, ' ').collect::<Vec<_>>()[..] {
+                [_, xy, .., path] => (xy, path, None),
+                _ => continue,
+            },
+            // Renames and copies are followed by a separate record with the source path
+            b'2' => match record.splitn(10, ' ').collect::<Vec<_>>()[..] {
+                [_, xy, .., path] => (xy, path, records.next().map(str::to_string)),
+                _ => continue,
+            },
+            b'u' => match record.splitn(11, ' ').collect::<Vec<_>>()[..] {
+                [_, xy, .., path] => (xy, path, None),
+                _ => continue,
+            },
+            b'?' => ("??", &record[2..], None),
+            b'!' => ("!!", &record[2..], None),
+            _ => continue,
+        };
+
+        entries.push(GitStatusEntry {
+            path: path.to_string(),
+            status: status.replace('.', " "),
+            orig_path,
+        });
+    }
+
+    GitStatus { branch, entries }
+}
+
+/// Diff the working tree (or the index with `staged`) against HEAD, optionally for one path.
+#[command]
+pub fn git_diff(
+    project_root: String,
+    path: Option<String>,
+    staged: Option<bool>,
+) -> Result<String, String> {
+    let mut args = vec!["diff".to_string(), "--no-color".to_string()];
+    if staged.unwrap_or(false) {
+        args.push("--cached".to_string());
+    }
+    if let Some(ref p) = path {
+        args.push("--".to_string());
+        args.push(git_path(&project_root, p)?);
+    }
+
+    let args: Vec<&str> = args.iter().map(|s| s.as_str()).collect();
+    let mut diff = run_git(&project_root, &args)?;
+
+    if diff.len() > MAX_DIFF_BYTES {
+        let mut cut = MAX_DIFF_BYTES;
+        while !diff.is_char_boundary(cut) {
+            cut -= 1;
+        }
+        diff.truncate(cut);
+        diff.push_str("\n... (diff truncated)");
+    }
+
+    Ok(diff)
+}
+
+#[command]
+pub fn git_log(
+    project_root: String,
+    path: Option<String>,
+    limit: Option<usize>,
+) -> Result<Vec<GitCommit>, String> {
+    let max_count = format!("--max-count={}", limit.unwrap_or(20));
+    let mut args = vec![
+        "log".to_string(),
+        max_count,
+        "--format=%H%x1f%an%x1f%ae%x1f%aI%x1f%s%x1e".to_string(),
+    ];
+    if let Some(ref p) = path {
+        args.push("--follow".to_string());
+        args.push("--".to_string());
+        args.push(git_path(&project_root, p)?);
+    }
+
+    let args: Vec<&str> = args.iter().map(|s| s.as_str()).collect();
+    let output = run_git(&project_root, &args)?;
+
+    Ok(output
+        .split('\x1e')
+        .filter_map(|record| {
+            let mut fields = record.trim_start_matches('\n').split('\x1f');
+            Some(GitCommit {
+                hash: fields.next().filter(|h| !h.is_empty())?.to_string(),
+                author: fields.next()?.to_string(),
+                email: fields.next()?.to_string(),
+                date: fields.next()?.to_string(),
+                subject: fields.next()?.to_string(),
+            })
+        })
+        .collect())
+}
+
+/// Blame lines `start_line..=end_line` (1-based) of a file.
+#[command]
+pub fn git_blame(
+    project_root: String,
+    path: String,
+    start_line: usize,
+    end_line: usize,
+) -> Result<Vec<GitBlameLine>, String> {
+    if start_line == 0 || end_line < start_line {
+        return Err(format!("Invalid line range {}-{}", start_line, end_line));
+    }
+
+    let file = git_path(&project_root, &path)?;
+    let range = format!("{},{}", start_line, end_line);
+    let output = run_git(
+        &project_root,
+        &["blame", "--line-porcelain", "-L", &range, "--", &file],
+    )?;
+
+    let mut lines = Vec::new();
+    let mut current: Option<GitBlameLine> = None;
+
+    for line in output.lines() {
+        if let Some(content) = line.strip_prefix('\t') {
+            if let Some(mut entry) = current.take() {
+                entry.content = content.to_string();
+                lines.push(entry);
+            }
+        } else if let Some(value) = line.strip_prefix("author ") {
+            if let Some(entry) = current.as_mut() {
+                entry.author = value.to_string();
+            }
+        } else if let Some(value) = line.strip_prefix("author-time ") {
+            if let Some(entry) = current.as_mut() {
+                entry.time = value.parse().unwrap_or_default();
+            }
+        } else if let Some(value) = line.strip_prefix("summary ") {
+            if let Some(entry) = current.as_mut() {
+                entry.summary = value.to_string();
+            }
+        } else if current.is_none() {
+            // Header line: <hash> <orig-line> <final-line> [<group-size>]
+            let mut parts = line.split(' ');
+            if let (Some(hash), Some(_), Some(final_line)) = (parts.next(), parts.next(), parts.next()) {
+                current = Some(GitBlameLine {
+                    line: final_line.parse().unwrap_or_default(),
+                    hash: hash.to_string(),
+                    author: String::new(),
+                    time: 0,
+                    summary: String::new(),
+                    content: String::new(),
+                });
+            }
+        }
+    }
+
+    Ok(lines)
+}
+
+/// Commit the given paths with a user-supplied message.
+///
+/// Only the listed paths are staged, so unrelated work in the tree is left alone.
+/// Subject to the edit policy like file writes: refused when read-only, and
+/// shown for approval first in always-ask mode.
+#[command]
+pub async fn git_commit_paths(
+    app: AppHandle,
+    approvals: State<'_, EditApprovals>,
+    project_root: String,
+    paths: Vec<String>,
+    message: String,
+) -> Result<GitCommit, String> {
+    if paths.is_empty() {
+        return Err("No paths to commit".to_string());
+    }
+    if message.trim().is_empty() {
+        return Err("Commit message must not be empty".to_string());
+    }
+
+    let files = paths
+        .iter()
+        .map(|p| git_path(&project_root, p))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let label = files.join(", ");
+    let preview = || commit_preview(&project_root, &files, message.trim());
+    request_approval(&app, &approvals, &label, preview, false).await?;
+
+    let mut add_args = vec!["add", "--"];
+    add_args.extend(files.iter().map(|f| f.as_str()));
+    run_git(&project_root, &add_args)?;
+
+    let mut commit_args = vec!["commit", "-m", message.trim(), "--"];
+    commit_args.extend(files.iter().map(|f| f.as_str()));
+    run_git(&project_root, &commit_args)?;
+
+    git_log(project_root, None, Some(1))?
+        .into_iter()
+        .next()
+        .ok_or_else(|| "Commit succeeded but could not be read back".to_string())
+}
+
+/// The message and changes a commit of `files` would record, for approval.
+fn commit_preview(project_root: &str, files: &[String], message: &str) -> String {
+    let mut preview = format!("Commit: {}\n", message);
+
+    // Fails before the first commit, when everything is new anyway
+    let mut diff_args = vec!["diff", "--no-color", "HEAD", "--"];
+    diff_args.extend(files.iter().map(|f| f.as_str()));
+    if let Ok(diff) = run_git(project_root, &diff_args) {
+        preview.push('\n');
+        preview.push_str(&diff);
+    }
+
+    let mut untracked_args = vec!["ls-files", "--others", "--exclude-standard", "-z", "--"];
+    untracked_args.extend(files.iter().map(|f| f.as_str()));
+    if let Ok(untracked) = run_git(project_root, &untracked_args) {
+        for file in untracked.split('\0').filter(|f| !f.is_empty()) {
+            preview.push_str(&format!("\nNew file: {}\n", file));
+        }
+    }
+
+    if preview.len() > MAX_DIFF_BYTES {
+        let mut cut = MAX_DIFF_BYTES;
+        while !preview.is_char_boundary(cut) {
+            cut -= 1;
+        }
+        preview.truncate(cut);
+        preview.push_str("\n... (preview truncated)\n");
+    }
+    preview
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_branch_and_entries() {
+        let output = [
+            "# branch.oid 1234abcd",
+            "# branch.head main",
+            "# branch.upstream origin/main",
+            "1 .M N... 100644 100644 100644 aaaa bbbb src/my file.rs",
+            "2 R. N... 100644 100644 100644 aaaa bbbb R100 new name.rs",
+            "old name.rs",
+            "u UU N... 100644 100644 100644 100644 aaaa bbbb cccc conflict.rs",
+            "? notes.txt",
+            "",
+        ]
+        .join("\0");
+        let status = parse_status(&output);
+
+        assert_eq!(status.branch.as_deref(), Some("main"));
+        let entries: Vec<_> = status
+            .entries
+            .iter()
+            .map(|e| (e.status.as_str(), e.path.as_str(), e.orig_path.as_deref()))
+            .collect();
+        assert_eq!(
+            entries,
+            [
+                (" M", "src/my file.rs", None),
+                ("R ", "new name.rs", Some("old name.rs")),
+                ("UU", "conflict.rs", None),
+                ("??", "notes.txt", None),
+            ]
+        );
+    }
+
+    #[test]
+    fn detached_head_has_no_branch() {
+        let status = parse_status("# branch.oid 1234abcd\0# branch.head (detached)\0");
+        assert_eq!(status.branch, None);
+    }
+
+    #[test]
+    fn unborn_branch_keeps_its_name() {
+        let status = parse_status("# branch.oid (initial)\0# branch.head main\0? a.txt\0");
+        assert_eq!(status.branch.as_deref(), Some("main"));
+        assert_eq!(status.entries.len(), 1);
+    }
+}
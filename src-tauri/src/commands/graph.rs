@@ -0,0 +1,88 @@
+use std::collections::HashMap;
+
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue, AUTHORIZATION, CONTENT_LENGTH, HOST};
+use reqwest::Method;
+use serde::Deserialize;
+use serde_json::Value;
+use tauri::{command, State};
+
+use crate::connectivity::Connectivity;
+use crate::graph::{AccessToken, BatchRequest, BatchResponse, Body, GraphClient, GraphError};
+
+/// Hand the Rust Graph client the access token obtained by the webview's auth flow.
+#[command]
+pub fn graph_set_token(
+    graph: State<'_, GraphClient>,
+    connectivity: State<'_, Connectivity>,
+    access_token: String,
+    expires_at: Option<u64>,
+) {
+    graph.tokens().set(Some(AccessToken {
+        value: access_token,
+        expires_at,
+    }));
+    connectivity.token_updated();
+}
+
+#[command]
+pub fn graph_clear_token(graph: State<'_, GraphClient>) {
+    graph.tokens().set(None);
+}
+
+/// A request body from the webview, tagged with how to send it.
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", content = "data", rename_all = "camelCase")]
+pub enum FetchBody {
+    Json(Value),
+    Text(String),
+    Bytes(Vec<u8>),
+}
+
+/// Perform a Graph request through the shared client (retries, throttling, token).
+#[command]
+pub async fn graph_fetch(
+    graph: State<'_, GraphClient>,
+    method: Option<String>,
+    path: String,
+    body: Option<FetchBody>,
+    headers: Option<HashMap<String, String>>,
+) -> Result<Value, GraphError> {
+    let method = match method {
+        Some(m) => Method::from_bytes(m.to_uppercase().as_bytes())
+            .map_err(|_| GraphError::new("invalid_method", format!("Invalid HTTP method: {}", m)))?,
+        None => Method::GET,
+    };
+    let headers = header_map(headers.unwrap_or_default())?;
+    let body = match &body {
+        Some(FetchBody::Json(value)) => Some(Body::Json(value)),
+        Some(FetchBody::Text(text)) => Some(Body::Text(text)),
+        Some(FetchBody::Bytes(bytes)) => Some(Body::Bytes(bytes)),
+        None => None,
+    };
+    graph.send_with(method, &path, body, &headers).await
+}
+
+/// Headers to forward with a webview request. The client sets
+/// `Authorization` itself, and reqwest owns `Host` and `Content-Length`.
+fn header_map(headers: HashMap<String, String>) -> Result<HeaderMap, GraphError> {
+    let mut map = HeaderMap::new();
+    for (name, value) in headers {
+        let invalid = || GraphError::new("invalid_header", format!("Invalid header: {}", name));
+        let header = HeaderName::from_bytes(name.as_bytes()).map_err(|_| invalid())?;
+        if [AUTHORIZATION, HOST, CONTENT_LENGTH].contains(&header) {
+            continue;
+        }
+        map.insert(header, HeaderValue::from_str(&value).map_err(|_| invalid())?);
+    }
+    Ok(map)
+}
+
+/// Send an explicit JSON batch. Larger lists are split into batches of 20
+/// (keeping `dependsOn` chains together); each item carries its own status.
+#[command]
+pub async fn graph_batch(
+    graph: State<'_, GraphClient>,
+    requests: Vec<BatchRequest>,
+) -> Result<Vec<BatchResponse>, GraphError> {
+    graph.batch(requests).await
+}
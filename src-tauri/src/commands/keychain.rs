@@ -47,3 +47,22 @@ pub async fn keychain_delete(key: String) -> Result<(), String> {
         }
     }
 }
+
+/// Read a secret from the keychain, creating and storing it with `generate`
+/// on first use. Used for keys the app owns (e.g. the cache encryption key).
+pub(crate) fn get_or_create_secret(
+    key: &str,
+    generate: impl FnOnce() -> String,
+) -> Result<String, String> {
+    match get_generic_password(SERVICE_NAME, key) {
+        Ok(bytes) => String::from_utf8(bytes.to_vec())
+            .map_err(|e| format!("Failed to decode keychain value: {}", e)),
+        Err(e) if e.code() == -25300 => {
+            let value = generate();
+            set_generic_password(SERVICE_NAME, key, value.as_bytes())
+                .map_err(|e| format!("Failed to store in keychain: {}", e))?;
+            Ok(value)
+        }
+        Err(e) => Err(format!("Failed to read from keychain: {}", e)),
+    }
+}
@@ -1,6 +1,15 @@
 pub mod auth;
+pub mod cache;
 pub mod claude;
+pub mod connectivity;
 pub mod deeplink;
 pub mod filesystem;
+pub mod git;
+pub mod graph;
 pub mod keychain;
 pub mod notifications;
+pub mod outbox;
+pub mod presence;
+pub mod realtime;
+pub mod sync;
+pub mod watcher;
@@ -1,24 +1,149 @@
-use tauri::{command, AppHandle};
+use std::collections::HashMap;
+
+use serde::Serialize;
+use tauri::{command, AppHandle, State};
 use tauri_plugin_notification::NotificationExt;
 
+use crate::cache::{Cache, HistoryQuery, NotificationRecord};
+use crate::notifications::{
+    DryRun, MessageRef, Notice, NotificationManager, NotificationPolicy, NotificationTarget,
+    Rule, SampleMessage, Sound, BUNDLED_SOUNDS,
+};
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SoundSettings {
+    /// Names usable as `{ "bundled": name }`.
+    pub bundled: Vec<&'static str>,
+    /// Conversation key to its sound.
+    pub chats: HashMap<String, Sound>,
+}
+
+/// Show a notification that opens the chat (or, given `team_id` and
+/// `channel_id`, the channel) when clicked. With `message_id` and
+/// `created_at` it also offers reply, mark as read and mute, and the sender,
+/// chat type, text and mention flag let notification rules match on it.
+/// Bursts for one conversation are coalesced into a summary.
 #[command]
+#[allow(clippy::too_many_arguments)]
 pub async fn send_notification(
-    app: AppHandle,
+    notifications: State<'_, NotificationManager>,
     title: String,
     body: String,
     chat_id: String,
+    team_id: Option<String>,
+    channel_id: Option<String>,
+    message_id: Option<String>,
+    created_at: Option<String>,
+    sender_id: Option<String>,
+    sender_email: Option<String>,
+    chat_type: Option<String>,
+    text: Option<String>,
+    mentions_me: Option<bool>,
 ) -> Result<(), String> {
-    app.notification()
-        .builder()
-        .title(&title)
-        .body(&body)
-        .action_type_id(&chat_id)
-        .show()
-        .map_err(|e| e.to_string())?;
-
+    let target = NotificationTarget::new(&chat_id, team_id.as_deref(), channel_id.as_deref());
+    let message = message_id.map(|id| MessageRef {
+        id,
+        created_at: created_at.unwrap_or_default(),
+        sender_id: sender_id.unwrap_or_default(),
+        sender_email,
+        chat_type: chat_type.unwrap_or_default(),
+        // Rules fall back to the banner text when the full text isn't given
+        text: text.unwrap_or_else(|| body.clone()),
+        mentions_me: mentions_me.unwrap_or_default(),
+    });
+    notifications.post(Notice {
+        title,
+        body,
+        target,
+        message,
+        sound: Sound::default(),
+        urgent: false,
+    });
     Ok(())
 }
 
+#[command]
+pub fn notifications_get_policy(notifications: State<'_, NotificationManager>) -> NotificationPolicy {
+    notifications.policy()
+}
+
+/// Replace the do-not-disturb policy; it applies to the next notification.
+#[command]
+pub fn notifications_set_policy(
+    notifications: State<'_, NotificationManager>,
+    policy: NotificationPolicy,
+) -> Result<(), String> {
+    notifications.set_policy(policy)
+}
+
+#[command]
+pub fn notifications_get_rules(notifications: State<'_, NotificationManager>) -> Vec<Rule> {
+    notifications.rules()
+}
+
+/// Replace the notification rules. They're checked in order; the first
+/// enabled match wins.
+#[command]
+pub fn notifications_set_rules(
+    notifications: State<'_, NotificationManager>,
+    rules: Vec<Rule>,
+) -> Result<(), String> {
+    notifications.set_rules(rules)
+}
+
+/// Which rule a sample message would hit and what would happen, against
+/// `rules` when given (e.g. unsaved edits) or the saved rules otherwise.
+#[command]
+pub fn notifications_dry_run(
+    notifications: State<'_, NotificationManager>,
+    sample: SampleMessage,
+    rules: Option<Vec<Rule>>,
+) -> Result<DryRun, String> {
+    notifications.dry_run(sample, rules)
+}
+
+#[command]
+pub fn notifications_get_sounds(notifications: State<'_, NotificationManager>) -> SoundSettings {
+    SoundSettings {
+        bundled: BUNDLED_SOUNDS.iter().map(|(name, _)| *name).collect(),
+        chats: notifications.chat_sounds(),
+    }
+}
+
+/// Set the notification sound of a conversation (chat ID or
+/// `<teamId>:<channelId>`); `"default"` clears it.
+#[command]
+pub fn notifications_set_chat_sound(
+    notifications: State<'_, NotificationManager>,
+    conversation: String,
+    sound: Sound,
+) -> Result<(), String> {
+    notifications.set_chat_sound(&conversation, sound)
+}
+
+#[command]
+pub fn notifications_preview_sound(notifications: State<'_, NotificationManager>, sound: Sound) -> Result<(), String> {
+    notifications.preview_sound(&sound)
+}
+
+/// Notifications shown or held back, newest first; see `HistoryQuery` for
+/// filters and paging.
+#[command]
+pub async fn notifications_history(
+    cache: State<'_, Cache>,
+    query: HistoryQuery,
+) -> Result<Vec<NotificationRecord>, String> {
+    cache.notification_history(&query)
+}
+
+/// Mark history entries seen, or all of them when `ids` is omitted. Returns
+/// how many changed.
+#[command]
+pub async fn notifications_mark_seen(cache: State<'_, Cache>, ids: Option<Vec<i64>>) -> Result<usize, String> {
+    cache.mark_notifications_seen(ids.as_deref())
+}
+
 #[command]
 pub async fn request_notification_permission(
     app: AppHandle,
@@ -0,0 +1,37 @@
+use serde_json::Value;
+use tauri::{command, State};
+
+use crate::outbox::{Outbox, OutboxItem};
+
+/// Queue a message for sending. `path` is the Graph messages (or replies)
+/// endpoint and `payload` the body built by `buildMessagePayload`.
+#[command]
+pub fn outbox_enqueue(
+    outbox: State<'_, Outbox>,
+    conversation: String,
+    path: String,
+    payload: Value,
+) -> Result<OutboxItem, String> {
+    outbox.enqueue(conversation, path, payload)
+}
+
+#[command]
+pub fn outbox_list(outbox: State<'_, Outbox>) -> Vec<OutboxItem> {
+    outbox.items()
+}
+
+#[command]
+pub fn outbox_resend(outbox: State<'_, Outbox>, id: String) -> Result<OutboxItem, String> {
+    outbox.resend(&id)
+}
+
+#[command]
+pub fn outbox_discard(outbox: State<'_, Outbox>, id: String) -> Result<(), String> {
+    outbox.discard(&id)
+}
+
+/// Retry everything waiting on backoff now (e.g. the browser went back online).
+#[command]
+pub fn outbox_flush(outbox: State<'_, Outbox>) {
+    outbox.flush();
+}
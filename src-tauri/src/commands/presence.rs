@@ -0,0 +1,85 @@
+use std::time::Duration;
+
+use tauri::{command, State};
+
+use crate::graph::GraphError;
+use crate::presence::{PreferredStatus, Presence, PresenceService};
+
+/// Start the refresh loop for the signed-in user.
+#[command]
+pub fn presence_start(presence: State<'_, PresenceService>, user_id: String) {
+    presence.start(user_id);
+}
+
+#[command]
+pub fn presence_stop(presence: State<'_, PresenceService>) {
+    presence.stop();
+}
+
+/// Replace the set of users whose presence is kept fresh (people on screen).
+/// Changes arrive as `presence:updated` events.
+#[command]
+pub fn presence_track(presence: State<'_, PresenceService>, user_ids: Vec<String>) {
+    presence.track(user_ids);
+}
+
+/// Presences of `user_ids`, served from the cache when fresh.
+#[command]
+pub async fn presence_get(presence: State<'_, PresenceService>, user_ids: Vec<String>) -> Result<Vec<Presence>, GraphError> {
+    presence.get(user_ids).await
+}
+
+#[command]
+pub fn presence_get_mine(presence: State<'_, PresenceService>) -> Option<Presence> {
+    presence.mine()
+}
+
+/// Set the user's preferred status; `expiration_minutes` defaults to Graph's
+/// own expiry when omitted.
+#[command]
+pub async fn presence_set_preferred(
+    presence: State<'_, PresenceService>,
+    status: PreferredStatus,
+    expiration_minutes: Option<u64>,
+) -> Result<(), GraphError> {
+    presence
+        .set_preferred(status, expiration_minutes.map(|m| Duration::from_secs(m * 60)))
+        .await
+}
+
+#[command]
+pub async fn presence_clear_preferred(presence: State<'_, PresenceService>) -> Result<(), GraphError> {
+    presence.clear_preferred().await
+}
+
+/// Set this app's presence session (`setPresence`); `session_id` is the
+/// app's client ID.
+#[command]
+pub async fn presence_set_session(
+    presence: State<'_, PresenceService>,
+    session_id: String,
+    availability: String,
+    activity: String,
+    expiration_minutes: Option<u64>,
+) -> Result<(), GraphError> {
+    presence
+        .set_session(
+            session_id,
+            availability,
+            activity,
+            expiration_minutes.map(|m| Duration::from_secs(m * 60)),
+        )
+        .await
+}
+
+/// Set the status message; an empty message clears it. `expires_at` is RFC 3339.
+#[command]
+pub async fn presence_set_status_message(
+    presence: State<'_, PresenceService>,
+    message: String,
+    expires_at: Option<String>,
+) -> Result<(), GraphError> {
+    presence
+        .set_status_message(message, expires_at)
+        .await
+}
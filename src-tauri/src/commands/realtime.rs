@@ -0,0 +1,37 @@
+use tauri::{command, AppHandle, State};
+
+use crate::realtime::{RealtimeConfig, RealtimeStatus, SubscriptionManager};
+
+#[command]
+pub fn realtime_get_config(app: AppHandle) -> RealtimeConfig {
+    RealtimeConfig::load(&app)
+}
+
+/// Save the relay/certificate settings. Takes effect on the next `realtime_start`.
+#[command]
+pub fn realtime_set_config(app: AppHandle, config: RealtimeConfig) -> Result<(), String> {
+    config.save(&app)
+}
+
+/// Subscribe to change notifications. `app_id` is the Azure AD client ID
+/// validation tokens must be issued for.
+#[command]
+pub async fn realtime_start(
+    realtime: State<'_, SubscriptionManager>,
+    app_id: String,
+) -> Result<RealtimeStatus, String> {
+    realtime.start(app_id).await
+}
+
+#[command]
+pub async fn realtime_stop(realtime: State<'_, SubscriptionManager>) -> Result<(), String> {
+    realtime.stop().await;
+    Ok(())
+}
+
+#[command]
+pub async fn realtime_status(
+    realtime: State<'_, SubscriptionManager>,
+) -> Result<RealtimeStatus, String> {
+    Ok(realtime.status().await)
+}
@@ -0,0 +1,48 @@
+use tauri::{command, State};
+
+use crate::sync::{ChannelRef, SyncEngine, SyncStatus, UnreadSnapshot};
+
+/// Start background sync for the signed-in user. Restarting is cheap and
+/// keeps watched channels.
+#[command]
+pub fn sync_start(sync: State<'_, SyncEngine>, user_id: String) {
+    sync.start(user_id);
+}
+
+#[command]
+pub fn sync_stop(sync: State<'_, SyncEngine>) {
+    sync.stop();
+}
+
+#[command]
+pub fn sync_status(sync: State<'_, SyncEngine>) -> SyncStatus {
+    sync.status()
+}
+
+/// Replace the set of channels peeked for new messages. There is no cap;
+/// requests are paced by the shared Graph throttle.
+#[command]
+pub fn sync_set_watched_channels(sync: State<'_, SyncEngine>, channels: Vec<ChannelRef>) {
+    sync.set_watched_channels(channels);
+}
+
+/// `key` is a chat ID or `<teamId>:<channelId>`; `None` when nothing is open.
+#[command]
+pub fn sync_set_active_conversation(sync: State<'_, SyncEngine>, key: Option<String>) {
+    sync.set_active_conversation(key);
+}
+
+#[command]
+pub fn sync_mark_read(sync: State<'_, SyncEngine>, key: String, timestamp: String) {
+    sync.mark_read(&key, &timestamp);
+}
+
+#[command]
+pub fn sync_get_unread(sync: State<'_, SyncEngine>) -> UnreadSnapshot {
+    sync.unread()
+}
+
+#[command]
+pub fn sync_poll_now(sync: State<'_, SyncEngine>) {
+    sync.poll_now();
+}
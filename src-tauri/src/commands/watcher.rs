@@ -0,0 +1,290 @@
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use notify_debouncer_mini::notify::{self, RecommendedWatcher, RecursiveMode};
+use notify_debouncer_mini::{new_debouncer, DebounceEventResult, Debouncer};
+use tauri::{command, AppHandle, Emitter, State};
+
+use super::filesystem::{resolve_safe_path, SKIP_DIRS};
+
+/// Changes arriving within this window are delivered as a single event.
+const DEBOUNCE: Duration = Duration::from_millis(300);
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct FileChange {
+    /// Path relative to the project root.
+    pub path: String,
+    /// `false` when the path was removed (or renamed away).
+    pub exists: bool,
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+struct FsChangedPayload {
+    watch_id: u32,
+    changes: Vec<FileChange>,
+}
+
+/// Managed state holding the active workspace watchers, keyed by watch ID.
+#[derive(Default)]
+pub struct FsWatchers {
+    watchers: Mutex<HashMap<u32, Arc<Mutex<Watch>>>>,
+    next_id: AtomicU32,
+}
+
+/// One workspace watch. A recursive watch gives every directory that isn't
+/// skipped or gitignored an OS watch of its own, so trees like `node_modules`
+/// never use up the watch limit, and picks up new directories as they appear.
+struct Watch {
+    /// Set right after creation; the debouncer's handler needs the watch too.
+    debouncer: Option<Debouncer<RecommendedWatcher>>,
+    root: PathBuf,
+    gitignore: Gitignore,
+    /// Directories watched so far.
+    dirs: HashSet<PathBuf>,
+}
+
+impl Watch {
+    /// Watch `dir` and the directories under it that aren't filtered out.
+    fn add_tree(&mut self, dir: &Path) -> notify::Result<()> {
+        let Some(debouncer) = self.debouncer.as_mut() else {
+            return Ok(());
+        };
+        for dir in watched_dirs(&self.root, &self.gitignore, dir) {
+            if !self.dirs.contains(&dir) {
+                debouncer.watcher().watch(&dir, RecursiveMode::NonRecursive)?;
+                self.dirs.insert(dir);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Watch a path inside the project root and emit debounced `fs:changed` events.
+///
+/// Paths under `SKIP_DIRS` and anything matched by the root `.gitignore` are
+/// neither watched nor reported. Returns an ID for `fs_unwatch`.
+#[command]
+pub fn fs_watch(
+    app: AppHandle,
+    watchers: State<'_, FsWatchers>,
+    project_root: String,
+    path: String,
+    recursive: Option<bool>,
+) -> Result<u32, String> {
+    let resolved = resolve_safe_path(&project_root, &path)?;
+    let root = Path::new(&project_root)
+        .canonicalize()
+        .map_err(|e| format!("Invalid project root: {}", e))?;
+
+    let id = watchers.next_id.fetch_add(1, Ordering::SeqCst) + 1;
+    let recursive = recursive.unwrap_or(true) && resolved.is_dir();
+    let watch = Arc::new(Mutex::new(Watch {
+        debouncer: None,
+        gitignore: load_gitignore(&root),
+        root,
+        dirs: HashSet::new(),
+    }));
+    // Weak, so dropping the watch from `FsWatchers` stops it
+    let handler_watch = Arc::downgrade(&watch);
+
+    let debouncer = new_debouncer(DEBOUNCE, move |result: DebounceEventResult| {
+        let events = match result {
+            Ok(events) => events,
+            Err(e) => {
+                let _ = app.emit("fs:watch-error", format!("Watcher error: {}", e));
+                return;
+            }
+        };
+        let Some(watch) = handler_watch.upgrade() else {
+            return;
+        };
+        let mut watch = watch.lock().unwrap();
+
+        let changes: Vec<FileChange> = events
+            .into_iter()
+            .filter(|event| !is_ignored(&watch.root, &watch.gitignore, &event.path))
+            .map(|event| FileChange {
+                path: event
+                    .path
+                    .strip_prefix(&watch.root)
+                    .unwrap_or(&event.path)
+                    .to_string_lossy()
+                    .to_string(),
+                exists: event.path.exists(),
+            })
+            .collect();
+
+        if recursive {
+            for change in &changes {
+                let path = watch.root.join(&change.path);
+                if !change.exists {
+                    // Watched again should it come back
+                    watch.dirs.retain(|dir| !dir.starts_with(&path));
+                } else if path.is_dir() && !watch.dirs.contains(&path) {
+                    if let Err(e) = watch.add_tree(&path) {
+                        let _ = app.emit("fs:watch-error", format!("Failed to watch path: {}", e));
+                    }
+                }
+            }
+        }
+
+        if !changes.is_empty() {
+            let _ = app.emit(
+                "fs:changed",
+                FsChangedPayload {
+                    watch_id: id,
+                    changes,
+                },
+            );
+        }
+    })
+    .map_err(|e| format!("Failed to create watcher: {}", e))?;
+
+    {
+        let mut watch = watch.lock().unwrap();
+        let watch = &mut *watch;
+        watch.debouncer = Some(debouncer);
+        let watched = if recursive {
+            watch.add_tree(&resolved)
+        } else {
+            let debouncer = watch.debouncer.as_mut().unwrap();
+            debouncer.watcher().watch(&resolved, RecursiveMode::NonRecursive)
+        };
+        watched.map_err(|e| format!("Failed to watch path: {}", e))?;
+    }
+
+    watchers.watchers.lock().unwrap().insert(id, watch);
+    Ok(id)
+}
+
+#[command]
+pub fn fs_unwatch(watchers: State<'_, FsWatchers>, id: u32) -> Result<(), String> {
+    // Dropping the debouncer stops its watcher thread
+    watchers
+        .watchers
+        .lock()
+        .unwrap()
+        .remove(&id)
+        .map(|_| ())
+        .ok_or_else(|| format!("No watcher with id {}", id))
+}
+
+fn load_gitignore(root: &Path) -> Gitignore {
+    let mut builder = GitignoreBuilder::new(root);
+    // A missing .gitignore just means nothing is ignored
+    let _ = builder.add(root.join(".gitignore"));
+    builder.build().unwrap_or_else(|_| Gitignore::empty())
+}
+
+/// `dir` and every directory below it that isn't filtered out. Symlinked
+/// directories aren't followed.
+fn watched_dirs(root: &Path, gitignore: &Gitignore, dir: &Path) -> Vec<PathBuf> {
+    let mut dirs = Vec::new();
+    let mut pending = vec![dir.to_path_buf()];
+    while let Some(dir) = pending.pop() {
+        if let Ok(entries) = fs::read_dir(&dir) {
+            for entry in entries.flatten() {
+                let path = entry.path();
+                if entry.file_type().is_ok_and(|t| t.is_dir()) && !is_ignored(root, gitignore, &path) {
+                    pending.push(path);
+                }
+            }
+        }
+        dirs.push(dir);
+    }
+    dirs
+}
+
+fn is_ignored(root: &Path, gitignore: &Gitignore, path: &Path) -> bool {
+    let relative = match path.strip_prefix(root) {
+        Ok(relative) => relative,
+        Err(_) => return true,
+    };
+
+    let in_skip_dir = relative.components().any(|c| {
+        c.as_os_str()
+            .to_str()
+            .is_some_and(|name| SKIP_DIRS.contains(&name))
+    });
+    if in_skip_dir {
+        return true;
+    }
+
+    gitignore
+        .matched_path_or_any_parents(relative, path.is_dir())
+        .is_ignore()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn workspace(gitignore: &str, paths: &[&str]) -> (tempfile::TempDir, PathBuf, Gitignore) {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().canonicalize().unwrap();
+        fs::write(root.join(".gitignore"), gitignore).unwrap();
+        for path in paths {
+            let path = root.join(path);
+            if path.extension().is_some() {
+                fs::create_dir_all(path.parent().unwrap()).unwrap();
+                fs::write(path, "").unwrap();
+            } else {
+                fs::create_dir_all(path).unwrap();
+            }
+        }
+        let gitignore = load_gitignore(&root);
+        (dir, root, gitignore)
+    }
+
+    #[test]
+    fn skips_build_and_vcs_dirs() {
+        let (_dir, root, gitignore) = workspace("", &[]);
+        for skipped in ["node_modules/pkg/index.js", ".git/HEAD", "target/debug/app", "app/.nuxt/x.js"] {
+            assert!(is_ignored(&root, &gitignore, &root.join(skipped)), "{}", skipped);
+        }
+        assert!(!is_ignored(&root, &gitignore, &root.join("src/main.rs")));
+        // Only whole components count
+        assert!(!is_ignored(&root, &gitignore, &root.join("targets/main.rs")));
+    }
+
+    #[test]
+    fn skips_gitignored_paths() {
+        let (_dir, root, gitignore) = workspace("*.log\n/build/\n!keep.log\n", &["build", "src/build"]);
+        assert!(is_ignored(&root, &gitignore, &root.join("debug.log")));
+        assert!(is_ignored(&root, &gitignore, &root.join("src/debug.log")));
+        assert!(!is_ignored(&root, &gitignore, &root.join("keep.log")));
+        // Files under an ignored directory are ignored too
+        assert!(is_ignored(&root, &gitignore, &root.join("build/out.js")));
+        // Anchored to the root
+        assert!(!is_ignored(&root, &gitignore, &root.join("src/build/out.js")));
+    }
+
+    #[test]
+    fn skips_paths_outside_the_root() {
+        let (_dir, root, gitignore) = workspace("", &[]);
+        assert!(is_ignored(&root, &gitignore, &root.parent().unwrap().join("elsewhere.rs")));
+        assert!(is_ignored(&root, &gitignore, Path::new("/etc/passwd")));
+    }
+
+    #[test]
+    fn watches_only_dirs_that_are_reported() {
+        let (_dir, root, gitignore) = workspace(
+            "/dist-old/\n",
+            &["src/a/b", "src/main.rs", "node_modules/pkg/lib", "dist-old/x", ".git/objects", "docs"],
+        );
+        let mut dirs: Vec<String> = watched_dirs(&root, &gitignore, &root)
+            .iter()
+            .map(|d| d.strip_prefix(&root).unwrap().to_string_lossy().to_string())
+            .collect();
+        dirs.sort();
+        assert_eq!(dirs, ["", "docs", "src", "src/a", "src/a/b"]);
+
+        let below: Vec<_> = watched_dirs(&root, &gitignore, &root.join("src/a"));
+        assert_eq!(below.len(), 2);
+    }
+}
@@ -0,0 +1,283 @@
+//! Online/offline state machine.
+//!
+//! Combines two signals: the outcome of every Graph request (reported by the
+//! client through [`GraphClient::on_outcome`]) and periodic reachability
+//! probes of the Graph and login endpoints. The resulting state is emitted as
+//! `connectivity:changed`, shown in the tray tooltip, and drives the rest of
+//! the backend: background sync and presence refresh pause while offline or
+//! signed out, and the outbox is flushed as soon as the network comes back.
+
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use chrono::{SecondsFormat, Utc};
+use serde::Serialize;
+use tauri::{AppHandle, Emitter};
+use tokio::sync::Notify;
+
+use crate::graph::{GraphClient, RequestOutcome};
+use crate::outbox::Outbox;
+use crate::presence::PresenceService;
+use crate::sync::SyncEngine;
+use crate::tray;
+
+/// Any HTTP response (even 401/404) proves the host is reachable; these are
+/// small, unauthenticated endpoints.
+const GRAPH_PROBE_URL: &str = "https://graph.microsoft.com/v1.0/";
+const LOGIN_PROBE_URL: &str = "https://login.microsoftonline.com/common/v2.0/.well-known/openid-configuration";
+const PROBE_TIMEOUT: Duration = Duration::from_secs(5);
+/// Probes slower than this count as a degraded connection.
+const SLOW_PROBE: Duration = Duration::from_secs(3);
+/// Consecutive 5xx/throttled requests before the connection counts as degraded.
+const SERVER_ERROR_THRESHOLD: u32 = 3;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConnectivityState {
+    Online,
+    /// Graph is reachable, but slow, sign-in is unreachable or Graph is failing.
+    Degraded,
+    Offline,
+    /// The network is fine but there's no valid token; the user must sign in.
+    AuthExpired,
+}
+
+impl ConnectivityState {
+    /// How often to probe in this state.
+    fn probe_interval(self) -> Duration {
+        match self {
+            Self::Online => Duration::from_secs(60),
+            Self::Degraded => Duration::from_secs(20),
+            Self::Offline => Duration::from_secs(10),
+            Self::AuthExpired => Duration::from_secs(60),
+        }
+    }
+
+    fn tray_note(self) -> Option<&'static str> {
+        match self {
+            Self::Online => None,
+            Self::Degraded => Some("Connection degraded"),
+            Self::Offline => Some("Offline"),
+            Self::AuthExpired => Some("Signed out"),
+        }
+    }
+
+    /// Whether background work can usefully run.
+    fn is_usable(self) -> bool {
+        matches!(self, Self::Online | Self::Degraded)
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConnectivityStatus {
+    pub state: ConnectivityState,
+    /// When the current state was entered.
+    pub since: String,
+    pub graph_reachable: bool,
+    pub login_reachable: bool,
+    /// Slowest probe of the last round.
+    pub latency_ms: Option<u64>,
+    pub detail: Option<String>,
+}
+
+#[derive(Clone)]
+pub struct Connectivity {
+    inner: Arc<Inner>,
+}
+
+struct Inner {
+    app: AppHandle,
+    sync: SyncEngine,
+    presence: PresenceService,
+    outbox: Outbox,
+    http: reqwest::Client,
+    status: Mutex<ConnectivityStatus>,
+    signals: Mutex<Signals>,
+    wake: Notify,
+}
+
+/// Evidence gathered from real traffic since the last probe.
+#[derive(Default)]
+struct Signals {
+    auth_expired: bool,
+    server_errors: u32,
+}
+
+impl Connectivity {
+    pub fn new(app: AppHandle, graph: &GraphClient, sync: SyncEngine, presence: PresenceService, outbox: Outbox) -> Self {
+        let connectivity = Self {
+            inner: Arc::new(Inner {
+                app,
+                sync,
+                presence,
+                outbox,
+                http: reqwest::Client::builder()
+                    .timeout(PROBE_TIMEOUT)
+                    .build()
+                    .unwrap_or_default(),
+                status: Mutex::new(ConnectivityStatus {
+                    state: ConnectivityState::Online,
+                    since: now(),
+                    graph_reachable: true,
+                    login_reachable: true,
+                    latency_ms: None,
+                    detail: None,
+                }),
+                signals: Mutex::new(Signals::default()),
+                wake: Notify::new(),
+            }),
+        };
+
+        let inner = connectivity.inner.clone();
+        graph.on_outcome(move |outcome| inner.observe(outcome));
+        connectivity
+    }
+
+    /// Spawn the probe loop. Call once.
+    pub fn start(&self) {
+        let inner = self.inner.clone();
+        tauri::async_runtime::spawn(async move {
+            inner.run().await;
+        });
+    }
+
+    pub fn status(&self) -> ConnectivityStatus {
+        self.inner.status.lock().unwrap().clone()
+    }
+
+    /// Probe right away, e.g. when the OS reports the network changed.
+    pub fn check_now(&self) {
+        self.inner.wake.notify_one();
+    }
+
+    /// A new token arrived from the auth flow. Sync is paused while signed
+    /// out, so no request would otherwise come along to clear the state.
+    pub fn token_updated(&self) {
+        self.inner.signals.lock().unwrap().auth_expired = false;
+        self.inner.wake.notify_one();
+    }
+}
+
+impl Inner {
+    async fn run(&self) {
+        loop {
+            self.probe().await;
+            let interval = self.status.lock().unwrap().state.probe_interval();
+            tokio::select! {
+                _ = tokio::time::sleep(interval) => {}
+                _ = self.wake.notified() => {}
+            }
+        }
+    }
+
+    /// Called for every finished Graph request.
+    fn observe(&self, outcome: RequestOutcome) {
+        let state = self.status.lock().unwrap().state;
+        match outcome {
+            RequestOutcome::Success => {
+                *self.signals.lock().unwrap() = Signals::default();
+                // A successful request is proof enough; no need to wait for a probe
+                if state != ConnectivityState::Online {
+                    self.wake.notify_one();
+                }
+            }
+            RequestOutcome::AuthExpired => {
+                self.signals.lock().unwrap().auth_expired = true;
+                if state != ConnectivityState::AuthExpired {
+                    self.wake.notify_one();
+                }
+            }
+            RequestOutcome::ServerError => {
+                let mut signals = self.signals.lock().unwrap();
+                signals.server_errors += 1;
+                if signals.server_errors == SERVER_ERROR_THRESHOLD {
+                    self.wake.notify_one();
+                }
+            }
+            RequestOutcome::NetworkError => {
+                if state != ConnectivityState::Offline {
+                    self.wake.notify_one();
+                }
+            }
+            RequestOutcome::OtherError => {}
+        }
+    }
+
+    async fn probe(&self) {
+        let (graph, login) = tokio::join!(self.reach(GRAPH_PROBE_URL), self.reach(LOGIN_PROBE_URL));
+        let latency = graph.max(login);
+        let graph_reachable = graph.is_some();
+        let login_reachable = login.is_some();
+
+        let (auth_expired, server_errors) = {
+            let signals = self.signals.lock().unwrap();
+            (signals.auth_expired, signals.server_errors)
+        };
+
+        let (state, detail) = match (graph_reachable, login_reachable) {
+            (false, false) => (ConnectivityState::Offline, None),
+            // Login alone is no use; nothing in the app works without Graph
+            (false, true) => (ConnectivityState::Offline, Some("Microsoft Graph is unreachable")),
+            _ if auth_expired => (ConnectivityState::AuthExpired, None),
+            (true, false) => (ConnectivityState::Degraded, Some("Sign-in service is unreachable")),
+            _ if server_errors >= SERVER_ERROR_THRESHOLD => {
+                (ConnectivityState::Degraded, Some("Microsoft Graph is returning errors"))
+            }
+            _ if latency.is_some_and(|l| l > SLOW_PROBE) => (ConnectivityState::Degraded, Some("Slow connection")),
+            _ => (ConnectivityState::Online, None),
+        };
+
+        self.apply(ConnectivityStatus {
+            state,
+            since: String::new(),
+            graph_reachable,
+            login_reachable,
+            latency_ms: latency.map(|l| l.as_millis() as u64),
+            detail: detail.map(str::to_string),
+        });
+    }
+
+    /// Round-trip time if the host answered at all.
+    async fn reach(&self, url: &str) -> Option<Duration> {
+        let started = Instant::now();
+        self.http.head(url).send().await.ok()?;
+        Some(started.elapsed())
+    }
+
+    fn apply(&self, mut next: ConnectivityStatus) {
+        let previous = {
+            let mut status = self.status.lock().unwrap();
+            let previous = status.state;
+            next.since = if previous == next.state {
+                status.since.clone()
+            } else {
+                now()
+            };
+            let unchanged = previous == next.state && status.detail == next.detail;
+            *status = next.clone();
+            if unchanged {
+                return;
+            }
+            previous
+        };
+
+        let _ = self.app.emit("connectivity:changed", &next);
+        if previous == next.state {
+            return;
+        }
+
+        if let Err(e) = tray::update_connectivity(&self.app, next.state.tray_note()) {
+            eprintln!("[connectivity] Failed to update tray: {}", e);
+        }
+        self.sync.set_paused(!next.state.is_usable());
+        self.presence.set_paused(!next.state.is_usable());
+        if next.state.is_usable() && !previous.is_usable() {
+            self.outbox.flush();
+        }
+    }
+}
+
+fn now() -> String {
+    Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true)
+}
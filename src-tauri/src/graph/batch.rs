@@ -0,0 +1,372 @@
+//! JSON batching (`POST /$batch`).
+//!
+//! [`GraphClient::batch`] sends an explicit list of requests, splitting it
+//! into batches of at most [`MAX_BATCH_SIZE`] while keeping every `dependsOn`
+//! chain in the same batch. Throttled items are retried on their own after
+//! `Retry-After`; other per-item failures are returned as-is.
+//!
+//! [`Coalescer`] sits behind [`GraphClient::send_batched`], which callers that
+//! fire many requests at once opt into: requests issued within a short window
+//! are queued and flushed as one batch, so polling many channels costs one
+//! round trip per twenty channels instead of one each. Single requests use
+//! [`GraphClient::send`] and don't pay for the window.
+
+use std::collections::HashMap;
+use std::mem;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use reqwest::Method;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tokio::sync::oneshot;
+
+use super::{GraphClient, GraphError};
+
+/// Graph rejects batches with more than 20 requests.
+pub const MAX_BATCH_SIZE: usize = 20;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BatchRequest {
+    /// Unique within the batch; responses are matched back by it.
+    pub id: String,
+    pub method: String,
+    /// Path relative to the Graph version root (e.g. `/me/chats`). Absolute
+    /// Graph URLs such as next links are accepted and made relative.
+    pub url: String,
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub headers: HashMap<String, String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub body: Option<Value>,
+    /// IDs of requests that must succeed before this one runs.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub depends_on: Vec<String>,
+}
+
+impl BatchRequest {
+    pub fn get(id: impl Into<String>, url: impl Into<String>) -> Self {
+        Self {
+            id: id.into(),
+            method: "GET".to_string(),
+            url: url.into(),
+            headers: HashMap::new(),
+            body: None,
+            depends_on: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BatchResponse {
+    pub id: String,
+    pub status: u16,
+    #[serde(default)]
+    pub headers: HashMap<String, String>,
+    #[serde(default)]
+    pub body: Value,
+}
+
+impl BatchResponse {
+    pub fn is_success(&self) -> bool {
+        (200..300).contains(&self.status)
+    }
+
+    /// The body of a successful item, or its error in the same shape the
+    /// client returns for standalone requests.
+    pub fn into_result(self) -> Result<Value, GraphError> {
+        if self.is_success() {
+            return Ok(self.body);
+        }
+        let error = self.body.get("error");
+        Err(GraphError {
+            status: Some(self.status),
+            code: error
+                .and_then(|e| e["code"].as_str())
+                .map(str::to_string)
+                .unwrap_or_else(|| self.status.to_string()),
+            message: error
+                .and_then(|e| e["message"].as_str())
+                .map(str::to_string)
+                .unwrap_or_else(|| format!("Batched request failed with status {}", self.status)),
+        })
+    }
+
+    fn is_throttled(&self) -> bool {
+        matches!(self.status, 429 | 503 | 504)
+    }
+
+    fn retry_after(&self) -> Option<Duration> {
+        self.headers
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case("retry-after"))
+            .and_then(|(_, value)| value.trim().parse::<u64>().ok())
+            .map(Duration::from_secs)
+    }
+}
+
+impl GraphClient {
+    /// Send `requests` as one or more `$batch` calls and return a response
+    /// for every request, in the same order.
+    pub async fn batch(&self, requests: Vec<BatchRequest>) -> Result<Vec<BatchResponse>, GraphError> {
+        let mut requests = requests;
+        for request in &mut requests {
+            request.url = self.batch_url(&request.url)?;
+            if request.body.is_some() {
+                request
+                    .headers
+                    .entry("Content-Type".to_string())
+                    .or_insert_with(|| "application/json".to_string());
+            }
+        }
+        let order: Vec<String> = requests.iter().map(|r| r.id.clone()).collect();
+
+        let mut responses = HashMap::new();
+        for chunk in chunk_by_dependencies(requests)? {
+            for response in self.send_chunk(chunk).await? {
+                responses.insert(response.id.clone(), response);
+            }
+        }
+
+        Ok(order
+            .into_iter()
+            .map(|id| {
+                responses.remove(&id).unwrap_or_else(|| BatchResponse {
+                    body: json!({ "error": { "code": "missingResponse", "message": "No response for batched request" } }),
+                    id,
+                    status: 502,
+                    headers: HashMap::new(),
+                })
+            })
+            .collect())
+    }
+
+    /// One `$batch` round trip, then retry throttled items that nothing else
+    /// depends on until they succeed or retries run out.
+    async fn send_chunk(&self, chunk: Vec<BatchRequest>) -> Result<Vec<BatchResponse>, GraphError> {
+        let mut pending = chunk;
+        let mut done = Vec::new();
+        let mut attempt = 0;
+
+        loop {
+            let value = self
+                .send(Method::POST, "/$batch", Some(&json!({ "requests": pending })))
+                .await?;
+            let responses: Vec<BatchResponse> = serde_json::from_value(value["responses"].clone())
+                .map_err(|e| GraphError::new("invalid_response", format!("Unexpected $batch response: {}", e)))?;
+
+            let linked: Vec<&str> = pending
+                .iter()
+                .flat_map(|r| r.depends_on.iter().map(String::as_str))
+                .collect();
+            let mut retry = Vec::new();
+            let mut delay = Duration::ZERO;
+            for response in responses {
+                let request = pending.iter().find(|r| r.id == response.id);
+                let standalone = request.is_some_and(|r| r.depends_on.is_empty())
+                    && !linked.contains(&response.id.as_str());
+                if response.is_throttled() && standalone && attempt < self.config().max_retries {
+                    delay = delay.max(response.retry_after().unwrap_or_else(|| self.backoff(attempt)));
+                    retry.extend(request.cloned());
+                } else {
+                    done.push(response);
+                }
+            }
+
+            if retry.is_empty() {
+                return Ok(done);
+            }
+            tokio::time::sleep(delay.min(self.config().max_delay)).await;
+            attempt += 1;
+            pending = retry;
+        }
+    }
+
+    /// Batch URLs are relative to the version root.
+    fn batch_url(&self, url: &str) -> Result<String, GraphError> {
+        let absolute = self.url(url)?;
+        let base = self.config().base_url.trim_end_matches('/');
+        Ok(absolute.strip_prefix(base).unwrap_or(&absolute).to_string())
+    }
+}
+
+/// Split requests into batches of at most [`MAX_BATCH_SIZE`], keeping each
+/// group of requests linked by `dependsOn` together and otherwise preserving
+/// order.
+fn chunk_by_dependencies(requests: Vec<BatchRequest>) -> Result<Vec<Vec<BatchRequest>>, GraphError> {
+    let index: HashMap<&str, usize> = requests.iter().enumerate().map(|(i, r)| (r.id.as_str(), i)).collect();
+    if index.len() != requests.len() {
+        return Err(GraphError::new("invalid_batch", "Batch request IDs must be unique"));
+    }
+
+    // Union-find over dependency links
+    let mut parent: Vec<usize> = (0..requests.len()).collect();
+    fn root(parent: &mut [usize], mut i: usize) -> usize {
+        while parent[i] != i {
+            parent[i] = parent[parent[i]];
+            i = parent[i];
+        }
+        i
+    }
+    for (i, request) in requests.iter().enumerate() {
+        for dependency in &request.depends_on {
+            let Some(&j) = index.get(dependency.as_str()) else {
+                return Err(GraphError::new(
+                    "invalid_batch",
+                    format!("Request {} depends on unknown request {}", request.id, dependency),
+                ));
+            };
+            let (a, b) = (root(&mut parent, i), root(&mut parent, j));
+            parent[a] = b;
+        }
+    }
+
+    let mut groups: Vec<(usize, Vec<BatchRequest>)> = Vec::new();
+    for (i, request) in requests.into_iter().enumerate() {
+        let group = root(&mut parent, i);
+        match groups.iter_mut().find(|(g, _)| *g == group) {
+            Some((_, members)) => members.push(request),
+            None => groups.push((group, vec![request])),
+        }
+    }
+
+    let mut chunks: Vec<Vec<BatchRequest>> = Vec::new();
+    for (_, group) in groups {
+        if group.len() > MAX_BATCH_SIZE {
+            return Err(GraphError::new(
+                "invalid_batch",
+                format!("A dependsOn chain may span at most {} requests", MAX_BATCH_SIZE),
+            ));
+        }
+        match chunks.iter_mut().find(|c| c.len() + group.len() <= MAX_BATCH_SIZE) {
+            Some(chunk) => chunk.extend(group),
+            None => chunks.push(group),
+        }
+    }
+    Ok(chunks)
+}
+
+struct Queued {
+    method: Method,
+    path: String,
+    body: Option<Value>,
+    reply: oneshot::Sender<Result<Value, GraphError>>,
+}
+
+/// Collects requests made within [`GraphConfig::batch_window`] of each other.
+///
+/// [`GraphConfig::batch_window`]: super::GraphConfig::batch_window
+#[derive(Default)]
+pub(super) struct Coalescer {
+    queue: Mutex<Vec<Queued>>,
+    /// Whether a flush is already scheduled for the current window.
+    scheduled: Mutex<bool>,
+}
+
+impl GraphClient {
+    /// Like [`send`](Self::send), but shares a `$batch` call with any other
+    /// requests made at about the same time. A request that ends up alone is
+    /// sent directly.
+    pub async fn send_batched(&self, method: Method, path: &str, body: Option<&Value>) -> Result<Value, GraphError> {
+        let (reply, response) = oneshot::channel();
+        let coalescer = self.coalescer();
+        let full = {
+            let mut queue = coalescer.queue.lock().unwrap();
+            queue.push(Queued {
+                method,
+                path: path.to_string(),
+                body: body.cloned(),
+                reply,
+            });
+            queue.len() >= MAX_BATCH_SIZE
+        };
+
+        let client = self.clone();
+        if full {
+            tauri::async_runtime::spawn(async move { client.flush_batch().await });
+        } else if !mem::replace(&mut *coalescer.scheduled.lock().unwrap(), true) {
+            tauri::async_runtime::spawn(async move {
+                tokio::time::sleep(client.config().batch_window).await;
+                *client.coalescer().scheduled.lock().unwrap() = false;
+                client.flush_batch().await;
+            });
+        }
+
+        response
+            .await
+            .unwrap_or_else(|_| Err(GraphError::new("network", "Batched request was dropped")))
+    }
+
+    pub async fn get_batched<T: serde::de::DeserializeOwned>(&self, path: &str) -> Result<T, GraphError> {
+        let value = self.send_batched(Method::GET, path, None).await?;
+        super::client::decode(value)
+    }
+
+    async fn flush_batch(&self) {
+        let queued = mem::take(&mut *self.coalescer().queue.lock().unwrap());
+
+        // One bad URL mustn't fail everyone else's requests
+        let mut valid = Vec::with_capacity(queued.len());
+        for q in queued {
+            match self.batch_url(&q.path) {
+                Ok(_) => valid.push(q),
+                Err(e) => {
+                    let _ = q.reply.send(Err(e));
+                }
+            }
+        }
+        let queued = valid;
+        if queued.is_empty() {
+            return;
+        }
+
+        if queued.len() == 1 {
+            let Queued { method, path, body, reply } = queued.into_iter().next().unwrap();
+            let _ = reply.send(self.send(method, &path, body.as_ref()).await);
+            return;
+        }
+
+        for chunk in chunk_queue(queued) {
+            let requests = chunk
+                .iter()
+                .enumerate()
+                .map(|(i, q)| BatchRequest {
+                    id: (i + 1).to_string(),
+                    method: q.method.to_string(),
+                    url: q.path.clone(),
+                    headers: HashMap::new(),
+                    body: q.body.clone(),
+                    depends_on: Vec::new(),
+                })
+                .collect();
+            match self.batch(requests).await {
+                Ok(responses) => {
+                    for (queued, response) in chunk.into_iter().zip(responses) {
+                        let _ = queued.reply.send(response.into_result());
+                    }
+                }
+                Err(e) => {
+                    for queued in chunk {
+                        let _ = queued.reply.send(Err(e.clone()));
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn chunk_queue(queued: Vec<Queued>) -> Vec<Vec<Queued>> {
+    let mut chunks = Vec::new();
+    let mut current = Vec::new();
+    for q in queued {
+        current.push(q);
+        if current.len() == MAX_BATCH_SIZE {
+            chunks.push(mem::take(&mut current));
+        }
+    }
+    if !current.is_empty() {
+        chunks.push(current);
+    }
+    chunks
+}
@@ -0,0 +1,410 @@
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use futures_util::stream::{self, Stream};
+use reqwest::header::{HeaderMap, HeaderValue, AUTHORIZATION, CONTENT_TYPE, RETRY_AFTER};
+use reqwest::{Method, StatusCode};
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+
+use super::batch::Coalescer;
+use super::throttle::Throttle;
+use super::token::TokenStore;
+use super::types::{Delta, Page};
+use super::{GraphError, GRAPH_BASE_URL};
+
+/// What a finished request says about connectivity; see [`GraphClient::on_outcome`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RequestOutcome {
+    Success,
+    /// No response at all (DNS, connect, timeout).
+    NetworkError,
+    /// 5xx or throttling that outlasted the retries.
+    ServerError,
+    /// No usable token and the auth flow didn't supply one.
+    AuthExpired,
+    /// Any other error response (404, 403, ...), which says nothing about the network.
+    OtherError,
+}
+
+type OutcomeHook = Box<dyn Fn(RequestOutcome) + Send + Sync>;
+
+/// A request body. The typed helpers send JSON; bodies passed through from
+/// the webview may also be text or raw bytes.
+#[derive(Debug, Clone, Copy)]
+pub enum Body<'a> {
+    Json(&'a Value),
+    /// Sent as `text/plain` unless a `Content-Type` header says otherwise.
+    Text(&'a str),
+    /// Sent as `application/octet-stream` unless a `Content-Type` header says otherwise.
+    Bytes(&'a [u8]),
+}
+
+#[derive(Debug, Clone)]
+pub struct GraphConfig {
+    pub base_url: String,
+    /// Retries for throttled (429/503/504) responses and transient network errors.
+    pub max_retries: u32,
+    /// Maximum requests in flight across all callers.
+    pub max_concurrency: usize,
+    /// Sustained request rate across all callers.
+    pub requests_per_second: f64,
+    /// Backoff base used when the server doesn't send `Retry-After`.
+    pub base_delay: Duration,
+    /// Upper bound for a single backoff.
+    pub max_delay: Duration,
+    /// How long to wait for the auth flow to supply a refreshed token.
+    pub token_wait: Duration,
+    /// How long [`GraphClient::send_batched`] holds a request so concurrent
+    /// ones can share a `$batch` call.
+    pub batch_window: Duration,
+}
+
+impl Default for GraphConfig {
+    fn default() -> Self {
+        Self {
+            base_url: GRAPH_BASE_URL.to_string(),
+            max_retries: 5,
+            max_concurrency: 4,
+            requests_per_second: 8.0,
+            base_delay: Duration::from_secs(1),
+            max_delay: Duration::from_secs(60),
+            token_wait: Duration::from_secs(30),
+            batch_window: Duration::from_millis(10),
+        }
+    }
+}
+
+/// Typed async Graph client. Cheap to clone; clones share the token and throttle.
+#[derive(Clone)]
+pub struct GraphClient {
+    inner: Arc<Inner>,
+}
+
+struct Inner {
+    http: reqwest::Client,
+    config: GraphConfig,
+    tokens: TokenStore,
+    throttle: Throttle,
+    coalescer: Coalescer,
+    on_outcome: Mutex<Option<OutcomeHook>>,
+}
+
+impl GraphClient {
+    pub fn new(config: GraphConfig) -> Self {
+        Self {
+            inner: Arc::new(Inner {
+                http: reqwest::Client::new(),
+                tokens: TokenStore::new(config.token_wait),
+                throttle: Throttle::new(config.max_concurrency, config.requests_per_second),
+                coalescer: Coalescer::default(),
+                on_outcome: Mutex::new(None),
+                config,
+            }),
+        }
+    }
+
+    pub fn tokens(&self) -> &TokenStore {
+        &self.inner.tokens
+    }
+
+    pub fn config(&self) -> &GraphConfig {
+        &self.inner.config
+    }
+
+    /// Register a callback told how every request ended, so connectivity can
+    /// be tracked from real traffic instead of only from probes.
+    pub fn on_outcome(&self, hook: impl Fn(RequestOutcome) + Send + Sync + 'static) {
+        *self.inner.on_outcome.lock().unwrap() = Some(Box::new(hook));
+    }
+
+    pub(super) fn coalescer(&self) -> &Coalescer {
+        &self.inner.coalescer
+    }
+
+    pub async fn get<T: DeserializeOwned>(&self, path: &str) -> Result<T, GraphError> {
+        let value = self.send(Method::GET, path, None).await?;
+        decode(value)
+    }
+
+    pub async fn post<T: DeserializeOwned>(&self, path: &str, body: &Value) -> Result<T, GraphError> {
+        let value = self.send(Method::POST, path, Some(body)).await?;
+        decode(value)
+    }
+
+    /// Send a request and return the JSON body (`Value::Null` for empty responses).
+    ///
+    /// `path` is either relative to the base URL or an absolute URL on the same
+    /// host (as returned in `@odata.nextLink`).
+    pub async fn send(
+        &self,
+        method: Method,
+        path: &str,
+        body: Option<&Value>,
+    ) -> Result<Value, GraphError> {
+        self.send_with(method, path, body.map(Body::Json), &HeaderMap::new()).await
+    }
+
+    /// Like [`send`](Self::send), with any kind of body and extra headers
+    /// (e.g. `ConsistencyLevel`, `Prefer`). The client sets `Authorization`
+    /// itself.
+    pub async fn send_with(
+        &self,
+        method: Method,
+        path: &str,
+        body: Option<Body<'_>>,
+        headers: &HeaderMap,
+    ) -> Result<Value, GraphError> {
+        let result = self.send_once(method, path, body, headers).await;
+        if let Some(hook) = self.inner.on_outcome.lock().unwrap().as_ref() {
+            hook(match &result {
+                Ok(_) => RequestOutcome::Success,
+                Err(e) if e.code == "auth_expired" => RequestOutcome::AuthExpired,
+                Err(e) if e.code == "network" => RequestOutcome::NetworkError,
+                Err(e) => match e.status {
+                    Some(429) | Some(500..=599) => RequestOutcome::ServerError,
+                    _ => RequestOutcome::OtherError,
+                },
+            });
+        }
+        result
+    }
+
+    async fn send_once(
+        &self,
+        method: Method,
+        path: &str,
+        body: Option<Body<'_>>,
+        headers: &HeaderMap,
+    ) -> Result<Value, GraphError> {
+        let url = self.url(path)?;
+        let config = &self.inner.config;
+        let mut attempt = 0;
+        let mut token_refreshed = false;
+
+        loop {
+            let token = self.inner.tokens.current().await?;
+
+            let mut request = self
+                .inner
+                .http
+                .request(method.clone(), &url)
+                .headers(headers.clone())
+                .header(AUTHORIZATION, format!("Bearer {}", token.value));
+            let default_type = |fallback: &'static str| {
+                headers.get(CONTENT_TYPE).cloned().unwrap_or(HeaderValue::from_static(fallback))
+            };
+            request = match body {
+                Some(Body::Json(body)) => request.json(body),
+                Some(Body::Text(text)) => request
+                    .header(CONTENT_TYPE, default_type("text/plain"))
+                    .body(text.to_string()),
+                Some(Body::Bytes(bytes)) => request
+                    .header(CONTENT_TYPE, default_type("application/octet-stream"))
+                    .body(bytes.to_vec()),
+                None => request,
+            };
+
+            let permit = self.inner.throttle.acquire().await;
+            let result = request.send().await;
+            drop(permit);
+
+            let response = match result {
+                Ok(response) => response,
+                Err(e) if attempt < config.max_retries && (e.is_connect() || e.is_timeout()) => {
+                    tokio::time::sleep(self.backoff(attempt)).await;
+                    attempt += 1;
+                    continue;
+                }
+                Err(e) => return Err(GraphError::new("network", format!("Request failed: {}", e))),
+            };
+
+            let status = response.status();
+
+            if status.is_success() {
+                // The request went through; keep the status so callers know it took effect
+                let failed = |code: &str, message: String| GraphError {
+                    status: Some(status.as_u16()),
+                    ..GraphError::new(code, message)
+                };
+                let bytes = response
+                    .bytes()
+                    .await
+                    .map_err(|e| failed("network", format!("Failed to read response: {}", e)))?;
+                if bytes.is_empty() {
+                    return Ok(Value::Null);
+                }
+                return serde_json::from_slice(&bytes)
+                    .map_err(|e| failed("invalid_response", format!("Failed to parse response: {}", e)));
+            }
+
+            if status == StatusCode::UNAUTHORIZED && !token_refreshed {
+                // Wait for a genuinely new token instead of replaying the rejected one
+                token_refreshed = true;
+                self.inner.tokens.refresh(Some(&token)).await?;
+                continue;
+            }
+
+            let retryable = matches!(
+                status,
+                StatusCode::TOO_MANY_REQUESTS
+                    | StatusCode::SERVICE_UNAVAILABLE
+                    | StatusCode::GATEWAY_TIMEOUT
+            );
+            if retryable && attempt < config.max_retries {
+                let delay = retry_after(response.headers())
+                    .unwrap_or_else(|| self.backoff(attempt))
+                    .min(config.max_delay);
+                if status == StatusCode::TOO_MANY_REQUESTS {
+                    self.inner.throttle.pause_for(delay);
+                }
+                tokio::time::sleep(delay).await;
+                attempt += 1;
+                continue;
+            }
+
+            return Err(error_from_response(response).await);
+        }
+    }
+
+    /// Stream every item of a collection, following `@odata.nextLink`.
+    pub fn paginate<T>(&self, path: &str) -> impl Stream<Item = Result<T, GraphError>> + Send
+    where
+        T: DeserializeOwned + Send + 'static,
+    {
+        let state = (self.clone(), Some(path.to_string()), VecDeque::<T>::new());
+
+        stream::unfold(state, |(client, mut next, mut buffered)| async move {
+            loop {
+                if let Some(item) = buffered.pop_front() {
+                    return Some((Ok(item), (client, next, buffered)));
+                }
+
+                let link = next.take()?;
+                match client.get::<Page<T>>(&link).await {
+                    Ok(page) => {
+                        buffered.extend(page.value);
+                        next = page.next_link;
+                    }
+                    // Yield the error and end the stream
+                    Err(e) => return Some((Err(e), (client, None, buffered))),
+                }
+            }
+        })
+    }
+
+    /// Run one round of a delta query: follow `@odata.nextLink` until Graph
+    /// hands back an `@odata.deltaLink`, collecting every changed item.
+    ///
+    /// `link` is either the initial `.../delta` path or a previously saved
+    /// delta link. A `410 Gone` (or `resyncRequired`) error means the saved link
+    /// has expired and the caller must start over.
+    pub async fn delta<T: DeserializeOwned>(&self, link: &str) -> Result<Delta<T>, GraphError> {
+        self.delta_pages(link, false).await
+    }
+
+    /// Like [`delta`](Self::delta), for callers running many delta queries at
+    /// once: their page requests share `$batch` calls.
+    pub async fn delta_batched<T: DeserializeOwned>(&self, link: &str) -> Result<Delta<T>, GraphError> {
+        self.delta_pages(link, true).await
+    }
+
+    async fn delta_pages<T: DeserializeOwned>(&self, link: &str, batched: bool) -> Result<Delta<T>, GraphError> {
+        let mut items = Vec::new();
+        let mut next = link.to_string();
+
+        loop {
+            let page: Page<T> = if batched {
+                self.get_batched(&next).await?
+            } else {
+                self.get(&next).await?
+            };
+            items.extend(page.value);
+
+            match (page.next_link, page.delta_link) {
+                (Some(next_link), _) => next = next_link,
+                (None, Some(delta_link)) => return Ok(Delta { items, delta_link }),
+                (None, None) => {
+                    return Err(GraphError::new(
+                        "invalid_response",
+                        "Delta response had neither a next link nor a delta link",
+                    ))
+                }
+            }
+        }
+    }
+
+    pub(super) fn url(&self, path: &str) -> Result<String, GraphError> {
+        let base = &self.inner.config.base_url;
+        if !path.starts_with("http") {
+            return Ok(format!("{}{}", base.trim_end_matches('/'), path));
+        }
+
+        // Never send the bearer token to a host other than Graph
+        let same_origin = match (url::Url::parse(path), url::Url::parse(base)) {
+            (Ok(target), Ok(base)) => target.origin() == base.origin(),
+            _ => false,
+        };
+        if !same_origin {
+            return Err(GraphError::new(
+                "invalid_url",
+                format!("Refusing to call non-Graph URL: {}", path),
+            ));
+        }
+        Ok(path.to_string())
+    }
+
+    /// Exponential backoff with a little jitter so callers don't retry in lockstep.
+    pub(super) fn backoff(&self, attempt: u32) -> Duration {
+        let config = &self.inner.config;
+        let exp = config.base_delay.saturating_mul(2u32.saturating_pow(attempt));
+        let jitter_ms = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .subsec_millis()
+            % 250;
+        (exp + Duration::from_millis(jitter_ms as u64)).min(config.max_delay)
+    }
+}
+
+pub(super) fn decode<T: DeserializeOwned>(value: Value) -> Result<T, GraphError> {
+    serde_json::from_value(value).map_err(|e| {
+        GraphError::new("invalid_response", format!("Unexpected response shape: {}", e))
+    })
+}
+
+/// Parse `Retry-After` as delta-seconds (Graph never sends the HTTP-date form).
+fn retry_after(headers: &HeaderMap) -> Option<Duration> {
+    headers
+        .get(RETRY_AFTER)?
+        .to_str()
+        .ok()?
+        .trim()
+        .parse::<u64>()
+        .ok()
+        .map(Duration::from_secs)
+}
+
+async fn error_from_response(response: reqwest::Response) -> GraphError {
+    let status = response.status();
+    let body: Option<Value> = response.json().await.ok();
+    let error = body.as_ref().and_then(|b| b.get("error"));
+
+    GraphError {
+        status: Some(status.as_u16()),
+        code: error
+            .and_then(|e| e["code"].as_str())
+            .unwrap_or(status.as_str())
+            .to_string(),
+        message: error
+            .and_then(|e| e["message"].as_str())
+            .map(|m| m.to_string())
+            .unwrap_or_else(|| {
+                status
+                    .canonical_reason()
+                    .unwrap_or("Unknown Graph API error")
+                    .to_string()
+            }),
+    }
+}
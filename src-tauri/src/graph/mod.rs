@@ -0,0 +1,61 @@
+//! Native Microsoft Graph client.
+//!
+//! All Graph traffic from the Rust side goes through a single [`GraphClient`]
+//! held in managed state, so retries, throttling and the access token are
+//! shared by every caller.
+
+mod batch;
+mod client;
+mod throttle;
+mod token;
+pub mod types;
+
+#[cfg(test)]
+mod tests;
+
+pub use batch::{BatchRequest, BatchResponse, MAX_BATCH_SIZE};
+pub use client::{Body, GraphClient, GraphConfig, RequestOutcome};
+pub use token::{AccessToken, TokenStore};
+
+/// Default Graph endpoint. Overridable via [`GraphConfig::base_url`] for mock servers.
+pub const GRAPH_BASE_URL: &str = "https://graph.microsoft.com/v1.0";
+
+/// Error returned by the Graph client. Serialized as-is to the frontend.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct GraphError {
+    /// HTTP status, if a response was received.
+    pub status: Option<u16>,
+    /// Graph error code (e.g. `TooManyRequests`) or a local code such as `network`.
+    pub code: String,
+    pub message: String,
+}
+
+impl GraphError {
+    pub fn new(code: &str, message: impl Into<String>) -> Self {
+        Self {
+            status: None,
+            code: code.to_string(),
+            message: message.into(),
+        }
+    }
+
+    /// A saved delta link was rejected and the delta query must restart from scratch.
+    pub fn requires_resync(&self) -> bool {
+        self.status == Some(410)
+            || matches!(
+                self.code.as_str(),
+                "resyncRequired" | "syncStateNotFound" | "syncStateInvalid"
+            )
+    }
+}
+
+impl std::fmt::Display for GraphError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.status {
+            Some(status) => write!(f, "Graph error {} ({}): {}", status, self.code, self.message),
+            None => write!(f, "Graph error ({}): {}", self.code, self.message),
+        }
+    }
+}
+
+impl std::error::Error for GraphError {}
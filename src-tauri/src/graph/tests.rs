@@ -0,0 +1,386 @@
+//! Client tests against a minimal HTTP/1.1 server on localhost, pointed at
+//! through [`GraphConfig::base_url`].
+
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use futures_util::TryStreamExt;
+use reqwest::header::{HeaderMap, HeaderValue};
+use reqwest::Method;
+use serde_json::{json, Value};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+use super::{AccessToken, Body, GraphClient, GraphConfig};
+
+#[derive(Debug, Clone)]
+struct Request {
+    method: String,
+    /// Path and query, e.g. `/v1.0/me?x=1`.
+    target: String,
+    /// Names lowercased.
+    headers: Vec<(String, String)>,
+    body: String,
+    at: Instant,
+}
+
+impl Request {
+    fn header(&self, name: &str) -> Option<&str> {
+        self.headers.iter().find(|(n, _)| n == name).map(|(_, v)| v.as_str())
+    }
+}
+
+struct Reply {
+    status: u16,
+    headers: Vec<(&'static str, String)>,
+    body: Value,
+}
+
+impl Reply {
+    fn ok(body: Value) -> Self {
+        Self { status: 200, headers: Vec::new(), body }
+    }
+
+    fn status(status: u16) -> Self {
+        Self {
+            status,
+            headers: Vec::new(),
+            body: json!({ "error": { "code": status.to_string(), "message": "mock" } }),
+        }
+    }
+
+    fn header(mut self, name: &'static str, value: impl Into<String>) -> Self {
+        self.headers.push((name, value.into()));
+        self
+    }
+}
+
+/// Answers each request with `handler(request, n)`, where `n` counts earlier
+/// requests to the same target.
+type Handler = Arc<dyn Fn(&Request, usize) -> Reply + Send + Sync>;
+
+struct MockGraph {
+    base_url: String,
+    requests: Arc<Mutex<Vec<Request>>>,
+}
+
+impl MockGraph {
+    async fn start(handler: impl Fn(&Request, usize) -> Reply + Send + Sync + 'static) -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let base_url = format!("http://{}/v1.0", listener.local_addr().unwrap());
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let handler: Handler = Arc::new(handler);
+
+        let log = requests.clone();
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                tokio::spawn(serve(stream, handler.clone(), log.clone()));
+            }
+        });
+        Self { base_url, requests }
+    }
+
+    fn client(&self) -> GraphClient {
+        let client = GraphClient::new(GraphConfig {
+            base_url: self.base_url.clone(),
+            base_delay: Duration::from_millis(10),
+            max_delay: Duration::from_secs(5),
+            token_wait: Duration::from_secs(2),
+            ..GraphConfig::default()
+        });
+        client.tokens().set(Some(token("first")));
+        client
+    }
+
+    fn requests(&self) -> Vec<Request> {
+        self.requests.lock().unwrap().clone()
+    }
+}
+
+fn token(value: &str) -> AccessToken {
+    AccessToken {
+        value: value.to_string(),
+        expires_at: None,
+    }
+}
+
+/// One request per connection, then close it.
+async fn serve(mut stream: TcpStream, handler: Handler, log: Arc<Mutex<Vec<Request>>>) {
+    let Some(request) = read_request(&mut stream).await else {
+        return;
+    };
+    let reply = {
+        let mut log = log.lock().unwrap();
+        let seen = log.iter().filter(|r| r.target == request.target).count();
+        log.push(request.clone());
+        handler(&request, seen)
+    };
+
+    let body = reply.body.to_string();
+    let mut head = format!(
+        "HTTP/1.1 {} Mock\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n",
+        reply.status,
+        body.len()
+    );
+    for (name, value) in &reply.headers {
+        head.push_str(&format!("{}: {}\r\n", name, value));
+    }
+    head.push_str("\r\n");
+    let _ = stream.write_all(head.as_bytes()).await;
+    let _ = stream.write_all(body.as_bytes()).await;
+    let _ = stream.shutdown().await;
+}
+
+async fn read_request(stream: &mut TcpStream) -> Option<Request> {
+    let mut buf = Vec::new();
+    let mut chunk = [0u8; 4096];
+    let head_len = loop {
+        let n = stream.read(&mut chunk).await.ok()?;
+        if n == 0 {
+            return None;
+        }
+        buf.extend_from_slice(&chunk[..n]);
+        if let Some(i) = buf.windows(4).position(|w| w == b"\r\n\r\n") {
+            break i + 4;
+        }
+    };
+
+    let head = String::from_utf8_lossy(&buf[..head_len]).to_string();
+    let mut lines = head.split("\r\n");
+    let mut request_line = lines.next()?.split(' ');
+    let method = request_line.next()?.to_string();
+    let target = request_line.next()?.to_string();
+    let headers: Vec<(String, String)> = lines
+        .filter_map(|line| line.split_once(':'))
+        .map(|(name, value)| (name.trim().to_ascii_lowercase(), value.trim().to_string()))
+        .collect();
+
+    let length: usize = headers
+        .iter()
+        .find(|(name, _)| name == "content-length")
+        .and_then(|(_, value)| value.parse().ok())
+        .unwrap_or(0);
+    while buf.len() < head_len + length {
+        let n = stream.read(&mut chunk).await.ok()?;
+        if n == 0 {
+            return None;
+        }
+        buf.extend_from_slice(&chunk[..n]);
+    }
+
+    Some(Request {
+        method,
+        target,
+        headers,
+        body: String::from_utf8_lossy(&buf[head_len..head_len + length]).to_string(),
+        at: Instant::now(),
+    })
+}
+
+#[tokio::test]
+async fn waits_for_retry_after() {
+    let server = MockGraph::start(|_, seen| match seen {
+        0 => Reply::status(429).header("Retry-After", "1"),
+        _ => Reply::ok(json!({ "id": "me" })),
+    })
+    .await;
+    let client = server.client();
+
+    let started = Instant::now();
+    let me: Value = client.get("/me").await.unwrap();
+
+    assert_eq!(me["id"], "me");
+    let requests = server.requests();
+    assert_eq!(requests.len(), 2);
+    // The configured backoff is 10ms; only Retry-After explains the wait
+    assert!(requests[1].at - requests[0].at >= Duration::from_millis(950));
+    assert!(started.elapsed() >= Duration::from_secs(1));
+}
+
+#[tokio::test]
+async fn gives_up_after_max_retries() {
+    let server = MockGraph::start(|_, _| Reply::status(503)).await;
+    let client = GraphClient::new(GraphConfig {
+        base_url: server.base_url.clone(),
+        max_retries: 2,
+        base_delay: Duration::from_millis(1),
+        ..GraphConfig::default()
+    });
+    client.tokens().set(Some(token("first")));
+
+    let error = client.get::<Value>("/me").await.unwrap_err();
+
+    assert_eq!(error.status, Some(503));
+    assert_eq!(server.requests().len(), 3);
+}
+
+#[tokio::test]
+async fn refreshes_token_after_401() {
+    let server = MockGraph::start(|request, _| match request.header("authorization") {
+        Some("Bearer second") => Reply::ok(json!({ "id": "me" })),
+        _ => Reply::status(401),
+    })
+    .await;
+    let client = server.client();
+    let refresher = client.clone();
+    client
+        .tokens()
+        .on_refresh_needed(move || refresher.tokens().set(Some(token("second"))));
+
+    let me: Value = client.get("/me").await.unwrap();
+
+    assert_eq!(me["id"], "me");
+    let auth: Vec<_> = server
+        .requests()
+        .iter()
+        .map(|r| r.header("authorization").unwrap_or_default().to_string())
+        .collect();
+    assert_eq!(auth, ["Bearer first", "Bearer second"]);
+}
+
+#[tokio::test]
+async fn fails_when_refreshed_token_is_rejected_too() {
+    let server = MockGraph::start(|_, _| Reply::status(401)).await;
+    let client = server.client();
+    let refresher = client.clone();
+    client
+        .tokens()
+        .on_refresh_needed(move || refresher.tokens().set(Some(token("second"))));
+
+    let error = client.get::<Value>("/me").await.unwrap_err();
+
+    // One refresh, not a loop
+    assert_eq!(error.status, Some(401));
+    assert_eq!(server.requests().len(), 2);
+}
+
+#[tokio::test]
+async fn throttling_pauses_every_caller() {
+    let server = MockGraph::start(|request, seen| {
+        if request.target.ends_with("/throttled") && seen == 0 {
+            Reply::status(429).header("Retry-After", "1")
+        } else {
+            Reply::ok(json!({}))
+        }
+    })
+    .await;
+    let client = server.client();
+
+    let throttled = tokio::spawn({
+        let client = client.clone();
+        async move { client.get::<Value>("/throttled").await }
+    });
+    while server.requests().is_empty() {
+        tokio::time::sleep(Duration::from_millis(5)).await;
+    }
+    // Let the 429 reach the client
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    client.get::<Value>("/other").await.unwrap();
+    throttled.await.unwrap().unwrap();
+
+    let requests = server.requests();
+    let first = requests[0].at;
+    let other = requests.iter().find(|r| r.target.ends_with("/other")).unwrap();
+    assert!(other.at - first >= Duration::from_millis(950));
+}
+
+#[tokio::test]
+async fn paginates_through_next_links() {
+    let server = MockGraph::start(|request, _| {
+        let base = format!("http://{}/v1.0", request.header("host").unwrap());
+        match request.target.as_str() {
+            "/v1.0/items" => Reply::ok(json!({
+                "value": [1, 2],
+                "@odata.nextLink": format!("{}/items?$skiptoken=a", base),
+            })),
+            "/v1.0/items?$skiptoken=a" => Reply::ok(json!({
+                "value": [3],
+                "@odata.nextLink": format!("{}/items?$skiptoken=b", base),
+            })),
+            _ => Reply::ok(json!({ "value": [4] })),
+        }
+    })
+    .await;
+    let client = server.client();
+
+    let items: Vec<i64> = client.paginate::<i64>("/items").try_collect().await.unwrap();
+
+    assert_eq!(items, [1, 2, 3, 4]);
+    let targets: Vec<_> = server.requests().into_iter().map(|r| r.target).collect();
+    assert_eq!(
+        targets,
+        ["/v1.0/items", "/v1.0/items?$skiptoken=a", "/v1.0/items?$skiptoken=b"]
+    );
+}
+
+#[tokio::test]
+async fn refuses_next_links_to_other_hosts() {
+    let server = MockGraph::start(|_, _| {
+        Reply::ok(json!({ "value": [1], "@odata.nextLink": "https://example.com/steal" }))
+    })
+    .await;
+    let client = server.client();
+
+    let result: Result<Vec<i64>, _> = client.paginate::<i64>("/items").try_collect().await;
+
+    assert_eq!(result.unwrap_err().code, "invalid_url");
+    assert_eq!(server.requests().len(), 1);
+}
+
+#[tokio::test]
+async fn forwards_headers_and_raw_bodies() {
+    let server = MockGraph::start(|_, _| Reply::ok(json!({}))).await;
+    let client = server.client();
+    let mut headers = HeaderMap::new();
+    headers.insert("ConsistencyLevel", HeaderValue::from_static("eventual"));
+
+    client
+        .send_with(Method::PUT, "/me/photo/$value", Some(Body::Text("hello")), &headers)
+        .await
+        .unwrap();
+
+    let request = &server.requests()[0];
+    assert_eq!(request.method, "PUT");
+    assert_eq!(request.header("consistencylevel"), Some("eventual"));
+    assert_eq!(request.header("content-type"), Some("text/plain"));
+    assert_eq!(request.header("authorization"), Some("Bearer first"));
+    assert_eq!(request.body, "hello");
+}
+
+#[tokio::test]
+async fn batches_only_when_asked() {
+    let server = MockGraph::start(|request, _| {
+        if !request.target.ends_with("/$batch") {
+            return Reply::ok(json!({ "path": request.target }));
+        }
+        let body: Value = serde_json::from_str(&request.body).unwrap();
+        let responses: Vec<Value> = body["requests"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|r| json!({ "id": r["id"], "status": 200, "body": { "path": r["url"] } }))
+            .collect();
+        Reply::ok(json!({ "responses": responses }))
+    })
+    .await;
+    let client = server.client();
+
+    let (a, b, c) = tokio::join!(
+        client.get_batched::<Value>("/teams/1/channels/a/messages"),
+        client.get_batched::<Value>("/teams/1/channels/b/messages"),
+        client.get_batched::<Value>("/teams/1/channels/c/messages"),
+    );
+    assert_eq!(a.unwrap()["path"], "/teams/1/channels/a/messages");
+    assert_eq!(b.unwrap()["path"], "/teams/1/channels/b/messages");
+    assert_eq!(c.unwrap()["path"], "/teams/1/channels/c/messages");
+    let requests = server.requests();
+    assert_eq!(requests.len(), 1);
+    assert_eq!(requests[0].method, "POST");
+
+    let (d, e) = tokio::join!(client.get::<Value>("/me"), client.get::<Value>("/me/chats"));
+    d.unwrap();
+    e.unwrap();
+    let targets: Vec<_> = server.requests().into_iter().skip(1).map(|r| r.target).collect();
+    assert!(targets.contains(&"/v1.0/me".to_string()) && targets.contains(&"/v1.0/me/chats".to_string()));
+}
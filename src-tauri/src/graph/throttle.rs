@@ -0,0 +1,92 @@
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use tokio::sync::{Semaphore, SemaphorePermit};
+
+/// Global request budget shared by every Graph caller.
+///
+/// Combines a concurrency cap, a token-bucket rate limit, and a global pause
+/// that is set whenever Graph answers with `429` so that *all* callers back
+/// off, not just the one that was throttled.
+pub struct Throttle {
+    permits: Semaphore,
+    bucket: Mutex<Bucket>,
+    paused_until: Mutex<Option<Instant>>,
+}
+
+struct Bucket {
+    tokens: f64,
+    capacity: f64,
+    per_second: f64,
+    last_refill: Instant,
+}
+
+impl Throttle {
+    pub fn new(max_concurrency: usize, requests_per_second: f64) -> Self {
+        let per_second = requests_per_second.max(0.1);
+        Self {
+            permits: Semaphore::new(max_concurrency.max(1)),
+            bucket: Mutex::new(Bucket {
+                tokens: per_second,
+                capacity: per_second,
+                per_second,
+                last_refill: Instant::now(),
+            }),
+            paused_until: Mutex::new(None),
+        }
+    }
+
+    /// Wait until a request may be sent. Hold the permit for the request's duration.
+    pub async fn acquire(&self) -> SemaphorePermit<'_> {
+        loop {
+            let wait = self.pause_remaining().or_else(|| self.take_token());
+            match wait {
+                Some(delay) => tokio::time::sleep(delay).await,
+                None => break,
+            }
+        }
+
+        self.permits
+            .acquire()
+            .await
+            .expect("throttle semaphore is never closed")
+    }
+
+    /// Stop all callers from sending for `delay`.
+    pub fn pause_for(&self, delay: Duration) {
+        let until = Instant::now() + delay;
+        let mut paused = self.paused_until.lock().unwrap();
+        if paused.is_none_or(|current| current < until) {
+            *paused = Some(until);
+        }
+    }
+
+    fn pause_remaining(&self) -> Option<Duration> {
+        let mut paused = self.paused_until.lock().unwrap();
+        match *paused {
+            Some(until) if until > Instant::now() => Some(until - Instant::now()),
+            Some(_) => {
+                *paused = None;
+                None
+            }
+            None => None,
+        }
+    }
+
+    /// Take a token from the bucket, or return how long until one is available.
+    fn take_token(&self) -> Option<Duration> {
+        let mut bucket = self.bucket.lock().unwrap();
+        let now = Instant::now();
+        let elapsed = now.duration_since(bucket.last_refill).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * bucket.per_second).min(bucket.capacity);
+        bucket.last_refill = now;
+
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            None
+        } else {
+            let missing = 1.0 - bucket.tokens;
+            Some(Duration::from_secs_f64(missing / bucket.per_second))
+        }
+    }
+}
@@ -0,0 +1,109 @@
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use tokio::sync::watch;
+
+use super::GraphError;
+
+/// Tokens expiring within this margin are treated as already expired.
+const EXPIRY_MARGIN: Duration = Duration::from_secs(60);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AccessToken {
+    pub value: String,
+    /// Expiry in milliseconds since the Unix epoch, if known.
+    pub expires_at: Option<u64>,
+}
+
+impl AccessToken {
+    fn is_fresh(&self) -> bool {
+        let Some(expires_at) = self.expires_at else {
+            return true;
+        };
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis() as u64;
+        expires_at > now + EXPIRY_MARGIN.as_millis() as u64
+    }
+}
+
+type RefreshHook = Box<dyn Fn() + Send + Sync>;
+
+/// Holds the current Graph access token.
+///
+/// The token is obtained and refreshed by the webview's auth flow and pushed
+/// here. When the client needs a fresh token it calls the refresh hook and
+/// waits for a *different* token to arrive, rather than retrying with the one
+/// that just failed.
+pub struct TokenStore {
+    tx: watch::Sender<Option<AccessToken>>,
+    on_refresh_needed: Mutex<Option<RefreshHook>>,
+    wait: Duration,
+}
+
+impl TokenStore {
+    pub fn new(wait: Duration) -> Self {
+        Self {
+            tx: watch::Sender::new(None),
+            on_refresh_needed: Mutex::new(None),
+            wait,
+        }
+    }
+
+    pub fn set(&self, token: Option<AccessToken>) {
+        self.tx.send_replace(token);
+    }
+
+    /// Register the callback used to ask the auth flow for a new token.
+    pub fn on_refresh_needed(&self, hook: impl Fn() + Send + Sync + 'static) {
+        *self.on_refresh_needed.lock().unwrap() = Some(Box::new(hook));
+    }
+
+    /// Return a usable token, requesting a refresh if the current one is missing or expiring.
+    pub async fn current(&self) -> Result<AccessToken, GraphError> {
+        let current = self.tx.borrow().clone();
+        match current {
+            Some(token) if token.is_fresh() => Ok(token),
+            stale => self.refresh(stale.as_ref()).await,
+        }
+    }
+
+    /// Replace `stale` with a new token, unless someone already did.
+    pub async fn refresh(&self, stale: Option<&AccessToken>) -> Result<AccessToken, GraphError> {
+        let mut rx = self.tx.subscribe();
+
+        // Another caller may have refreshed while our request was in flight
+        if let Some(token) = rx.borrow_and_update().as_ref() {
+            if Some(token) != stale && token.is_fresh() {
+                return Ok(token.clone());
+            }
+        }
+
+        if let Some(hook) = self.on_refresh_needed.lock().unwrap().as_ref() {
+            hook();
+        }
+
+        let wait = async {
+            loop {
+                if rx.changed().await.is_err() {
+                    return None;
+                }
+                if let Some(token) = rx.borrow_and_update().as_ref() {
+                    if Some(token) != stale {
+                        return Some(token.clone());
+                    }
+                }
+            }
+        };
+
+        match tokio::time::timeout(self.wait, wait).await {
+            Ok(Some(token)) => Ok(token),
+            _ => Err(GraphError {
+                status: Some(401),
+                code: "auth_expired".to_string(),
+                message: "No valid access token available. Please sign in again.".to_string(),
+            }),
+        }
+    }
+}
@@ -0,0 +1,211 @@
+//! Typed subsets of the Graph resources Teamy works with.
+//!
+//! Fields the Rust side doesn't use are kept in `extra` so a value can be
+//! re-emitted to the frontend without losing anything.
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+/// One page of a Graph collection.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Page<T> {
+    pub value: Vec<T>,
+    #[serde(rename = "@odata.nextLink")]
+    pub next_link: Option<String>,
+    #[serde(rename = "@odata.deltaLink")]
+    pub delta_link: Option<String>,
+}
+
+/// The changes from one round of a delta query.
+#[derive(Debug, Clone)]
+pub struct Delta<T> {
+    pub items: Vec<T>,
+    /// Link to pass to the next round to get only subsequent changes.
+    pub delta_link: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Identity {
+    pub id: Option<String>,
+    pub display_name: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IdentitySet {
+    pub user: Option<Identity>,
+    pub application: Option<Identity>,
+}
+
+impl IdentitySet {
+    pub fn user_id(&self) -> Option<&str> {
+        self.user.as_ref()?.id.as_deref()
+    }
+
+    pub fn display_name(&self) -> Option<&str> {
+        self.user
+            .as_ref()
+            .or(self.application.as_ref())?
+            .display_name
+            .as_deref()
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ItemBody {
+    pub content_type: String,
+    pub content: String,
+}
+
+impl ItemBody {
+    /// The body as plain text, with HTML tags removed and common entities decoded.
+    pub fn plain_text(&self) -> String {
+        if self.content_type.eq_ignore_ascii_case("html") {
+            strip_html(&self.content)
+        } else {
+            self.content.trim().to_string()
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChatMessagePreview {
+    pub id: String,
+    pub created_date_time: String,
+    #[serde(default)]
+    pub is_deleted: bool,
+    pub body: Option<ItemBody>,
+    pub from: Option<IdentitySet>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChatViewpoint {
+    #[serde(default)]
+    pub is_hidden: bool,
+    pub last_message_read_date_time: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChatMember {
+    pub id: String,
+    pub display_name: Option<String>,
+    pub user_id: Option<String>,
+    pub email: Option<String>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Chat {
+    pub id: String,
+    pub topic: Option<String>,
+    /// `oneOnOne`, `group` or `meeting`.
+    pub chat_type: String,
+    pub last_updated_date_time: Option<String>,
+    pub members: Option<Vec<ChatMember>>,
+    pub last_message_preview: Option<ChatMessagePreview>,
+    pub viewpoint: Option<ChatViewpoint>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChannelIdentity {
+    pub team_id: String,
+    pub channel_id: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChatMessageMention {
+    pub id: i64,
+    pub mention_text: Option<String>,
+    pub mentioned: Option<IdentitySet>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChatMessage {
+    pub id: String,
+    pub reply_to_id: Option<String>,
+    /// `message`, `chatEvent`, `typing`, ...
+    pub message_type: String,
+    pub created_date_time: String,
+    pub last_modified_date_time: Option<String>,
+    pub deleted_date_time: Option<String>,
+    pub chat_id: Option<String>,
+    pub channel_identity: Option<ChannelIdentity>,
+    pub from: Option<IdentitySet>,
+    pub body: Option<ItemBody>,
+    #[serde(default)]
+    pub mentions: Vec<ChatMessageMention>,
+    #[serde(default)]
+    pub attachments: Vec<Value>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+impl ChatMessage {
+    /// Whether a delta round reported this message as deleted.
+    pub fn is_removed(&self) -> bool {
+        self.deleted_date_time.is_some() || self.extra.contains_key("@removed")
+    }
+
+    /// Whether `user_id` is @mentioned in this message.
+    pub fn mentions_user(&self, user_id: &str) -> bool {
+        self.mentions.iter().any(|m| {
+            m.mentioned
+                .as_ref()
+                .and_then(|set| set.user_id())
+                .is_some_and(|id| id == user_id)
+        })
+    }
+}
+
+/// Strip HTML tags and decode the handful of entities Teams produces.
+pub fn strip_html(html: &str) -> String {
+    let mut text = String::with_capacity(html.len());
+    let mut in_tag = false;
+    let mut tag = String::new();
+
+    for c in html.chars() {
+        match c {
+            '<' => {
+                in_tag = true;
+                tag.clear();
+            }
+            '>' if in_tag => {
+                in_tag = false;
+                // Keep block boundaries as whitespace so words don't run together
+                let name = tag
+                    .trim_start_matches('/')
+                    .split(|c: char| c.is_whitespace() || c == '/')
+                    .next()
+                    .unwrap_or("")
+                    .to_ascii_lowercase();
+                if matches!(name.as_str(), "br" | "p" | "div" | "li" | "tr") {
+                    text.push(' ');
+                }
+            }
+            _ if in_tag => tag.push(c),
+            _ => text.push(c),
+        }
+    }
+
+    let decoded = text
+        .replace("&nbsp;", " ")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&amp;", "&");
+
+    decoded.split_whitespace().collect::<Vec<_>>().join(" ")
+}
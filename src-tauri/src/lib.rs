@@ -1,4 +1,13 @@
+mod cache;
 mod commands;
+mod connectivity;
+pub mod graph;
+mod notifications;
+mod outbox;
+mod presence;
+mod realtime;
+mod settings;
+mod sync;
 mod tray;
 
 use tauri::{
@@ -78,6 +87,16 @@ pub fn run() {
         .invoke_handler(tauri::generate_handler![
             // Notifications
             commands::notifications::send_notification,
+            commands::notifications::notifications_get_policy,
+            commands::notifications::notifications_set_policy,
+            commands::notifications::notifications_get_rules,
+            commands::notifications::notifications_set_rules,
+            commands::notifications::notifications_dry_run,
+            commands::notifications::notifications_get_sounds,
+            commands::notifications::notifications_set_chat_sound,
+            commands::notifications::notifications_preview_sound,
+            commands::notifications::notifications_history,
+            commands::notifications::notifications_mark_seen,
             commands::notifications::request_notification_permission,
             commands::notifications::is_notification_permission_granted,
             // Keychain
@@ -100,12 +119,137 @@ pub fn run() {
             commands::filesystem::fs_read_file,
             commands::filesystem::fs_write_file,
             commands::filesystem::fs_edit_file,
+            commands::filesystem::fs_multi_edit_file,
             commands::filesystem::fs_list_directory,
             commands::filesystem::fs_search_files,
+            commands::filesystem::fs_get_edit_policy,
+            commands::filesystem::fs_set_edit_policy,
+            commands::filesystem::fs_list_pending_edits,
+            commands::filesystem::fs_approve_edit,
+            commands::filesystem::fs_reject_edit,
+            commands::watcher::fs_watch,
+            commands::watcher::fs_unwatch,
+            // Git
+            commands::git::git_status,
+            commands::git::git_diff,
+            commands::git::git_log,
+            commands::git::git_blame,
+            commands::git::git_commit_paths,
+            // Graph
+            commands::graph::graph_set_token,
+            commands::graph::graph_clear_token,
+            commands::graph::graph_fetch,
+            commands::graph::graph_batch,
+            // Background sync
+            commands::sync::sync_start,
+            commands::sync::sync_stop,
+            commands::sync::sync_status,
+            commands::sync::sync_set_watched_channels,
+            commands::sync::sync_set_active_conversation,
+            commands::sync::sync_mark_read,
+            commands::sync::sync_get_unread,
+            commands::sync::sync_poll_now,
+            // Local cache
+            commands::cache::cache_is_available,
+            commands::cache::cache_get_chats,
+            commands::cache::cache_put_chats,
+            commands::cache::cache_get_channels,
+            commands::cache::cache_put_channels,
+            commands::cache::cache_get_members,
+            commands::cache::cache_get_messages,
+            commands::cache::cache_put_messages,
+            commands::cache::cache_get_read_markers,
+            commands::cache::cache_clear,
+            commands::cache::search_messages,
+            // Outbox
+            commands::outbox::outbox_enqueue,
+            commands::outbox::outbox_list,
+            commands::outbox::outbox_resend,
+            commands::outbox::outbox_discard,
+            commands::outbox::outbox_flush,
+            // Connectivity
+            commands::connectivity::connectivity_status,
+            commands::connectivity::connectivity_check,
+            // Presence
+            commands::presence::presence_start,
+            commands::presence::presence_stop,
+            commands::presence::presence_track,
+            commands::presence::presence_get,
+            commands::presence::presence_get_mine,
+            commands::presence::presence_set_preferred,
+            commands::presence::presence_clear_preferred,
+            commands::presence::presence_set_session,
+            commands::presence::presence_set_status_message,
+            // Change notifications
+            commands::realtime::realtime_get_config,
+            commands::realtime::realtime_set_config,
+            commands::realtime::realtime_start,
+            commands::realtime::realtime_stop,
+            commands::realtime::realtime_status,
             // Tray
             tray::set_tray_unread_count,
+            tray::set_tray_unread_chats,
+            tray::get_dock_settings,
+            tray::set_dock_settings,
         ])
         .setup(|app| {
+            // Managed state
+            app.manage(commands::filesystem::EditApprovals::load(app.handle()));
+            app.manage(commands::watcher::FsWatchers::default());
+
+            // Graph client — ask the webview for a new token when ours is rejected or expiring
+            let graph_client = graph::GraphClient::new(graph::GraphConfig::default());
+            let refresh_handle = app.handle().clone();
+            graph_client.tokens().on_refresh_needed(move || {
+                let _ = refresh_handle.emit("graph:token-refresh-needed", ());
+            });
+            app.manage(graph_client.clone());
+
+            // Encrypted local cache (disabled rather than fatal if it can't be opened)
+            let cache = cache::Cache::open(app.handle());
+            app.manage(cache.clone());
+
+            // Outgoing messages — queued sends survive restarts and network drops
+            let outbox = outbox::Outbox::new(app.handle().clone(), graph_client.clone(), cache.clone());
+            outbox.start();
+            app.manage(outbox.clone());
+
+            // Notifications — grouped and rate-limited per conversation
+            let notification_manager = notifications::NotificationManager::new(app.handle().clone(), cache.clone());
+            notification_manager.start();
+            app.manage(notification_manager.clone());
+
+            // Background sync — idle until the frontend calls sync_start with the user ID
+            let sync_engine = sync::SyncEngine::new(
+                app.handle().clone(),
+                graph_client.clone(),
+                cache,
+                notification_manager,
+                sync::SyncConfig::default(),
+            );
+            app.manage(sync_engine.clone());
+
+            // Presence — idle until the frontend calls presence_start with the user ID
+            let presence = presence::PresenceService::new(app.handle().clone(), graph_client.clone());
+            app.manage(presence.clone());
+
+            // Connectivity — pauses sync and presence while offline, flushes the outbox when back
+            let connectivity = connectivity::Connectivity::new(
+                app.handle().clone(),
+                &graph_client,
+                sync_engine.clone(),
+                presence,
+                outbox,
+            );
+            app.manage(connectivity.clone());
+
+            // Change notifications — wake the sync as soon as Graph reports a change
+            app.manage(realtime::SubscriptionManager::new(
+                app.handle().clone(),
+                graph_client,
+                sync_engine,
+            ));
+
             // Menu bar
             let menu = create_menu(app)?;
             app.set_menu(menu)?;
@@ -124,6 +268,9 @@ pub fn run() {
             // System tray
             tray::create_tray(app)?;
 
+            // Probes report into the tray, so start them once it exists
+            connectivity.start();
+
             // Global shortcut: Cmd+Shift+T to toggle window
             #[cfg(desktop)]
             {
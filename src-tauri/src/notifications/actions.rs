@@ -0,0 +1,118 @@
+//! Quick actions, run straight against the outbox and sync engine.
+
+use std::time::Duration;
+
+use chrono::{DateTime, SecondsFormat, Utc};
+use reqwest::Method;
+use serde::Serialize;
+use serde_json::json;
+use tauri::{AppHandle, Emitter, Manager};
+
+use super::{Notice, NotificationTarget};
+use crate::graph::GraphClient;
+use crate::outbox::Outbox;
+use crate::sync::SyncEngine;
+
+pub const MUTE_DURATION: Duration = Duration::from_secs(60 * 60);
+
+/// Emitted so the webview's unread store follows reads made from a notification.
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct MarkedRead {
+    key: String,
+    timestamp: String,
+}
+
+/// Bring the main window forward and navigate it to `target`.
+pub fn open(app: &AppHandle, target: &NotificationTarget) {
+    if let Some(window) = app.get_webview_window("main") {
+        let _ = window.unminimize();
+        let _ = window.show();
+        let _ = window.set_focus();
+        let _ = window.emit("navigate", target.route());
+    }
+}
+
+/// Queue `text` as a reply through the outbox (so it survives going offline)
+/// and mark the conversation read. Channel replies go to the message's thread.
+pub fn reply(app: &AppHandle, notice: &Notice, text: &str) -> Result<(), String> {
+    let text = text.trim();
+    if text.is_empty() {
+        return Ok(());
+    }
+    let path = match (&notice.target, &notice.message) {
+        (NotificationTarget::Chat(chat_id), _) => format!("/chats/{}/messages", chat_id),
+        (NotificationTarget::Channel { team_id, channel_id }, Some(message)) => format!(
+            "/teams/{}/channels/{}/messages/{}/replies",
+            team_id, channel_id, message.id
+        ),
+        (NotificationTarget::Channel { team_id, channel_id }, None) => {
+            format!("/teams/{}/channels/{}/messages", team_id, channel_id)
+        }
+    };
+    let payload = json!({
+        "body": { "contentType": "html", "content": format!("<p>{}</p>", escape_html(text)) },
+    });
+    app.state::<Outbox>().enqueue(notice.target.key(), path, payload)?;
+    mark_read(app, notice);
+    Ok(())
+}
+
+/// Mark the conversation read up to now (or the message, if the clocks
+/// disagree) locally and, for chats, on the server.
+pub fn mark_read(app: &AppHandle, notice: &Notice) {
+    let now = Utc::now();
+    let created = notice
+        .message
+        .as_ref()
+        .and_then(|message| DateTime::parse_from_rfc3339(&message.created_at).ok())
+        .map(|created| created.with_timezone(&Utc));
+    let timestamp = match created {
+        Some(created) if created > now => created,
+        _ => now,
+    };
+    mark_read_at(app, &notice.target, timestamp.to_rfc3339_opts(SecondsFormat::Millis, true));
+}
+
+/// Mark `target` read up to `timestamp` (RFC 3339) locally and, for chats,
+/// on the server.
+pub fn mark_read_at(app: &AppHandle, target: &NotificationTarget, timestamp: String) {
+    let key = target.key();
+    let sync = app.state::<SyncEngine>();
+    sync.mark_read(&key, &timestamp);
+    let _ = app.emit(
+        "notifications:marked-read",
+        MarkedRead {
+            key,
+            timestamp,
+        },
+    );
+
+    // Channels have no server-side read state
+    let (NotificationTarget::Chat(chat_id), Some(user_id)) = (target, sync.user_id()) else {
+        return;
+    };
+    let graph = app.state::<GraphClient>().inner().clone();
+    let path = format!("/chats/{}/markChatReadForUser", chat_id);
+    tauri::async_runtime::spawn(async move {
+        let body = json!({ "user": { "id": user_id } });
+        if let Err(e) = graph.send(Method::POST, &path, Some(&body)).await {
+            eprintln!("[notifications] markChatReadForUser failed: {}", e);
+        }
+    });
+}
+
+fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
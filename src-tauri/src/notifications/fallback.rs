@@ -0,0 +1,16 @@
+use tauri::AppHandle;
+use tauri_plugin_notification::NotificationExt;
+
+use super::Notice;
+
+/// The plugin can't report clicks or actions, or replace notifications, so
+/// the notice is shown as plain title and body.
+pub fn show(app: &AppHandle, notice: Notice, _replaces: Option<u32>) -> Result<Option<u32>, String> {
+    app.notification()
+        .builder()
+        .title(&notice.title)
+        .body(&notice.body)
+        .show()
+        .map_err(|e| e.to_string())?;
+    Ok(None)
+}
@@ -0,0 +1,159 @@
+//! freedesktop notifications over D-Bus. The quick actions are "Mark as read"
+//! and "Mute"; servers that advertise `inline-reply` (e.g. Plasma) also get a
+//! "Reply" text field. Elsewhere clicking the notification opens the
+//! conversation to reply there.
+//!
+//! Some servers only signal the connection that posted a notification, so
+//! everything is posted on one shared connection, and a single thread listens
+//! there for the responses to all of them.
+
+use std::collections::HashMap;
+use std::sync::{LazyLock, Mutex};
+
+use tauri::AppHandle;
+use zbus::blocking::{Connection, MessageIterator};
+use zbus::message::Type;
+use zbus::zvariant::Value;
+use zbus::{MatchRule, Message};
+
+use super::{Notice, NotificationAction};
+
+const BUS_NAME: &str = "org.freedesktop.Notifications";
+const OBJECT_PATH: &str = "/org/freedesktop/Notifications";
+const INTERFACE: &str = "org.freedesktop.Notifications";
+
+/// Notification servers invoke the "default" action when the notification
+/// body itself is clicked.
+const DEFAULT_ACTION: &str = "default";
+/// Turned into a text field by servers with the `inline-reply` capability;
+/// the text comes back in a `NotificationReplied` signal.
+const INLINE_REPLY_ACTION: &str = "inline-reply";
+const MARK_READ_ACTION: &str = "mark-read";
+const MUTE_ACTION: &str = "mute";
+/// freedesktop sound theme name for incoming messages.
+const DEFAULT_SOUND: &str = "message-new-instant";
+const URGENCY_CRITICAL: u8 = 2;
+
+struct Server {
+    connection: Connection,
+    inline_reply: bool,
+}
+
+static SERVER: LazyLock<Result<Server, String>> = LazyLock::new(connect);
+
+/// Notifications that may still be answered, by server ID. Replacing a
+/// notification keeps its ID, so the new notice simply takes over the entry.
+static SHOWN: LazyLock<Mutex<HashMap<u32, (AppHandle, Notice)>>> = LazyLock::new(Default::default);
+
+pub fn show(app: &AppHandle, notice: Notice, replaces: Option<u32>) -> Result<Option<u32>, String> {
+    let server = SERVER.as_ref().map_err(Clone::clone)?;
+
+    let mut actions = vec![DEFAULT_ACTION, "Open"];
+    if notice.message.is_some() {
+        if server.inline_reply {
+            actions.extend([INLINE_REPLY_ACTION, "Reply"]);
+        }
+        actions.extend([MARK_READ_ACTION, "Mark as read", MUTE_ACTION, "Mute for 1 hour"]);
+    }
+    let mut hints: HashMap<&str, Value> = HashMap::new();
+    if !notice.sound.uses_platform() {
+        // Played by us, or silent
+        hints.insert("suppress-sound", Value::Bool(true));
+    } else if notice.urgent {
+        hints.insert("sound-name", Value::from(DEFAULT_SOUND));
+    }
+    if notice.urgent {
+        hints.insert("urgency", Value::U8(URGENCY_CRITICAL));
+    }
+
+    let reply = server
+        .connection
+        .call_method(
+            Some(BUS_NAME),
+            OBJECT_PATH,
+            Some(INTERFACE),
+            "Notify",
+            &(
+                "Teamy",
+                replaces.unwrap_or(0),
+                "",
+                notice.title.as_str(),
+                notice.body.as_str(),
+                actions,
+                hints,
+                // The server's default timeout
+                -1i32,
+            ),
+        )
+        .map_err(|e| e.to_string())?;
+    let id: u32 = reply.body().deserialize().map_err(|e| e.to_string())?;
+    SHOWN.lock().unwrap().insert(id, (app.clone(), notice));
+    Ok(Some(id))
+}
+
+fn connect() -> Result<Server, String> {
+    let connection = Connection::session().map_err(|e| format!("No session bus: {}", e))?;
+    let rule = MatchRule::builder()
+        .msg_type(Type::Signal)
+        .interface(INTERFACE)
+        .and_then(|rule| rule.path(OBJECT_PATH))
+        .map_err(|e| e.to_string())?
+        .build();
+    // Subscribe before anything is posted, so no response can slip past
+    let signals = MessageIterator::for_match_rule(rule, &connection, None).map_err(|e| e.to_string())?;
+    std::thread::spawn(move || run_listener(signals));
+
+    let capabilities: Vec<String> = connection
+        .call_method(Some(BUS_NAME), OBJECT_PATH, Some(INTERFACE), "GetCapabilities", &())
+        .and_then(|reply| reply.body().deserialize())
+        .unwrap_or_default();
+    Ok(Server {
+        connection,
+        inline_reply: capabilities.iter().any(|c| c == INLINE_REPLY_ACTION),
+    })
+}
+
+fn run_listener(signals: MessageIterator) {
+    for signal in signals.flatten() {
+        let Some((id, action)) = parse_signal(&signal) else {
+            continue;
+        };
+        let Some(action) = action else {
+            SHOWN.lock().unwrap().remove(&id);
+            continue;
+        };
+        // Ours only; other apps' notifications are signalled here too
+        let shown = SHOWN.lock().unwrap().get(&id).cloned();
+        if let Some((app, notice)) = shown {
+            super::handle(&app, &notice, action);
+        }
+    }
+}
+
+/// The notification a signal is about, and the user's response; `None` once
+/// it's been closed.
+fn parse_signal(signal: &Message) -> Option<(u32, Option<NotificationAction>)> {
+    let header = signal.header();
+    let body = signal.body();
+    match header.member()?.as_str() {
+        "ActionInvoked" => {
+            let (id, key): (u32, String) = body.deserialize().ok()?;
+            let action = match key.as_str() {
+                DEFAULT_ACTION => NotificationAction::Open,
+                MARK_READ_ACTION => NotificationAction::MarkRead,
+                MUTE_ACTION => NotificationAction::Mute,
+                _ => return None,
+            };
+            Some((id, Some(action)))
+        }
+        "NotificationReplied" => {
+            let (id, text): (u32, String) = body.deserialize().ok()?;
+            Some((id, Some(NotificationAction::Reply(text))))
+        }
+        "NotificationClosed" => {
+            let (id, _reason): (u32, u32) = body.deserialize().ok()?;
+            Some((id, None))
+        }
+        _ => None,
+    }
+}
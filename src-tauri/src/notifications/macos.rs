@@ -0,0 +1,135 @@
+//! Notification Center via `mac-notification-sys`. A notification carries
+//! one main button and one close button, and a text field takes the main
+//! button's place. Message notifications therefore get a dropdown with
+//! "Reply", "Mark as read" and "Mute" next to a plain "Close"; picking
+//! "Reply" posts a follow-up notification with the text field. The API can't
+//! replace a delivered notification, so summaries arrive as new ones.
+//!
+//! `send` blocks until the user responds, so a small pool of threads waits on
+//! notifications. With every one of them waiting, further notifications are
+//! posted without quick actions rather than piling up threads.
+
+use std::sync::mpsc::{self, Receiver, SyncSender};
+use std::sync::{Arc, LazyLock, Mutex, Once};
+
+use mac_notification_sys::{MainButton, Notification, NotificationResponse};
+use tauri::AppHandle;
+
+use super::{Notice, NotificationAction};
+
+const REPLY_ACTION: &str = "Reply";
+const MUTE_ACTION: &str = "Mute for 1 hour";
+const MARK_READ_ACTION: &str = "Mark as read";
+/// Dismisses the notification without doing anything.
+const CLOSE_BUTTON: &str = "Close";
+
+/// Notifications that can wait for a response at the same time.
+const LISTENERS: usize = 4;
+
+static REGISTER_BUNDLE: Once = Once::new();
+
+/// Hands a notification to an idle listener; the channel has no buffer, so
+/// sending only succeeds while one is waiting for work.
+static POOL: LazyLock<SyncSender<(AppHandle, Notice)>> = LazyLock::new(|| {
+    let (tx, rx) = mpsc::sync_channel(0);
+    let rx = Arc::new(Mutex::new(rx));
+    for _ in 0..LISTENERS {
+        let rx = rx.clone();
+        std::thread::spawn(move || run_listener(rx));
+    }
+    tx
+});
+
+pub fn show(app: &AppHandle, notice: Notice, _replaces: Option<u32>) -> Result<Option<u32>, String> {
+    // Notifications are posted (and responses delivered) on behalf of our bundle
+    REGISTER_BUNDLE.call_once(|| {
+        if let Err(e) = mac_notification_sys::set_application(&app.config().identifier) {
+            eprintln!("[notifications] Failed to register bundle: {}", e);
+        }
+    });
+
+    match POOL.try_send((app.clone(), notice)) {
+        Ok(()) => Ok(None),
+        Err(mpsc::TrySendError::Full((_, notice)) | mpsc::TrySendError::Disconnected((_, notice))) => {
+            eprintln!("[notifications] All listeners busy, posting without actions");
+            build(&notice, Buttons::None)
+                .send()
+                .map(|_| None)
+                .map_err(|e| e.to_string())
+        }
+    }
+}
+
+fn run_listener(jobs: Arc<Mutex<Receiver<(AppHandle, Notice)>>>) {
+    loop {
+        // The lock is released before the notification is shown
+        let Ok((app, notice)) = jobs.lock().unwrap().recv() else {
+            return;
+        };
+        if let Some(action) = respond(&notice) {
+            super::handle(&app, &notice, action);
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Buttons {
+    /// Posted without waiting for a response.
+    None,
+    /// The quick actions, for message notifications.
+    Actions,
+    /// Only a reply field.
+    ReplyField,
+}
+
+/// Show `notice` and wait for what the user does with it.
+fn respond(notice: &Notice) -> Option<NotificationAction> {
+    let mut buttons = Buttons::Actions;
+    loop {
+        match build(notice, buttons).send() {
+            Ok(NotificationResponse::ActionButton(action)) if action == REPLY_ACTION => buttons = Buttons::ReplyField,
+            Ok(NotificationResponse::ActionButton(action)) if action == MARK_READ_ACTION => {
+                return Some(NotificationAction::MarkRead)
+            }
+            Ok(NotificationResponse::ActionButton(action)) if action == MUTE_ACTION => {
+                return Some(NotificationAction::Mute)
+            }
+            Ok(NotificationResponse::Click) => return Some(NotificationAction::Open),
+            Ok(NotificationResponse::Reply(text)) => return Some(NotificationAction::Reply(text)),
+            Ok(_) => return None,
+            Err(e) => {
+                eprintln!("[notifications] Failed to show notification: {}", e);
+                return None;
+            }
+        }
+    }
+}
+
+fn build(notice: &Notice, buttons: Buttons) -> Notification<'_> {
+    let mut notification = Notification::new();
+    notification
+        .title(&notice.title)
+        .message(&notice.body)
+        .wait_for_click(!matches!(buttons, Buttons::None));
+    if notice.message.is_some() {
+        match buttons {
+            Buttons::None => {}
+            Buttons::Actions => {
+                notification
+                    .main_button(MainButton::DropdownActions(
+                        "Actions",
+                        &[REPLY_ACTION, MARK_READ_ACTION, MUTE_ACTION],
+                    ))
+                    .close_button(CLOSE_BUTTON);
+            }
+            Buttons::ReplyField => {
+                notification.main_button(MainButton::Response(REPLY_ACTION));
+            }
+        }
+    }
+    // Banners are silent by default; other sounds are played by us
+    if notice.urgent && notice.sound.uses_platform() {
+        notification.sound(mac_notification_sys::Sound::Default);
+    }
+    notification
+}
@@ -0,0 +1,548 @@
+//! Policy, grouping, coalescing and rate limiting.
+//!
+//! Each notification is first run through the user's rules, which may hold
+//! it back, change how it sounds or escalate it past everything below. Then
+//! it's checked against the per-conversation mutes and the
+//! [`NotificationPolicy`]; suppressed ones are dropped. The rest are
+//! grouped by conversation. The first message of a quiet conversation is
+//! shown straight away; anything arriving within
+//! [`COALESCE_WINDOW`] of the last banner is held and then shown as one
+//! summary ("5 new messages from Alice") that replaces the previous banner
+//! where the platform allows it. On top of that at most
+//! [`GLOBAL_LIMIT`] banners are shown per [`GLOBAL_PERIOD`]; groups over the
+//! limit keep accumulating until a slot frees up.
+//!
+//! Every decision, shown or not, is recorded in the cache's notification
+//! history and announced as `notifications:recorded`.
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use chrono::{Local, SecondsFormat, Utc};
+use tauri::{AppHandle, Emitter, Manager, UserAttentionType};
+use tokio::sync::Notify;
+
+use super::policy::{NotificationPolicy, SuppressReason};
+use super::rules::{DryRun, Rule, RuleAction, RuleSet, SampleMessage};
+use super::{mutes, platform, sounds, Notice, NotificationTarget, Sound};
+use crate::cache::{Cache, NotificationRecord};
+use crate::presence::PresenceService;
+
+/// Minimum gap between banners for one conversation.
+const COALESCE_WINDOW: Duration = Duration::from_secs(5);
+const GLOBAL_LIMIT: usize = 6;
+const GLOBAL_PERIOD: Duration = Duration::from_secs(60);
+/// After this long without a banner a conversation's count starts over.
+const GROUP_RESET: Duration = Duration::from_secs(10 * 60);
+
+#[derive(Clone)]
+pub struct NotificationManager {
+    inner: Arc<Inner>,
+}
+
+struct Inner {
+    app: AppHandle,
+    cache: Cache,
+    policy: Mutex<NotificationPolicy>,
+    rules: Mutex<RuleSet>,
+    state: Mutex<State>,
+    wake: Notify,
+}
+
+#[derive(Default)]
+struct State {
+    groups: HashMap<String, Group>,
+    /// When recent banners were shown, for the global limit.
+    recent: VecDeque<Instant>,
+}
+
+#[derive(Default)]
+struct Group {
+    pending: Vec<Notice>,
+    /// When the oldest pending notice arrived; decides who goes first when
+    /// the global limit is hit.
+    waiting_since: Option<Instant>,
+    last_shown: Option<Instant>,
+    /// Messages covered by the banner currently on screen.
+    shown_count: usize,
+    /// Senders covered by that banner, in order of appearance.
+    senders: Vec<String>,
+    /// Platform ID of that banner, for replacing it.
+    platform_id: Option<u32>,
+}
+
+impl NotificationManager {
+    pub fn new(app: AppHandle, cache: Cache) -> Self {
+        Self {
+            inner: Arc::new(Inner {
+                cache,
+                policy: Mutex::new(NotificationPolicy::load(&app)),
+                rules: Mutex::new(RuleSet::load(&app)),
+                app,
+                state: Mutex::new(State::default()),
+                wake: Notify::new(),
+            }),
+        }
+    }
+
+    /// Spawn the delivery loop. Call once.
+    pub fn start(&self) {
+        let inner = self.inner.clone();
+        tauri::async_runtime::spawn(async move {
+            inner.run().await;
+        });
+    }
+
+    /// Queue a notification; it's shown now, or folded into a summary later.
+    /// Returns why it was suppressed instead, if it was.
+    pub fn post(&self, mut notice: Notice) -> Option<SuppressReason> {
+        let rule = self.inner.rules.lock().unwrap().evaluate(&notice, &Local::now()).cloned();
+        if notice.sound == Sound::Default {
+            notice.sound = sounds::chat_sound(&self.inner.app, &notice.target.key());
+        }
+        let mut reason = None;
+        let mut escalate = false;
+        if let Some(rule) = &rule {
+            match rule.action {
+                RuleAction::Notify => {}
+                RuleAction::Silent => notice.sound = Sound::Silent,
+                RuleAction::Sound => notice.sound = rule.sound.clone().unwrap_or_default(),
+                RuleAction::BadgeOnly => reason = Some(SuppressReason::Rule),
+                RuleAction::Escalate => {
+                    notice.urgent = true;
+                    escalate = true;
+                }
+            }
+        }
+        if reason.is_none() && !escalate {
+            reason = self.inner.suppress_reason(&notice);
+        }
+        self.inner.record(&notice, reason, rule.map(|r| r.id));
+
+        if reason.is_some() {
+            return reason;
+        }
+        if escalate {
+            self.inner.escalate(notice);
+            return None;
+        }
+        {
+            let mut state = self.inner.state.lock().unwrap();
+            let group = state.groups.entry(notice.target.key()).or_default();
+            group.waiting_since.get_or_insert_with(Instant::now);
+            group.pending.push(notice);
+        }
+        self.inner.wake.notify_one();
+        None
+    }
+
+    pub fn policy(&self) -> NotificationPolicy {
+        self.inner.policy.lock().unwrap().clone()
+    }
+
+    pub fn set_policy(&self, policy: NotificationPolicy) -> Result<(), String> {
+        policy.validate()?;
+        policy.save(&self.inner.app)?;
+        *self.inner.policy.lock().unwrap() = policy;
+        Ok(())
+    }
+
+    pub fn rules(&self) -> Vec<Rule> {
+        self.inner.rules.lock().unwrap().rules().to_vec()
+    }
+
+    pub fn set_rules(&self, rules: Vec<Rule>) -> Result<(), String> {
+        let rules = RuleSet::new(rules)?;
+        rules.save(&self.inner.app)?;
+        *self.inner.rules.lock().unwrap() = rules;
+        Ok(())
+    }
+
+    /// Conversations with a sound of their own, by key.
+    pub fn chat_sounds(&self) -> HashMap<String, Sound> {
+        sounds::chat_sounds(&self.inner.app)
+    }
+
+    pub fn set_chat_sound(&self, key: &str, sound: Sound) -> Result<(), String> {
+        sounds::set_chat_sound(&self.inner.app, key, sound)
+    }
+
+    /// Play `sound` now, as a notification would.
+    pub fn preview_sound(&self, sound: &Sound) -> Result<(), String> {
+        sound.validate()?;
+        sounds::play(sound)
+    }
+
+    /// Which rule `sample` would hit, among `rules` or else the saved ones.
+    pub fn dry_run(&self, sample: SampleMessage, rules: Option<Vec<Rule>>) -> Result<DryRun, String> {
+        match rules {
+            Some(rules) => RuleSet::new(rules)?.dry_run(sample),
+            None => self.inner.rules.lock().unwrap().dry_run(sample),
+        }
+    }
+
+    /// The conversation was read: drop anything held back for it and start
+    /// counting from zero.
+    pub fn dismiss(&self, key: &str) {
+        self.inner.state.lock().unwrap().groups.remove(key);
+        if self.inner.cache.is_available() {
+            if let Err(e) = self.inner.cache.mark_conversation_notifications_seen(key) {
+                eprintln!("[notifications] {}", e);
+            }
+        }
+    }
+}
+
+impl Inner {
+    fn record(&self, notice: &Notice, reason: Option<SuppressReason>, rule_id: Option<String>) {
+        if !self.cache.is_available() {
+            return;
+        }
+        let (chat_id, team_id, channel_id) = match &notice.target {
+            NotificationTarget::Chat(chat_id) => (Some(chat_id.clone()), None, None),
+            NotificationTarget::Channel { team_id, channel_id } => (None, Some(team_id.clone()), Some(channel_id.clone())),
+        };
+        let record = NotificationRecord {
+            id: 0,
+            created_at: Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true),
+            conversation: notice.target.key(),
+            chat_id,
+            team_id,
+            channel_id,
+            message_id: notice.message.as_ref().map(|m| m.id.clone()).filter(|id| !id.is_empty()),
+            title: notice.title.clone(),
+            body: notice.body.clone(),
+            shown: reason.is_none(),
+            reason: reason.map(|r| r.as_str().to_string()),
+            rule_id,
+            seen: false,
+        };
+        match self.cache.record_notification(record) {
+            Ok(record) => {
+                let _ = self.app.emit("notifications:recorded", record);
+            }
+            Err(e) => eprintln!("[notifications] {}", e),
+        }
+    }
+
+    fn suppress_reason(&self, notice: &Notice) -> Option<SuppressReason> {
+        if mutes::is_muted(&self.app, &notice.target.key()) {
+            return Some(SuppressReason::ChatMuted);
+        }
+        let presence = self.app.try_state::<PresenceService>().and_then(|p| p.mine());
+        self.policy
+            .lock()
+            .unwrap()
+            .evaluate(notice, presence.as_ref(), Local::now())
+    }
+
+    /// Show right away, skipping coalescing and the global limit, and ask for
+    /// the user's attention.
+    fn escalate(&self, notice: Notice) {
+        match self.show(notice, None) {
+            Ok(_) => self.state.lock().unwrap().recent.push_back(Instant::now()),
+            Err(e) => eprintln!("[notifications] Failed to show notification: {}", e),
+        }
+        if let Some(window) = self.app.get_webview_window("main") {
+            let _ = window.request_user_attention(Some(UserAttentionType::Critical));
+        }
+    }
+
+    fn show(&self, notice: Notice, replaces: Option<u32>) -> Result<Option<u32>, String> {
+        if let Err(e) = sounds::play(&notice.sound) {
+            eprintln!("[notifications] {}", e);
+        }
+        platform::show(&self.app, notice, replaces)
+    }
+
+    async fn run(&self) {
+        loop {
+            let next = self.deliver_due();
+            match next {
+                Some(delay) => {
+                    tokio::select! {
+                        _ = tokio::time::sleep(delay) => {}
+                        _ = self.wake.notified() => {}
+                    }
+                }
+                None => self.wake.notified().await,
+            }
+        }
+    }
+
+    /// Show every group that's due and allowed, oldest first. Returns how long
+    /// until the next held group could go.
+    fn deliver_due(&self) -> Option<Duration> {
+        let now = Instant::now();
+        // Showing can block on the platform; don't hold up `post` meanwhile
+        let (banners, next) = self.state.lock().unwrap().take_due(now);
+        for banner in banners {
+            match self.show(banner.notice, banner.replaces) {
+                Ok(id) => self.state.lock().unwrap().shown(&banner.key, id.or(banner.replaces), now),
+                Err(e) => eprintln!("[notifications] Failed to show notification: {}", e),
+            }
+        }
+        next
+    }
+}
+
+/// A banner taken out of its group, to be shown.
+struct Banner {
+    key: String,
+    notice: Notice,
+    /// Platform ID of the group's banner on screen.
+    replaces: Option<u32>,
+}
+
+impl State {
+    /// Take a banner from every group that's due, as long as the global
+    /// limit allows, oldest first. Also returns how long until the next held
+    /// group could go.
+    fn take_due(&mut self, now: Instant) -> (Vec<Banner>, Option<Duration>) {
+        while self.recent.front().is_some_and(|t| now.duration_since(*t) >= GLOBAL_PERIOD) {
+            self.recent.pop_front();
+        }
+        self.groups
+            .retain(|_, g| !g.pending.is_empty() || g.last_shown.is_some_and(|t| now.duration_since(t) < GROUP_RESET));
+
+        let mut waiting: Vec<(Instant, String)> = self
+            .groups
+            .iter()
+            .filter_map(|(key, g)| Some((g.waiting_since?, key.clone())))
+            .collect();
+        waiting.sort();
+
+        let mut banners = Vec::new();
+        let mut next: Option<Duration> = None;
+        let mut soonest = |delay: Duration| next = Some(next.map_or(delay, |n| n.min(delay)));
+        for (_, key) in waiting {
+            let group = self.groups.get_mut(&key).unwrap();
+            let ready_in = group
+                .last_shown
+                .map(|t| COALESCE_WINDOW.saturating_sub(now.duration_since(t)))
+                .unwrap_or_default();
+            if !ready_in.is_zero() {
+                soonest(ready_in);
+                continue;
+            }
+            // Banners taken this round count once they're shown
+            if self.recent.len() + banners.len() >= GLOBAL_LIMIT {
+                let oldest = self.recent.front().copied().unwrap_or(now);
+                soonest(GLOBAL_PERIOD.saturating_sub(now.duration_since(oldest)));
+                continue;
+            }
+
+            let notice = group.take_banner(now);
+            let replaces = group.platform_id;
+            banners.push(Banner { key, notice, replaces });
+        }
+        (banners, next)
+    }
+
+    /// Count a banner taken by [`Self::take_due`] as shown at `now`.
+    fn shown(&mut self, key: &str, platform_id: Option<u32>, now: Instant) {
+        self.recent.push_back(now);
+        // The conversation may have been read meanwhile
+        if let Some(group) = self.groups.get_mut(key) {
+            group.last_shown = Some(now);
+            group.platform_id = platform_id;
+        }
+    }
+}
+
+impl Group {
+    /// Fold the pending notices into the banner to show now. The group only
+    /// counts as shown once [`State::shown`] says so.
+    fn take_banner(&mut self, now: Instant) -> Notice {
+        if self.last_shown.is_none_or(|t| now.duration_since(t) >= GROUP_RESET) {
+            self.shown_count = 0;
+            self.senders.clear();
+            self.platform_id = None;
+        }
+        let pending = std::mem::take(&mut self.pending);
+        self.waiting_since = None;
+        self.shown_count += pending.len();
+        for notice in &pending {
+            if !self.senders.contains(&notice.title) {
+                self.senders.push(notice.title.clone());
+            }
+        }
+
+        let latest = pending.into_iter().last().expect("groups are only shown with pending notices");
+        if self.shown_count == 1 {
+            return latest;
+        }
+        Notice {
+            title: sender_list(&self.senders),
+            body: format!(
+                "{} new messages from {}\n{}",
+                self.shown_count,
+                match self.senders.as_slice() {
+                    [only] => only.clone(),
+                    senders => format!("{} people", senders.len()),
+                },
+                latest.body
+            ),
+            ..latest
+        }
+    }
+}
+
+/// "Alice", "Alice and Bob", "Alice, Bob and Carol", "Alice, Bob and 3 others".
+fn sender_list(senders: &[String]) -> String {
+    match senders {
+        [] => String::new(),
+        [only] => only.clone(),
+        [first, second] => format!("{} and {}", first, second),
+        [first, second, third] => format!("{}, {} and {}", first, second, third),
+        [first, second, rest @ ..] => format!("{}, {} and {} others", first, second, rest.len()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn notice(chat: &str, sender: &str, body: &str) -> Notice {
+        Notice {
+            title: sender.to_string(),
+            body: body.to_string(),
+            target: NotificationTarget::Chat(chat.to_string()),
+            message: None,
+            sound: Sound::Default,
+            urgent: false,
+        }
+    }
+
+    fn post(state: &mut State, notice: Notice, now: Instant) {
+        let group = state.groups.entry(notice.target.key()).or_default();
+        group.waiting_since.get_or_insert(now);
+        group.pending.push(notice);
+    }
+
+    fn names(names: &[&str]) -> Vec<String> {
+        names.iter().map(|n| n.to_string()).collect()
+    }
+
+    #[test]
+    fn lists_senders() {
+        assert_eq!(sender_list(&[]), "");
+        assert_eq!(sender_list(&names(&["Alice"])), "Alice");
+        assert_eq!(sender_list(&names(&["Alice", "Bob"])), "Alice and Bob");
+        assert_eq!(sender_list(&names(&["Alice", "Bob", "Carol"])), "Alice, Bob and Carol");
+        assert_eq!(sender_list(&names(&["Alice", "Bob", "Carol", "Dave", "Eve"])), "Alice, Bob and 3 others");
+    }
+
+    #[test]
+    fn shows_a_single_message_as_is() {
+        let now = Instant::now();
+        let mut group = Group::default();
+        group.pending.push(notice("chat", "Alice", "Hi"));
+
+        let banner = group.take_banner(now);
+        assert_eq!((banner.title.as_str(), banner.body.as_str()), ("Alice", "Hi"));
+        assert!(group.pending.is_empty());
+        assert_eq!(group.waiting_since, None);
+        assert_eq!(group.last_shown, None);
+    }
+
+    #[test]
+    fn summarizes_messages_since_the_last_banner() {
+        let now = Instant::now();
+        let mut group = Group::default();
+        group.pending.push(notice("chat", "Alice", "Hi"));
+        group.take_banner(now);
+        group.last_shown = Some(now);
+        group.platform_id = Some(7);
+
+        group.pending.push(notice("chat", "Bob", "Hello"));
+        group.pending.push(notice("chat", "Alice", "Lunch?"));
+        let banner = group.take_banner(now + COALESCE_WINDOW);
+        assert_eq!(banner.title, "Alice and Bob");
+        assert_eq!(banner.body, "3 new messages from 2 people\nLunch?");
+        assert_eq!(group.platform_id, Some(7));
+    }
+
+    #[test]
+    fn starts_over_after_a_quiet_spell() {
+        let now = Instant::now();
+        let mut group = Group::default();
+        group.pending.push(notice("chat", "Alice", "Hi"));
+        group.take_banner(now);
+        group.last_shown = Some(now);
+        group.platform_id = Some(7);
+
+        group.pending.push(notice("chat", "Bob", "Hello"));
+        let banner = group.take_banner(now + GROUP_RESET);
+        assert_eq!((banner.title.as_str(), banner.body.as_str()), ("Bob", "Hello"));
+        assert_eq!(group.senders, names(&["Bob"]));
+        assert_eq!(group.platform_id, None);
+    }
+
+    #[test]
+    fn holds_messages_within_the_coalesce_window() {
+        let now = Instant::now();
+        let mut state = State::default();
+        post(&mut state, notice("chat", "Alice", "Hi"), now);
+        let (banners, next) = state.take_due(now);
+        assert_eq!(banners.len(), 1);
+        assert_eq!(next, None);
+        state.shown("chat", Some(7), now);
+
+        let later = now + Duration::from_secs(2);
+        post(&mut state, notice("chat", "Alice", "Still there?"), later);
+        let (banners, next) = state.take_due(later);
+        assert!(banners.is_empty());
+        assert_eq!(next, Some(COALESCE_WINDOW - Duration::from_secs(2)));
+
+        let (banners, _) = state.take_due(now + COALESCE_WINDOW);
+        assert_eq!(banners.len(), 1);
+        assert_eq!(banners[0].replaces, Some(7));
+        assert_eq!(banners[0].notice.body, "2 new messages from Alice\nStill there?");
+    }
+
+    #[test]
+    fn holds_groups_over_the_global_limit() {
+        let now = Instant::now();
+        let mut state = State::default();
+        for i in 0..GLOBAL_LIMIT + 2 {
+            post(&mut state, notice(&format!("chat{}", i), "Alice", "Hi"), now + Duration::from_millis(i as u64));
+        }
+        let later = now + Duration::from_secs(1);
+        let (banners, next) = state.take_due(later);
+        assert_eq!(banners.len(), GLOBAL_LIMIT);
+        // Oldest first
+        assert_eq!(banners[0].key, "chat0");
+        for banner in &banners {
+            state.shown(&banner.key, None, later);
+        }
+        assert_eq!(next, Some(GLOBAL_PERIOD));
+
+        let (banners, next) = state.take_due(later + Duration::from_secs(30));
+        assert!(banners.is_empty());
+        assert_eq!(next, Some(Duration::from_secs(30)));
+
+        let (banners, _) = state.take_due(later + GLOBAL_PERIOD);
+        let keys: Vec<_> = banners.iter().map(|b| b.key.as_str()).collect();
+        assert_eq!(keys, [format!("chat{}", GLOBAL_LIMIT), format!("chat{}", GLOBAL_LIMIT + 1)]);
+    }
+
+    #[test]
+    fn failed_banners_dont_count() {
+        let now = Instant::now();
+        let mut state = State::default();
+        for i in 0..GLOBAL_LIMIT {
+            post(&mut state, notice(&format!("chat{}", i), "Alice", "Hi"), now);
+        }
+        // None of them made it to the screen
+        let (banners, _) = state.take_due(now);
+        assert_eq!(banners.len(), GLOBAL_LIMIT);
+
+        post(&mut state, notice("chat0", "Alice", "Hello?"), now);
+        let (banners, next) = state.take_due(now);
+        assert_eq!(banners.len(), 1);
+        assert_eq!(banners[0].notice.body, "Hello?");
+        assert_eq!(next, None);
+    }
+}
@@ -0,0 +1,137 @@
+//! Native notifications that lead back to their conversation.
+//!
+//! The notification plugin can show notifications on desktop but never
+//! reports clicks or actions, so each platform gets a small backend that
+//! does: macOS through `mac-notification-sys`, Linux through freedesktop
+//! notifications over D-Bus. A click shows the main window and emits the same `navigate`
+//! event the tray uses. Notifications about a message also offer quick
+//! actions (reply, mark as read, mute), which are handled here in Rust so
+//! they work without opening the window. Other platforms fall back to the
+//! plugin, where a click only brings the app forward.
+//!
+//! Everything goes through [`NotificationManager`], which applies the user's
+//! notification rules and the [`NotificationPolicy`] (quiet hours, mutes,
+//! presence) and then groups, coalesces and rate-limits banners per
+//! conversation.
+
+mod actions;
+mod manager;
+mod mutes;
+mod policy;
+mod rules;
+mod sounds;
+#[cfg_attr(target_os = "macos", path = "macos.rs")]
+#[cfg_attr(all(unix, not(target_os = "macos")), path = "linux.rs")]
+#[cfg_attr(not(unix), path = "fallback.rs")]
+mod platform;
+
+use chrono::{SecondsFormat, Utc};
+use tauri::AppHandle;
+
+pub use manager::NotificationManager;
+pub use policy::NotificationPolicy;
+pub use rules::{DryRun, Rule, SampleMessage};
+pub use sounds::{Sound, BUNDLED as BUNDLED_SOUNDS};
+
+/// The conversation a notification opens when clicked.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NotificationTarget {
+    Chat(String),
+    Channel { team_id: String, channel_id: String },
+}
+
+impl NotificationTarget {
+    /// A channel when both team and channel are known, the chat otherwise.
+    pub fn new(chat_id: &str, team_id: Option<&str>, channel_id: Option<&str>) -> Self {
+        match (team_id, channel_id) {
+            (Some(team_id), Some(channel_id)) => Self::Channel {
+                team_id: team_id.to_string(),
+                channel_id: channel_id.to_string(),
+            },
+            _ => Self::Chat(chat_id.to_string()),
+        }
+    }
+
+    /// The webview route showing this conversation.
+    pub fn route(&self) -> String {
+        match self {
+            Self::Chat(chat_id) => format!("/chat/{}", chat_id),
+            Self::Channel { team_id, channel_id } => format!("/channel/{}/{}", team_id, channel_id),
+        }
+    }
+
+    /// Conversation key as used by the sync and unread store: the chat ID or
+    /// `<teamId>:<channelId>`.
+    pub fn key(&self) -> String {
+        match self {
+            Self::Chat(chat_id) => chat_id.clone(),
+            Self::Channel { team_id, channel_id } => format!("{}:{}", team_id, channel_id),
+        }
+    }
+}
+
+/// What a notification is about. Notifications with a `message` get the
+/// quick actions.
+#[derive(Debug, Clone)]
+pub struct Notice {
+    pub title: String,
+    pub body: String,
+    pub target: NotificationTarget,
+    pub message: Option<MessageRef>,
+    pub sound: Sound,
+    /// Shown with the platform's urgent style.
+    pub urgent: bool,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct MessageRef {
+    pub id: String,
+    pub created_at: String,
+    pub sender_id: String,
+    pub sender_email: Option<String>,
+    /// `oneOnOne`, `group`, `meeting` or `channel`.
+    pub chat_type: String,
+    /// Full plain-text content, for keyword rules.
+    pub text: String,
+    pub mentions_me: bool,
+}
+
+/// How the user responded to a notification.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NotificationAction {
+    /// Clicked the notification itself.
+    Open,
+    /// Typed a reply into the notification (macOS, and Linux servers with
+    /// inline replies).
+    #[cfg_attr(not(unix), allow(dead_code))]
+    Reply(String),
+    MarkRead,
+    /// Mute the conversation for [`actions::MUTE_DURATION`].
+    Mute,
+}
+
+/// Mark `target` read up to now, as the notification's "Mark as read" does.
+pub fn mark_read(app: &AppHandle, target: &NotificationTarget) {
+    let now = Utc::now().to_rfc3339_opts(SecondsFormat::Millis, true);
+    actions::mark_read_at(app, target, now);
+}
+
+/// Act on the user's response to `notice`. Called from the platform
+/// backends, off the main thread.
+fn handle(app: &AppHandle, notice: &Notice, action: NotificationAction) {
+    let result = match action {
+        NotificationAction::Open => {
+            actions::open(app, &notice.target);
+            Ok(())
+        }
+        NotificationAction::Reply(text) => actions::reply(app, notice, &text),
+        NotificationAction::MarkRead => {
+            actions::mark_read(app, notice);
+            Ok(())
+        }
+        NotificationAction::Mute => mutes::mute(app, &notice.target.key(), actions::MUTE_DURATION),
+    };
+    if let Err(e) = result {
+        eprintln!("[notifications] Notification action failed: {}", e);
+    }
+}
@@ -0,0 +1,40 @@
+//! Per-conversation "mute until" times, kept in the settings store so they
+//! survive restarts.
+
+use std::time::Duration;
+
+use chrono::{DateTime, SecondsFormat, Utc};
+use serde_json::{Map, Value};
+use tauri::AppHandle;
+
+use crate::settings;
+
+const MUTES_KEY: &str = "notificationMutes";
+
+/// Silence notifications for `key` (chat ID or `<teamId>:<channelId>`) for `duration`.
+pub fn mute(app: &AppHandle, key: &str, duration: Duration) -> Result<(), String> {
+    let until = Utc::now() + chrono::Duration::from_std(duration).unwrap_or_default();
+
+    // Drop expired entries while we're here
+    let now = Utc::now();
+    let mut mutes: Map<String, Value> = settings::load(app, MUTES_KEY).unwrap_or_default();
+    mutes.retain(|_, until| parse_until(until).is_some_and(|until| until > now));
+    mutes.insert(
+        key.to_string(),
+        Value::String(until.to_rfc3339_opts(SecondsFormat::Secs, true)),
+    );
+    settings::save(app, MUTES_KEY, &mutes)
+}
+
+/// Whether notifications for `key` are currently muted.
+pub fn is_muted(app: &AppHandle, key: &str) -> bool {
+    settings::load::<Map<String, Value>>(app, MUTES_KEY)
+        .and_then(|mutes| mutes.get(key).and_then(parse_until))
+        .is_some_and(|until| until > Utc::now())
+}
+
+fn parse_until(value: &Value) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(value.as_str()?)
+        .ok()
+        .map(|t| t.with_timezone(&Utc))
+}
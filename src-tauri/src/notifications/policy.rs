@@ -0,0 +1,266 @@
+//! When notifications may interrupt: quiet hours per weekday, a global
+//! "mute until", and automatic suppression while the user's presence says
+//! they're busy. VIP senders and @mentions can be allowed through all three.
+//! Per-conversation mutes (see [`super::mutes`]) are absolute.
+//!
+//! Stored in `settings.json` under `notificationPolicy`.
+
+use chrono::{DateTime, Datelike, Local, NaiveTime, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+
+use super::Notice;
+use crate::presence::Presence;
+use crate::settings;
+
+const POLICY_KEY: &str = "notificationPolicy";
+
+/// Activities during which Teams itself holds back notifications.
+const BUSY_ACTIVITIES: [&str; 3] = ["InAMeeting", "Presenting", "UrgentInterruptionsOnly"];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct NotificationPolicy {
+    /// Monday first. A window ending before it starts runs past midnight
+    /// into the next day; equal start and end cover the whole day.
+    pub quiet_hours: [QuietHours; 7],
+    /// Suppress everything (bar bypasses) until this RFC 3339 time.
+    pub mute_until: Option<String>,
+    /// Suppress while presence is Do Not Disturb, in a meeting or presenting.
+    pub follow_presence: bool,
+    /// Sender user IDs, email addresses or display names (case-insensitive)
+    /// that are always let through.
+    pub vip_senders: Vec<String>,
+    /// Let @mentions of the user through.
+    pub mentions_bypass: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct QuietHours {
+    pub enabled: bool,
+    /// `HH:MM`, local time.
+    pub start: String,
+    pub end: String,
+}
+
+impl Default for QuietHours {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            start: "18:00".to_string(),
+            end: "09:00".to_string(),
+        }
+    }
+}
+
+impl Default for NotificationPolicy {
+    fn default() -> Self {
+        Self {
+            quiet_hours: Default::default(),
+            mute_until: None,
+            follow_presence: true,
+            vip_senders: Vec::new(),
+            mentions_bypass: true,
+        }
+    }
+}
+
+/// Why a notification was held back.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SuppressReason {
+    ChatMuted,
+    MutedUntil,
+    QuietHours,
+    Presence,
+    /// A rule asked for badge only.
+    Rule,
+}
+
+impl SuppressReason {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::ChatMuted => "chat_muted",
+            Self::MutedUntil => "muted_until",
+            Self::QuietHours => "quiet_hours",
+            Self::Presence => "presence",
+            Self::Rule => "rule",
+        }
+    }
+}
+
+impl NotificationPolicy {
+    pub fn load(app: &AppHandle) -> Self {
+        settings::load(app, POLICY_KEY).unwrap_or_default()
+    }
+
+    pub fn save(&self, app: &AppHandle) -> Result<(), String> {
+        settings::save(app, POLICY_KEY, self)
+    }
+
+    /// Check every field so a bad time is reported when saving rather than
+    /// silently ignored later.
+    pub fn validate(&self) -> Result<(), String> {
+        for day in &self.quiet_hours {
+            parse_time(&day.start)?;
+            parse_time(&day.end)?;
+        }
+        if let Some(until) = &self.mute_until {
+            DateTime::parse_from_rfc3339(until).map_err(|e| format!("Invalid mute time {}: {}", until, e))?;
+        }
+        Ok(())
+    }
+
+    /// Whether `notice` should be held back right now, and why.
+    pub fn evaluate(&self, notice: &Notice, presence: Option<&Presence>, now: DateTime<Local>) -> Option<SuppressReason> {
+        if self.bypasses(notice) {
+            return None;
+        }
+        if self.is_muted_until(now.with_timezone(&Utc)) {
+            return Some(SuppressReason::MutedUntil);
+        }
+        if self.in_quiet_hours(&now) {
+            return Some(SuppressReason::QuietHours);
+        }
+        if self.follow_presence && presence.is_some_and(is_busy) {
+            return Some(SuppressReason::Presence);
+        }
+        None
+    }
+
+    fn bypasses(&self, notice: &Notice) -> bool {
+        let Some(message) = &notice.message else {
+            return false;
+        };
+        if self.mentions_bypass && message.mentions_me {
+            return true;
+        }
+        self.vip_senders.iter().map(|v| v.trim()).filter(|v| !v.is_empty()).any(|vip| {
+            vip.eq_ignore_ascii_case(&message.sender_id)
+                || vip.eq_ignore_ascii_case(&notice.title)
+                || message.sender_email.as_deref().is_some_and(|e| vip.eq_ignore_ascii_case(e))
+        })
+    }
+
+    fn is_muted_until(&self, now: DateTime<Utc>) -> bool {
+        self.mute_until
+            .as_deref()
+            .and_then(|until| DateTime::parse_from_rfc3339(until).ok())
+            .is_some_and(|until| until > now)
+    }
+
+    fn in_quiet_hours<Tz: TimeZone>(&self, now: &DateTime<Tz>) -> bool {
+        let today = now.weekday().num_days_from_monday() as usize;
+        let yesterday = (today + 6) % 7;
+        let time = now.time();
+
+        let window = |day: &QuietHours| -> Option<(NaiveTime, NaiveTime)> {
+            if !day.enabled {
+                return None;
+            }
+            Some((parse_time(&day.start).ok()?, parse_time(&day.end).ok()?))
+        };
+        let in_today = window(&self.quiet_hours[today]).is_some_and(|(start, end)| match start.cmp(&end) {
+            std::cmp::Ordering::Less => time >= start && time < end,
+            std::cmp::Ordering::Equal => true,
+            std::cmp::Ordering::Greater => time >= start,
+        });
+        // Yesterday's overnight window spilling into this morning
+        let from_yesterday = window(&self.quiet_hours[yesterday]).is_some_and(|(start, end)| start > end && time < end);
+        in_today || from_yesterday
+    }
+}
+
+fn is_busy(presence: &Presence) -> bool {
+    presence.availability == "DoNotDisturb" || BUSY_ACTIVITIES.contains(&presence.activity.as_str())
+}
+
+pub(super) fn parse_time(value: &str) -> Result<NaiveTime, String> {
+    NaiveTime::parse_from_str(value.trim(), "%H:%M").map_err(|e| format!("Invalid time {}: {}", value, e))
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::FixedOffset;
+
+    use super::*;
+
+    /// 2026-10-19 is a Monday.
+    fn at(day: u32, hour: u32, minute: u32) -> DateTime<FixedOffset> {
+        FixedOffset::east_opt(2 * 3600)
+            .unwrap()
+            .with_ymd_and_hms(2026, 10, 19 + day, hour, minute, 0)
+            .unwrap()
+    }
+
+    fn policy(windows: &[(usize, &str, &str)]) -> NotificationPolicy {
+        let mut policy = NotificationPolicy::default();
+        for &(day, start, end) in windows {
+            policy.quiet_hours[day] = QuietHours {
+                enabled: true,
+                start: start.to_string(),
+                end: end.to_string(),
+            };
+        }
+        policy
+    }
+
+    #[test]
+    fn quiet_within_a_daytime_window() {
+        let policy = policy(&[(0, "12:00", "13:00")]);
+        assert!(!policy.in_quiet_hours(&at(0, 11, 59)));
+        assert!(policy.in_quiet_hours(&at(0, 12, 0)));
+        assert!(!policy.in_quiet_hours(&at(0, 13, 0)));
+        assert!(!policy.in_quiet_hours(&at(1, 12, 30)));
+    }
+
+    #[test]
+    fn overnight_windows_run_into_the_next_morning() {
+        let policy = policy(&[(0, "22:00", "07:00")]);
+        assert!(!policy.in_quiet_hours(&at(0, 6, 0)));
+        assert!(!policy.in_quiet_hours(&at(0, 21, 59)));
+        assert!(policy.in_quiet_hours(&at(0, 22, 0)));
+        assert!(policy.in_quiet_hours(&at(0, 23, 59)));
+        // Tuesday has no window of its own
+        assert!(policy.in_quiet_hours(&at(1, 0, 0)));
+        assert!(policy.in_quiet_hours(&at(1, 6, 59)));
+        assert!(!policy.in_quiet_hours(&at(1, 7, 0)));
+        assert!(!policy.in_quiet_hours(&at(1, 22, 0)));
+    }
+
+    #[test]
+    fn sunday_night_spills_into_monday() {
+        let policy = policy(&[(6, "23:00", "08:00")]);
+        assert!(policy.in_quiet_hours(&at(0, 7, 30)));
+        assert!(!policy.in_quiet_hours(&at(0, 8, 0)));
+        assert!(policy.in_quiet_hours(&at(6, 23, 30)));
+    }
+
+    #[test]
+    fn disabled_days_dont_spill_over() {
+        let mut policy = policy(&[(0, "22:00", "07:00")]);
+        policy.quiet_hours[0].enabled = false;
+        assert!(!policy.in_quiet_hours(&at(0, 23, 0)));
+        assert!(!policy.in_quiet_hours(&at(1, 6, 0)));
+    }
+
+    #[test]
+    fn equal_start_and_end_cover_the_whole_day() {
+        let policy = policy(&[(2, "09:00", "09:00")]);
+        assert!(policy.in_quiet_hours(&at(2, 0, 0)));
+        assert!(policy.in_quiet_hours(&at(2, 9, 0)));
+        assert!(policy.in_quiet_hours(&at(2, 23, 59)));
+        // Only that day
+        assert!(!policy.in_quiet_hours(&at(1, 23, 59)));
+        assert!(!policy.in_quiet_hours(&at(3, 8, 0)));
+    }
+
+    #[test]
+    fn combines_a_spill_over_with_the_days_own_window() {
+        let policy = policy(&[(0, "22:00", "07:00"), (1, "12:00", "13:00")]);
+        assert!(policy.in_quiet_hours(&at(1, 6, 0)));
+        assert!(!policy.in_quiet_hours(&at(1, 9, 0)));
+        assert!(policy.in_quiet_hours(&at(1, 12, 30)));
+    }
+}
@@ -0,0 +1,572 @@
+//! User-defined notification rules. Rules are checked in order before the
+//! do-not-disturb policy and the first enabled one that matches decides what
+//! happens to a notification; with no match it's shown as usual.
+//!
+//! Stored in `settings.json` under `notificationRules`, e.g.
+//!
+//! ```json
+//! [{ "id": "meetings", "name": "Quiet meeting chats",
+//!    "when": { "chatTypes": ["meeting"] }, "action": "badgeOnly" },
+//!  { "id": "outage", "when": { "pattern": "(?i)\\b(outage|sev ?1)\\b" },
+//!    "action": "escalate" },
+//!  { "id": "boss", "when": { "senders": ["ceo@contoso.com"] },
+//!    "action": "sound", "sound": { "bundled": "chime" } }]
+//! ```
+
+use chrono::{DateTime, Datelike, Local, TimeZone};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+
+use super::policy::parse_time;
+use super::{MessageRef, Notice, NotificationTarget, Sound};
+use crate::settings;
+
+const RULES_KEY: &str = "notificationRules";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Rule {
+    pub id: String,
+    #[serde(default)]
+    pub name: String,
+    #[serde(default = "enabled_by_default")]
+    pub enabled: bool,
+    /// Every condition set here has to hold; an empty match catches all.
+    #[serde(default)]
+    pub when: RuleMatch,
+    pub action: RuleAction,
+    /// What [`RuleAction::Sound`] plays.
+    #[serde(default)]
+    pub sound: Option<Sound>,
+}
+
+fn enabled_by_default() -> bool {
+    true
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct RuleMatch {
+    /// Sender user IDs, email addresses or display names (case-insensitive).
+    pub senders: Vec<String>,
+    /// `oneOnOne`, `group`, `meeting` or `channel`.
+    pub chat_types: Vec<String>,
+    /// Chat IDs, channel IDs or `<teamId>:<channelId>` keys.
+    pub conversations: Vec<String>,
+    /// Case-insensitive substrings of the message; any of them.
+    pub keywords: Vec<String>,
+    /// Regular expression over the message text.
+    pub pattern: Option<String>,
+    /// Whether the message has to (or must not) @mention the user.
+    pub mentions_me: Option<bool>,
+    /// Weekdays the rule applies on, 0 = Monday; empty for every day. A
+    /// window past midnight belongs to the day it starts on.
+    pub days: Vec<u8>,
+    /// `HH:MM` local time window; one ending before it starts runs past
+    /// midnight.
+    pub start: Option<String>,
+    pub end: Option<String>,
+}
+
+/// What a matching rule does with the notification.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum RuleAction {
+    /// Show it as usual.
+    Notify,
+    /// Show it without a sound.
+    Silent,
+    /// Don't show it; it still counts towards the unread badge.
+    BadgeOnly,
+    /// Show it with the rule's sound instead of the conversation's.
+    Sound,
+    /// Show it straight away with the urgent style, ignoring mutes, quiet
+    /// hours and rate limits, and ask for the user's attention.
+    Escalate,
+}
+
+/// A message to try rules against without waiting for a real one.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct SampleMessage {
+    pub sender_id: String,
+    pub sender_name: String,
+    pub sender_email: Option<String>,
+    pub chat_type: String,
+    pub chat_id: String,
+    pub team_id: Option<String>,
+    pub channel_id: Option<String>,
+    pub text: String,
+    pub mentions_me: bool,
+    /// RFC 3339; now when missing.
+    pub time: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DryRun {
+    /// The rule that matched, if any.
+    pub rule_id: Option<String>,
+    pub action: RuleAction,
+}
+
+/// Rules with their patterns compiled.
+#[derive(Debug, Clone, Default)]
+pub struct RuleSet {
+    rules: Vec<Rule>,
+    patterns: Vec<Option<Regex>>,
+}
+
+impl RuleSet {
+    /// Check and compile `rules`, so a bad pattern or time is reported when
+    /// saving rather than silently never matching.
+    pub fn new(rules: Vec<Rule>) -> Result<Self, String> {
+        let mut patterns = Vec::with_capacity(rules.len());
+        for rule in &rules {
+            let label = if rule.name.is_empty() { &rule.id } else { &rule.name };
+            if rule.id.trim().is_empty() {
+                return Err("Every rule needs an id".to_string());
+            }
+            if rules.iter().filter(|r| r.id == rule.id).count() > 1 {
+                return Err(format!("Duplicate rule id {}", rule.id));
+            }
+            let when = &rule.when;
+            if let Some(day) = when.days.iter().find(|d| **d > 6) {
+                return Err(format!("Rule {}: invalid weekday {}", label, day));
+            }
+            match (&when.start, &when.end) {
+                (Some(start), Some(end)) => {
+                    parse_time(start).map_err(|e| format!("Rule {}: {}", label, e))?;
+                    parse_time(end).map_err(|e| format!("Rule {}: {}", label, e))?;
+                }
+                (None, None) => {}
+                _ => return Err(format!("Rule {}: a time window needs both start and end", label)),
+            }
+            match (&rule.action, &rule.sound) {
+                (RuleAction::Sound, None) => return Err(format!("Rule {}: the sound action needs a sound", label)),
+                // Files are only checked when played; one going missing shouldn't void every rule
+                (_, Some(sound @ Sound::Bundled(_))) => sound.validate().map_err(|e| format!("Rule {}: {}", label, e))?,
+                _ => {}
+            }
+            let pattern = when
+                .pattern
+                .as_deref()
+                .map(Regex::new)
+                .transpose()
+                .map_err(|e| format!("Rule {}: invalid pattern: {}", label, e))?;
+            patterns.push(pattern);
+        }
+        Ok(Self { rules, patterns })
+    }
+
+    /// The saved rules; invalid ones are dropped with a log line.
+    pub fn load(app: &AppHandle) -> Self {
+        let rules: Vec<Rule> = settings::load(app, RULES_KEY).unwrap_or_default();
+        Self::new(rules).unwrap_or_else(|e| {
+            eprintln!("[notifications] Ignoring saved rules: {}", e);
+            Self::default()
+        })
+    }
+
+    pub fn save(&self, app: &AppHandle) -> Result<(), String> {
+        settings::save(app, RULES_KEY, &self.rules)
+    }
+
+    pub fn rules(&self) -> &[Rule] {
+        &self.rules
+    }
+
+    /// The first enabled rule matching `notice` at `now`.
+    pub fn evaluate<Tz: TimeZone>(&self, notice: &Notice, now: &DateTime<Tz>) -> Option<&Rule> {
+        self.rules
+            .iter()
+            .zip(&self.patterns)
+            .find(|(rule, pattern)| rule.enabled && rule.when.matches(notice, pattern.as_ref(), now))
+            .map(|(rule, _)| rule)
+    }
+
+    pub fn dry_run(&self, sample: SampleMessage) -> Result<DryRun, String> {
+        let now = match &sample.time {
+            Some(time) => DateTime::parse_from_rfc3339(time)
+                .map_err(|e| format!("Invalid time {}: {}", time, e))?
+                .with_timezone(&Local),
+            None => Local::now(),
+        };
+        let notice = sample.into_notice();
+        Ok(match self.evaluate(&notice, &now) {
+            Some(rule) => DryRun {
+                rule_id: Some(rule.id.clone()),
+                action: rule.action,
+            },
+            None => DryRun {
+                rule_id: None,
+                action: RuleAction::Notify,
+            },
+        })
+    }
+}
+
+impl RuleMatch {
+    fn matches<Tz: TimeZone>(&self, notice: &Notice, pattern: Option<&Regex>, now: &DateTime<Tz>) -> bool {
+        let message = notice.message.as_ref();
+        let text = message.map_or(notice.body.as_str(), |m| m.text.as_str());
+
+        if !self.senders.is_empty() {
+            let sender = |s: &String| {
+                s.eq_ignore_ascii_case(&notice.title)
+                    || message.is_some_and(|m| {
+                        s.eq_ignore_ascii_case(&m.sender_id)
+                            || m.sender_email.as_deref().is_some_and(|e| s.eq_ignore_ascii_case(e))
+                    })
+            };
+            if !self.senders.iter().any(sender) {
+                return false;
+            }
+        }
+        if !self.chat_types.is_empty() {
+            let chat_type = message.map_or("", |m| m.chat_type.as_str());
+            if !self.chat_types.iter().any(|t| t.eq_ignore_ascii_case(chat_type)) {
+                return false;
+            }
+        }
+        if !self.conversations.is_empty() && !self.conversations.iter().any(|c| is_conversation(&notice.target, c)) {
+            return false;
+        }
+        if !self.keywords.is_empty() {
+            let text = text.to_lowercase();
+            if !self.keywords.iter().any(|k| text.contains(&k.to_lowercase())) {
+                return false;
+            }
+        }
+        if pattern.is_some_and(|p| !p.is_match(text)) {
+            return false;
+        }
+        if let Some(mentions_me) = self.mentions_me {
+            if message.is_some_and(|m| m.mentions_me) != mentions_me {
+                return false;
+            }
+        }
+        let today = now.weekday().num_days_from_monday() as u8;
+        let yesterday = (today + 6) % 7;
+        let on = |day: u8| self.days.is_empty() || self.days.contains(&day);
+        let (Some(start), Some(end)) = (&self.start, &self.end) else {
+            return on(today);
+        };
+        let (Ok(start), Ok(end)) = (parse_time(start), parse_time(end)) else {
+            return false;
+        };
+        let time = now.time();
+        if start <= end {
+            on(today) && time >= start && time < end
+        } else {
+            // Yesterday's overnight window spilling into this morning
+            (on(today) && time >= start) || (on(yesterday) && time < end)
+        }
+    }
+}
+
+fn is_conversation(target: &NotificationTarget, id: &str) -> bool {
+    match target {
+        NotificationTarget::Chat(chat_id) => chat_id == id,
+        NotificationTarget::Channel { channel_id, .. } => channel_id == id || target.key() == id,
+    }
+}
+
+impl SampleMessage {
+    fn into_notice(self) -> Notice {
+        Notice {
+            target: NotificationTarget::new(&self.chat_id, self.team_id.as_deref(), self.channel_id.as_deref()),
+            title: self.sender_name,
+            body: self.text.clone(),
+            message: Some(MessageRef {
+                sender_id: self.sender_id,
+                sender_email: self.sender_email,
+                chat_type: self.chat_type,
+                text: self.text,
+                mentions_me: self.mentions_me,
+                ..Default::default()
+            }),
+            sound: Sound::default(),
+            urgent: false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::FixedOffset;
+
+    use super::*;
+
+    fn rule(id: &str, when: RuleMatch, action: RuleAction) -> Rule {
+        Rule {
+            id: id.to_string(),
+            name: String::new(),
+            enabled: true,
+            when,
+            action,
+            sound: None,
+        }
+    }
+
+    fn strings(values: &[&str]) -> Vec<String> {
+        values.iter().map(|v| v.to_string()).collect()
+    }
+
+    fn sample() -> SampleMessage {
+        SampleMessage {
+            sender_id: "u-alice".to_string(),
+            sender_name: "Alice Smith".to_string(),
+            sender_email: Some("alice@contoso.com".to_string()),
+            chat_type: "group".to_string(),
+            chat_id: "19:chat".to_string(),
+            text: "The build is broken again".to_string(),
+            ..Default::default()
+        }
+    }
+
+    /// 2026-10-19 is a Monday.
+    fn at(day: u32, hour: u32, minute: u32) -> DateTime<FixedOffset> {
+        FixedOffset::west_opt(5 * 3600)
+            .unwrap()
+            .with_ymd_and_hms(2026, 10, 19 + day, hour, minute, 0)
+            .unwrap()
+    }
+
+    fn matches(when: RuleMatch, sample: SampleMessage) -> bool {
+        matches_at(when, sample, at(0, 12, 0))
+    }
+
+    fn matches_at(when: RuleMatch, sample: SampleMessage, now: DateTime<FixedOffset>) -> bool {
+        let pattern = when.pattern.as_deref().map(|p| Regex::new(p).unwrap());
+        when.matches(&sample.into_notice(), pattern.as_ref(), &now)
+    }
+
+    #[test]
+    fn accepts_valid_rules() {
+        let mut chime = rule("boss", RuleMatch::default(), RuleAction::Sound);
+        chime.sound = Some(Sound::Bundled("chime".to_string()));
+        let window = RuleMatch {
+            days: vec![0, 6],
+            start: Some("22:00".to_string()),
+            end: Some("07:00".to_string()),
+            pattern: Some("(?i)outage".to_string()),
+            ..Default::default()
+        };
+        let rules = RuleSet::new(vec![chime, rule("night", window, RuleAction::Silent)]).unwrap();
+        assert_eq!(rules.rules().len(), 2);
+    }
+
+    #[test]
+    fn rejects_invalid_rules() {
+        let invalid = |rules: Vec<Rule>| RuleSet::new(rules).unwrap_err();
+
+        assert!(invalid(vec![rule(" ", RuleMatch::default(), RuleAction::Notify)]).contains("needs an id"));
+        let twice = rule("a", RuleMatch::default(), RuleAction::Notify);
+        assert!(invalid(vec![twice.clone(), twice]).contains("Duplicate rule id"));
+
+        let weekday = RuleMatch {
+            days: vec![7],
+            ..Default::default()
+        };
+        assert!(invalid(vec![rule("a", weekday, RuleAction::Notify)]).contains("invalid weekday"));
+
+        let half_window = RuleMatch {
+            start: Some("09:00".to_string()),
+            ..Default::default()
+        };
+        assert!(invalid(vec![rule("a", half_window, RuleAction::Notify)]).contains("needs both start and end"));
+
+        let bad_time = RuleMatch {
+            start: Some("25:00".to_string()),
+            end: Some("09:00".to_string()),
+            ..Default::default()
+        };
+        assert!(invalid(vec![rule("a", bad_time, RuleAction::Notify)]).contains("Invalid time"));
+
+        let bad_pattern = RuleMatch {
+            pattern: Some("(unclosed".to_string()),
+            ..Default::default()
+        };
+        assert!(invalid(vec![rule("a", bad_pattern, RuleAction::Notify)]).contains("invalid pattern"));
+
+        assert!(invalid(vec![rule("a", RuleMatch::default(), RuleAction::Sound)]).contains("needs a sound"));
+        let mut unknown = rule("a", RuleMatch::default(), RuleAction::Sound);
+        unknown.sound = Some(Sound::Bundled("klaxon".to_string()));
+        assert!(invalid(vec![unknown]).contains("Unknown sound"));
+    }
+
+    #[test]
+    fn names_the_rule_in_errors() {
+        let mut named = rule(
+            "a",
+            RuleMatch {
+                days: vec![9],
+                ..Default::default()
+            },
+            RuleAction::Notify,
+        );
+        named.name = "Weekends".to_string();
+        assert_eq!(RuleSet::new(vec![named]).unwrap_err(), "Rule Weekends: invalid weekday 9");
+    }
+
+    #[test]
+    fn empty_match_catches_everything() {
+        assert!(matches(RuleMatch::default(), sample()));
+    }
+
+    #[test]
+    fn matches_senders_by_id_email_or_name() {
+        for sender in ["u-alice", "ALICE@contoso.com", "alice smith"] {
+            let when = RuleMatch {
+                senders: strings(&["someone@else.com", sender]),
+                ..Default::default()
+            };
+            assert!(matches(when, sample()), "{}", sender);
+        }
+        let when = RuleMatch {
+            senders: strings(&["bob@contoso.com"]),
+            ..Default::default()
+        };
+        assert!(!matches(when, sample()));
+    }
+
+    #[test]
+    fn matches_chat_types() {
+        let when = |types: &[&str]| RuleMatch {
+            chat_types: strings(types),
+            ..Default::default()
+        };
+        assert!(matches(when(&["oneOnOne", "group"]), sample()));
+        assert!(!matches(when(&["meeting"]), sample()));
+    }
+
+    #[test]
+    fn matches_chats_and_channels() {
+        let when = |ids: &[&str]| RuleMatch {
+            conversations: strings(ids),
+            ..Default::default()
+        };
+        assert!(matches(when(&["19:chat"]), sample()));
+        assert!(!matches(when(&["19:other"]), sample()));
+
+        let channel = SampleMessage {
+            team_id: Some("team".to_string()),
+            channel_id: Some("19:channel".to_string()),
+            ..sample()
+        };
+        assert!(matches(when(&["19:channel"]), channel.clone()));
+        assert!(matches(when(&["team:19:channel"]), channel.clone()));
+        assert!(!matches(when(&["19:chat"]), channel));
+    }
+
+    #[test]
+    fn matches_keywords_and_patterns() {
+        let keywords = |words: &[&str]| RuleMatch {
+            keywords: strings(words),
+            ..Default::default()
+        };
+        assert!(matches(keywords(&["deploy", "BUILD"]), sample()));
+        assert!(!matches(keywords(&["deploy"]), sample()));
+
+        let pattern = |pattern: &str| RuleMatch {
+            pattern: Some(pattern.to_string()),
+            ..Default::default()
+        };
+        assert!(matches(pattern(r"\bbroken\b"), sample()));
+        assert!(!matches(pattern(r"^broken"), sample()));
+    }
+
+    #[test]
+    fn matches_mentions() {
+        let when = |mentions_me| RuleMatch {
+            mentions_me: Some(mentions_me),
+            ..Default::default()
+        };
+        let mentioned = SampleMessage {
+            mentions_me: true,
+            ..sample()
+        };
+        assert!(matches(when(true), mentioned.clone()));
+        assert!(!matches(when(false), mentioned));
+        assert!(!matches(when(true), sample()));
+        assert!(matches(when(false), sample()));
+    }
+
+    #[test]
+    fn matches_days_and_time_windows() {
+        let weekend = RuleMatch {
+            days: vec![5, 6],
+            ..Default::default()
+        };
+        assert!(!matches_at(weekend.clone(), sample(), at(0, 12, 0)));
+        assert!(matches_at(weekend, sample(), at(5, 12, 0)));
+
+        let lunch = RuleMatch {
+            start: Some("12:00".to_string()),
+            end: Some("13:00".to_string()),
+            ..Default::default()
+        };
+        assert!(matches_at(lunch.clone(), sample(), at(0, 12, 30)));
+        assert!(!matches_at(lunch, sample(), at(0, 13, 0)));
+
+        let night = RuleMatch {
+            start: Some("22:00".to_string()),
+            end: Some("07:00".to_string()),
+            ..Default::default()
+        };
+        assert!(matches_at(night.clone(), sample(), at(0, 23, 0)));
+        assert!(matches_at(night.clone(), sample(), at(1, 6, 59)));
+        assert!(!matches_at(night, sample(), at(1, 7, 0)));
+    }
+
+    #[test]
+    fn overnight_windows_belong_to_the_day_they_start() {
+        // Friday night into Saturday morning
+        let friday_night = RuleMatch {
+            days: vec![4],
+            start: Some("22:00".to_string()),
+            end: Some("07:00".to_string()),
+            ..Default::default()
+        };
+        assert!(matches_at(friday_night.clone(), sample(), at(4, 23, 0)));
+        assert!(matches_at(friday_night.clone(), sample(), at(5, 6, 0)));
+        assert!(!matches_at(friday_night.clone(), sample(), at(5, 23, 0)));
+        // Thursday's window would have ended before Friday morning
+        assert!(!matches_at(friday_night.clone(), sample(), at(4, 6, 0)));
+
+        // Sunday night spills into Monday of the next week
+        let sunday_night = RuleMatch {
+            days: vec![6],
+            ..friday_night
+        };
+        assert!(matches_at(sunday_night.clone(), sample(), at(0, 6, 0)));
+        assert!(!matches_at(sunday_night, sample(), at(0, 23, 0)));
+    }
+
+    #[test]
+    fn first_enabled_match_wins() {
+        let mut disabled = rule("disabled", RuleMatch::default(), RuleAction::Escalate);
+        disabled.enabled = false;
+        let meetings = RuleMatch {
+            chat_types: strings(&["meeting"]),
+            ..Default::default()
+        };
+        let rules = RuleSet::new(vec![
+            disabled,
+            rule("meetings", meetings, RuleAction::BadgeOnly),
+            rule("rest", RuleMatch::default(), RuleAction::Silent),
+            rule("never", RuleMatch::default(), RuleAction::Notify),
+        ])
+        .unwrap();
+        let notice = sample().into_notice();
+        assert_eq!(rules.evaluate(&notice, &at(0, 12, 0)).map(|r| r.id.as_str()), Some("rest"));
+
+        let run = rules.dry_run(SampleMessage {
+            chat_type: "meeting".to_string(),
+            ..sample()
+        });
+        assert_eq!(run.unwrap().rule_id.as_deref(), Some("meetings"));
+    }
+}
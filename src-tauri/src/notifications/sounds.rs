@@ -0,0 +1,164 @@
+//! Notification sounds: the platform default, silence, one of the sounds
+//! bundled with the app, or an audio file of the user's. Anything but the
+//! default is played here rather than by the OS, so it's heard even when
+//! system notification sounds are turned off, and the banner itself stays
+//! quiet.
+//!
+//! Per-conversation choices live in `settings.json` under
+//! `notificationSounds`; rules can override them.
+
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufReader, Cursor};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, SyncSender, TrySendError};
+use std::sync::LazyLock;
+use std::time::Duration;
+
+use rodio::{Decoder, OutputStreamBuilder, Sink, Source};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use tauri::AppHandle;
+
+use crate::settings;
+
+const SOUNDS_KEY: &str = "notificationSounds";
+/// Longer files are cut off; a notification sound isn't a song.
+const MAX_CLIP_LENGTH: Duration = Duration::from_secs(5);
+/// Clips waiting behind the one playing; a burst beyond this is dropped
+/// rather than played out long after its notifications.
+const MAX_QUEUED_CLIPS: usize = 2;
+
+/// Sounds shipped with the app, by name.
+pub const BUNDLED: [(&str, &[u8]); 3] = [
+    ("chime", include_bytes!("../../sounds/chime.wav")),
+    ("ping", include_bytes!("../../sounds/ping.wav")),
+    ("pop", include_bytes!("../../sounds/pop.wav")),
+];
+
+/// Serialized as `"default"`, `"silent"`, `{ "bundled": "chime" }` or
+/// `{ "file": "/path/to/sound.mp3" }`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Sound {
+    /// Whatever the platform plays for notifications.
+    #[default]
+    Default,
+    Silent,
+    /// One of [`BUNDLED`].
+    Bundled(String),
+    /// WAV, MP3, Ogg Vorbis or FLAC.
+    File(String),
+}
+
+impl Sound {
+    /// Whether the platform's own notification sound should be kept.
+    pub fn uses_platform(&self) -> bool {
+        *self == Self::Default
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        match self {
+            Self::Default | Self::Silent => Ok(()),
+            Self::Bundled(name) => bundled(name).map(|_| ()),
+            Self::File(path) if Path::new(path).is_file() => Ok(()),
+            Self::File(path) => Err(format!("Sound file not found: {}", path)),
+        }
+    }
+}
+
+/// The sound chosen for `key` (chat ID or `<teamId>:<channelId>`).
+pub fn chat_sound(app: &AppHandle, key: &str) -> Sound {
+    stored(app)
+        .remove(key)
+        .and_then(|sound| serde_json::from_value(sound).ok())
+        .unwrap_or_default()
+}
+
+/// Every conversation with a sound of its own.
+pub fn chat_sounds(app: &AppHandle) -> HashMap<String, Sound> {
+    stored(app)
+        .into_iter()
+        .filter_map(|(key, sound)| Some((key, serde_json::from_value(sound).ok()?)))
+        .collect()
+}
+
+/// Choose the sound for `key`; the default clears the choice.
+pub fn set_chat_sound(app: &AppHandle, key: &str, sound: Sound) -> Result<(), String> {
+    sound.validate()?;
+    let mut sounds = stored(app);
+    if sound == Sound::Default {
+        sounds.remove(key);
+    } else {
+        sounds.insert(key.to_string(), serde_json::json!(sound));
+    }
+    settings::save(app, SOUNDS_KEY, &sounds)
+}
+
+fn stored(app: &AppHandle) -> Map<String, Value> {
+    settings::load(app, SOUNDS_KEY).unwrap_or_default()
+}
+
+enum Clip {
+    Bundled(&'static [u8]),
+    File(PathBuf),
+}
+
+/// Sounds are played one after another on their own thread; the output
+/// stream isn't `Send` on every platform.
+static PLAYER: LazyLock<SyncSender<Clip>> = LazyLock::new(|| {
+    let (tx, rx) = mpsc::sync_channel(MAX_QUEUED_CLIPS);
+    std::thread::spawn(move || run_player(rx));
+    tx
+});
+
+/// Play `sound` unless the platform takes care of it (or it's silent).
+pub fn play(sound: &Sound) -> Result<(), String> {
+    let clip = match sound {
+        Sound::Default | Sound::Silent => return Ok(()),
+        Sound::Bundled(name) => Clip::Bundled(bundled(name)?),
+        Sound::File(path) => Clip::File(PathBuf::from(path)),
+    };
+    match PLAYER.try_send(clip) {
+        Ok(()) | Err(TrySendError::Full(_)) => Ok(()),
+        Err(TrySendError::Disconnected(_)) => Err("Sound player stopped".to_string()),
+    }
+}
+
+fn bundled(name: &str) -> Result<&'static [u8], String> {
+    BUNDLED
+        .iter()
+        .find(|(n, _)| *n == name)
+        .map(|(_, data)| *data)
+        .ok_or_else(|| format!("Unknown sound: {}", name))
+}
+
+fn run_player(clips: Receiver<Clip>) {
+    for clip in clips {
+        if let Err(e) = play_clip(clip) {
+            eprintln!("[notifications] Failed to play sound: {}", e);
+        }
+    }
+}
+
+/// Open the default output for each clip, so a device switched since the
+/// last one (e.g. headphones) is picked up, and it isn't held open between
+/// notifications.
+fn play_clip(clip: Clip) -> Result<(), String> {
+    let mut stream = OutputStreamBuilder::open_default_stream().map_err(|e| e.to_string())?;
+    stream.log_on_drop(false);
+    let sink = Sink::connect_new(stream.mixer());
+    match clip {
+        Clip::Bundled(data) => {
+            let source = Decoder::new(Cursor::new(data)).map_err(|e| e.to_string())?;
+            sink.append(source.take_duration(MAX_CLIP_LENGTH));
+        }
+        Clip::File(path) => {
+            let file = File::open(&path).map_err(|e| format!("{}: {}", path.display(), e))?;
+            let source = Decoder::new(BufReader::new(file)).map_err(|e| format!("{}: {}", path.display(), e))?;
+            sink.append(source.take_duration(MAX_CLIP_LENGTH));
+        }
+    }
+    sink.sleep_until_end();
+    Ok(())
+}
@@ -0,0 +1,470 @@
+//! Persistent queue of outgoing messages.
+//!
+//! The webview hands over a fully built Graph payload (text, mentions,
+//! hosted images) and gets back a queued item immediately. A single worker
+//! posts items in order per conversation, retrying with exponential backoff
+//! while the network is down or Graph is throttling, and parks items that
+//! Graph rejects (or that may have gone through without Graph's reply
+//! arriving) as `failed` until the user resends or discards them; later
+//! messages to that conversation wait behind it. Items are written through
+//! to the encrypted cache so they survive restarts.
+//!
+//! Events: `outbox:queued`, `outbox:updated` (state changes and retries),
+//! `outbox:sent` (with the created message) and `outbox:removed`.
+
+use std::collections::HashSet;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use chrono::{DateTime, SecondsFormat, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tauri::{AppHandle, Emitter};
+use tokio::sync::Notify;
+
+use crate::cache::Cache;
+use crate::graph::{GraphClient, GraphError};
+
+const BASE_BACKOFF: Duration = Duration::from_secs(2);
+const MAX_BACKOFF: Duration = Duration::from_secs(5 * 60);
+/// Server-side failures (5xx, throttling) give up after this many attempts.
+/// Connectivity and sign-in problems retry indefinitely.
+const MAX_SERVER_ATTEMPTS: u32 = 8;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OutboxState {
+    Pending,
+    Sending,
+    Failed,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OutboxItem {
+    pub id: String,
+    /// Chat ID or `<teamId>:<channelId>`; items of one conversation go out in order.
+    pub conversation: String,
+    /// Graph path the payload is POSTed to.
+    pub path: String,
+    pub payload: Value,
+    pub created_at: String,
+    pub state: OutboxState,
+    pub attempts: u32,
+    pub next_attempt_at: Option<String>,
+    pub last_error: Option<String>,
+}
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SentEvent<'a> {
+    item: &'a OutboxItem,
+    message: &'a Value,
+}
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct RemovedEvent<'a> {
+    id: &'a str,
+    conversation: &'a str,
+}
+
+#[derive(Clone)]
+pub struct Outbox {
+    inner: Arc<Inner>,
+}
+
+struct Inner {
+    app: AppHandle,
+    graph: GraphClient,
+    cache: Cache,
+    items: Mutex<Vec<OutboxItem>>,
+    wake: Notify,
+}
+
+impl Outbox {
+    /// Load queued items left over from the last run. Anything that was
+    /// mid-send when the app quit is retried.
+    pub fn new(app: AppHandle, graph: GraphClient, cache: Cache) -> Self {
+        let mut items: Vec<OutboxItem> = cache
+            .outbox_items()
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(|v| serde_json::from_value(v).ok())
+            .collect();
+        for item in &mut items {
+            if item.state == OutboxState::Sending {
+                item.state = OutboxState::Pending;
+                item.next_attempt_at = None;
+            }
+        }
+        Self {
+            inner: Arc::new(Inner {
+                app,
+                graph,
+                cache,
+                items: Mutex::new(items),
+                wake: Notify::new(),
+            }),
+        }
+    }
+
+    /// Spawn the send worker. Call once.
+    pub fn start(&self) {
+        let inner = self.inner.clone();
+        tauri::async_runtime::spawn(async move {
+            inner.run().await;
+        });
+    }
+
+    pub fn enqueue(&self, conversation: String, path: String, payload: Value) -> Result<OutboxItem, String> {
+        if !is_message_path(&path) {
+            return Err(format!("Not a message endpoint: {}", path));
+        }
+        let item = OutboxItem {
+            id: format!("outbox-{}", hex(&rand::random::<[u8; 12]>())),
+            conversation,
+            path,
+            payload,
+            created_at: now(),
+            state: OutboxState::Pending,
+            attempts: 0,
+            next_attempt_at: None,
+            last_error: None,
+        };
+        self.inner.items.lock().unwrap().push(item.clone());
+        self.inner.persist(&item);
+        let _ = self.inner.app.emit("outbox:queued", &item);
+        self.inner.wake.notify_one();
+        Ok(item)
+    }
+
+    pub fn items(&self) -> Vec<OutboxItem> {
+        self.inner.items.lock().unwrap().clone()
+    }
+
+    /// Retry a failed (or backing-off) item straight away.
+    pub fn resend(&self, id: &str) -> Result<OutboxItem, String> {
+        let item = self
+            .inner
+            .update(id, |item| {
+                item.state = OutboxState::Pending;
+                item.attempts = 0;
+                item.next_attempt_at = None;
+                item.last_error = None;
+            })
+            .ok_or_else(|| format!("No queued message {}", id))?;
+        self.inner.wake.notify_one();
+        Ok(item)
+    }
+
+    pub fn discard(&self, id: &str) -> Result<(), String> {
+        let removed = {
+            let mut items = self.inner.items.lock().unwrap();
+            let index = items
+                .iter()
+                .position(|i| i.id == id)
+                .ok_or_else(|| format!("No queued message {}", id))?;
+            if items[index].state == OutboxState::Sending {
+                return Err("Message is being sent".to_string());
+            }
+            items.remove(index)
+        };
+        self.inner.remove(&removed);
+        Ok(())
+    }
+
+    /// Retry everything that's waiting now, e.g. when connectivity returns.
+    pub fn flush(&self) {
+        {
+            let mut items = self.inner.items.lock().unwrap();
+            for item in items.iter_mut().filter(|i| i.state == OutboxState::Pending) {
+                item.next_attempt_at = None;
+            }
+        }
+        self.inner.wake.notify_one();
+    }
+}
+
+impl Inner {
+    async fn run(&self) {
+        loop {
+            while let Some(item) = self.next_due() {
+                self.deliver(item).await;
+            }
+            match self.next_wake_in() {
+                Some(delay) => {
+                    tokio::select! {
+                        _ = tokio::time::sleep(delay) => {}
+                        _ = self.wake.notified() => {}
+                    }
+                }
+                None => self.wake.notified().await,
+            }
+        }
+    }
+
+    /// The first conversation head that is pending and due. Marks it as
+    /// sending.
+    fn next_due(&self) -> Option<OutboxItem> {
+        let now = Utc::now();
+        let mut items = self.items.lock().unwrap();
+        let (index, _) = heads(&items).find(|(_, item)| item.state == OutboxState::Pending && is_due(item, now))?;
+        let item = &mut items[index];
+        item.state = OutboxState::Sending;
+        item.attempts += 1;
+        let item = item.clone();
+        drop(items);
+        self.persist(&item);
+        let _ = self.app.emit("outbox:updated", &item);
+        Some(item)
+    }
+
+    fn next_wake_in(&self) -> Option<Duration> {
+        let now = Utc::now();
+        let items = self.items.lock().unwrap();
+        heads(&items)
+            .map(|(_, item)| item)
+            .filter(|i| i.state == OutboxState::Pending)
+            .filter_map(|i| i.next_attempt_at.as_deref().and_then(parse_time))
+            .min()
+            .map(|at| (at - now).to_std().unwrap_or_default())
+    }
+
+    async fn deliver(&self, item: OutboxItem) {
+        match self.graph.post::<Value>(&item.path, &item.payload).await {
+            Ok(message) => {
+                self.items.lock().unwrap().retain(|i| i.id != item.id);
+                let _ = self.cache.remove_outbox_item(&item.id);
+                let _ = self.app.emit("outbox:sent", SentEvent { item: &item, message: &message });
+            }
+            Err(e) => {
+                let retry = is_retryable(&e, item.attempts);
+                self.update(&item.id, |i| {
+                    i.last_error = Some(if was_accepted(&e) {
+                        format!("The message may have been sent: {}", e.message)
+                    } else {
+                        e.message.clone()
+                    });
+                    if retry {
+                        i.state = OutboxState::Pending;
+                        i.next_attempt_at = Some(after(backoff(i.attempts)));
+                    } else {
+                        i.state = OutboxState::Failed;
+                        i.next_attempt_at = None;
+                    }
+                });
+                eprintln!("[outbox] Send to {} failed ({}): {}", item.conversation, if retry { "retrying" } else { "giving up" }, e);
+            }
+        }
+    }
+
+    /// Apply `f` to a queued item, persist it and announce the change.
+    fn update(&self, id: &str, f: impl FnOnce(&mut OutboxItem)) -> Option<OutboxItem> {
+        let item = {
+            let mut items = self.items.lock().unwrap();
+            let item = items.iter_mut().find(|i| i.id == id)?;
+            f(item);
+            item.clone()
+        };
+        self.persist(&item);
+        let _ = self.app.emit("outbox:updated", &item);
+        Some(item)
+    }
+
+    fn remove(&self, item: &OutboxItem) {
+        let _ = self.cache.remove_outbox_item(&item.id);
+        let _ = self.app.emit(
+            "outbox:removed",
+            RemovedEvent {
+                id: &item.id,
+                conversation: &item.conversation,
+            },
+        );
+    }
+
+    fn persist(&self, item: &OutboxItem) {
+        if !self.cache.is_available() {
+            return;
+        }
+        if let Ok(value) = serde_json::to_value(item) {
+            if let Err(e) = self.cache.put_outbox_item(&item.id, &item.created_at, &value) {
+                eprintln!("[outbox] {}", e);
+            }
+        }
+    }
+}
+
+/// No connection or no token: wait it out. Throttling and server errors:
+/// retry a bounded number of times. Anything else (bad request, forbidden,
+/// chat gone, or a reply that was lost after Graph accepted the message)
+/// won't get better on its own, or would post the message twice.
+fn is_retryable(error: &GraphError, attempts: u32) -> bool {
+    match error.status {
+        None => error.code == "network",
+        Some(401) if error.code == "auth_expired" => true,
+        Some(408 | 429) | Some(500..=599) => attempts < MAX_SERVER_ATTEMPTS,
+        Some(_) => false,
+    }
+}
+
+/// Graph answered with success, but the created message couldn't be read back.
+fn was_accepted(error: &GraphError) -> bool {
+    error.status.is_some_and(|s| (200..300).contains(&s))
+}
+
+fn backoff(attempts: u32) -> Duration {
+    BASE_BACKOFF
+        .saturating_mul(2u32.saturating_pow(attempts.saturating_sub(1)))
+        .min(MAX_BACKOFF)
+}
+
+/// Only chat/channel message and reply endpoints may be queued.
+fn is_message_path(path: &str) -> bool {
+    let path = path.split('?').next().unwrap_or(path);
+    (path.starts_with("/me/chats/") || path.starts_with("/chats/") || path.starts_with("/teams/"))
+        && (path.ends_with("/messages") || path.ends_with("/replies"))
+}
+
+/// The oldest queued item of each conversation, with its index. Only these
+/// may be sent; everything else waits its turn, whatever state the head is in.
+fn heads(items: &[OutboxItem]) -> impl Iterator<Item = (usize, &OutboxItem)> {
+    let mut seen = HashSet::new();
+    items
+        .iter()
+        .enumerate()
+        .filter(move |(_, item)| seen.insert(item.conversation.as_str()))
+}
+
+fn is_due(item: &OutboxItem, now: DateTime<Utc>) -> bool {
+    item.next_attempt_at
+        .as_deref()
+        .and_then(parse_time)
+        .is_none_or(|at| at <= now)
+}
+
+fn parse_time(timestamp: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(timestamp).ok().map(|t| t.with_timezone(&Utc))
+}
+
+fn now() -> String {
+    Utc::now().to_rfc3339_opts(SecondsFormat::Millis, true)
+}
+
+fn after(delay: Duration) -> String {
+    let delay = chrono::Duration::from_std(delay).unwrap_or_default();
+    (Utc::now() + delay).to_rfc3339_opts(SecondsFormat::Millis, true)
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item(id: &str, conversation: &str, state: OutboxState) -> OutboxItem {
+        OutboxItem {
+            id: id.to_string(),
+            conversation: conversation.to_string(),
+            path: format!("/chats/{}/messages", conversation),
+            payload: Value::Null,
+            created_at: now(),
+            state,
+            attempts: 0,
+            next_attempt_at: None,
+            last_error: None,
+        }
+    }
+
+    fn head_ids(items: &[OutboxItem]) -> Vec<&str> {
+        heads(items).map(|(_, item)| item.id.as_str()).collect()
+    }
+
+    #[test]
+    fn only_the_oldest_item_per_conversation_is_a_head() {
+        let items = [
+            item("a1", "a", OutboxState::Pending),
+            item("b1", "b", OutboxState::Pending),
+            item("a2", "a", OutboxState::Pending),
+            item("b2", "b", OutboxState::Pending),
+        ];
+        assert_eq!(head_ids(&items), ["a1", "b1"]);
+    }
+
+    #[test]
+    fn failed_and_sending_items_hold_their_conversation() {
+        let items = [
+            item("a1", "a", OutboxState::Failed),
+            item("a2", "a", OutboxState::Pending),
+            item("b1", "b", OutboxState::Sending),
+            item("b2", "b", OutboxState::Pending),
+            item("c1", "c", OutboxState::Pending),
+        ];
+        let sendable: Vec<_> = heads(&items)
+            .filter(|(_, item)| item.state == OutboxState::Pending)
+            .map(|(index, _)| index)
+            .collect();
+        assert_eq!(sendable, [4]);
+    }
+
+    fn error(status: Option<u16>, code: &str) -> GraphError {
+        GraphError {
+            status,
+            ..GraphError::new(code, "failed")
+        }
+    }
+
+    #[test]
+    fn unanswered_requests_retry_indefinitely() {
+        assert!(is_retryable(&error(None, "network"), 100));
+        assert!(is_retryable(&error(Some(401), "auth_expired"), 100));
+    }
+
+    #[test]
+    fn server_errors_retry_a_bounded_number_of_times() {
+        for status in [408, 429, 500, 503] {
+            assert!(is_retryable(&error(Some(status), "serverError"), 1));
+            assert!(is_retryable(&error(Some(status), "serverError"), MAX_SERVER_ATTEMPTS - 1));
+            assert!(!is_retryable(&error(Some(status), "serverError"), MAX_SERVER_ATTEMPTS));
+        }
+    }
+
+    #[test]
+    fn rejected_or_possibly_sent_messages_are_not_retried() {
+        assert!(!is_retryable(&error(Some(400), "BadRequest"), 1));
+        assert!(!is_retryable(&error(Some(401), "InvalidAuthenticationToken"), 1));
+        assert!(!is_retryable(&error(Some(404), "NotFound"), 1));
+        assert!(!is_retryable(&error(None, "invalid_url"), 1));
+
+        // Graph answered, but the reply was cut off or garbled
+        for e in [error(Some(201), "network"), error(Some(201), "invalid_response")] {
+            assert!(!is_retryable(&e, 1));
+            assert!(was_accepted(&e));
+        }
+        assert!(!was_accepted(&error(None, "network")));
+        assert!(!was_accepted(&error(Some(500), "serverError")));
+    }
+
+    #[test]
+    fn backoff_doubles_up_to_the_cap() {
+        assert_eq!(backoff(1), BASE_BACKOFF);
+        assert_eq!(backoff(2), BASE_BACKOFF * 2);
+        assert_eq!(backoff(4), BASE_BACKOFF * 8);
+        assert_eq!(backoff(8), BASE_BACKOFF * 128);
+        assert_eq!(backoff(9), MAX_BACKOFF);
+        assert_eq!(backoff(u32::MAX), MAX_BACKOFF);
+        // Not yet attempted
+        assert_eq!(backoff(0), BASE_BACKOFF);
+    }
+
+    #[test]
+    fn items_backing_off_are_not_due() {
+        let now = Utc::now();
+        let mut waiting = item("a1", "a", OutboxState::Pending);
+        waiting.next_attempt_at = Some(after(Duration::from_secs(60)));
+        assert!(!is_due(&waiting, now));
+        assert!(is_due(&item("a2", "a", OutboxState::Pending), now));
+    }
+}
@@ -0,0 +1,380 @@
+//! Presence of the signed-in user and of everyone on screen.
+//!
+//! The webview declares which user IDs it is showing; a single loop fetches
+//! them (plus the signed-in user) with `getPresencesByUserId` — one request
+//! per 650 IDs, several of those sharing a `$batch` — and caches each result
+//! for [`PRESENCE_TTL`]. Changes are emitted as `presence:updated`, and the
+//! user's own presence as `presence:mine`, which also drives the tray's
+//! status submenu.
+
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use reqwest::Method;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tauri::{AppHandle, Emitter};
+use tokio::sync::Notify;
+
+use crate::graph::types::Page;
+use crate::graph::{BatchRequest, GraphClient, GraphError};
+use crate::tray;
+
+/// How long a fetched presence is trusted; also the refresh interval.
+pub const PRESENCE_TTL: Duration = Duration::from_secs(30);
+/// `getPresencesByUserId` accepts at most 650 IDs per call.
+const IDS_PER_REQUEST: usize = 650;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Presence {
+    pub id: String,
+    pub availability: String,
+    pub activity: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status_message: Option<Value>,
+}
+
+/// The choices offered for "set my status".
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PreferredStatus {
+    Available,
+    Busy,
+    DoNotDisturb,
+    AppearAway,
+}
+
+impl PreferredStatus {
+    pub const ALL: [Self; 4] = [Self::Available, Self::Busy, Self::DoNotDisturb, Self::AppearAway];
+
+    /// Graph's `(availability, activity)` pair for this status.
+    fn graph_values(self) -> (&'static str, &'static str) {
+        match self {
+            Self::Available => ("Available", "Available"),
+            Self::Busy => ("Busy", "Busy"),
+            Self::DoNotDisturb => ("DoNotDisturb", "DoNotDisturb"),
+            Self::AppearAway => ("Away", "Away"),
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Available => "Available",
+            Self::Busy => "Busy",
+            Self::DoNotDisturb => "Do not disturb",
+            Self::AppearAway => "Appear away",
+        }
+    }
+
+    pub fn id(self) -> &'static str {
+        match self {
+            Self::Available => "Available",
+            Self::Busy => "Busy",
+            Self::DoNotDisturb => "DoNotDisturb",
+            Self::AppearAway => "AppearAway",
+        }
+    }
+
+    pub fn from_id(id: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|s| s.id() == id)
+    }
+
+    /// The status an availability shows up as in the tray submenu.
+    pub fn from_availability(availability: &str) -> Option<Self> {
+        match availability {
+            "Available" | "AvailableIdle" => Some(Self::Available),
+            "Busy" | "BusyIdle" => Some(Self::Busy),
+            "DoNotDisturb" => Some(Self::DoNotDisturb),
+            "Away" | "BeRightBack" => Some(Self::AppearAway),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct PresenceService {
+    inner: Arc<Inner>,
+}
+
+struct Inner {
+    app: AppHandle,
+    graph: GraphClient,
+    state: Mutex<State>,
+    wake: Notify,
+    generation: AtomicU64,
+    paused: AtomicBool,
+}
+
+#[derive(Default)]
+struct State {
+    user_id: Option<String>,
+    tracked: HashSet<String>,
+    cache: HashMap<String, Cached>,
+}
+
+struct Cached {
+    presence: Presence,
+    fetched: Instant,
+}
+
+impl PresenceService {
+    pub fn new(app: AppHandle, graph: GraphClient) -> Self {
+        Self {
+            inner: Arc::new(Inner {
+                app,
+                graph,
+                state: Mutex::new(State::default()),
+                wake: Notify::new(),
+                generation: AtomicU64::new(0),
+                paused: AtomicBool::new(false),
+            }),
+        }
+    }
+
+    /// Start refreshing tracked presences for the signed-in user. Restarting
+    /// keeps the tracked set and cache.
+    pub fn start(&self, user_id: String) {
+        let generation = self.inner.generation.fetch_add(1, Ordering::SeqCst) + 1;
+        {
+            let mut state = self.inner.state.lock().unwrap();
+            if state.user_id.as_deref() != Some(user_id.as_str()) {
+                state.cache.clear();
+            }
+            state.user_id = Some(user_id);
+        }
+        let inner = self.inner.clone();
+        tauri::async_runtime::spawn(async move {
+            inner.run(generation).await;
+        });
+    }
+
+    pub fn stop(&self) {
+        self.inner.generation.fetch_add(1, Ordering::SeqCst);
+        self.inner.state.lock().unwrap().user_id = None;
+        self.inner.wake.notify_waiters();
+    }
+
+    /// Suspend or resume refreshing (see `crate::connectivity`).
+    pub fn set_paused(&self, paused: bool) {
+        if self.inner.paused.swap(paused, Ordering::SeqCst) != paused && !paused {
+            self.inner.wake.notify_waiters();
+        }
+    }
+
+    /// Replace the set of users kept fresh. Newly added users are fetched right away.
+    pub fn track(&self, user_ids: Vec<String>) {
+        let added = {
+            let mut state = self.inner.state.lock().unwrap();
+            let tracked: HashSet<String> = user_ids.into_iter().collect();
+            let added = tracked.iter().any(|id| !state.cache.contains_key(id));
+            state.tracked = tracked;
+            added
+        };
+        if added {
+            self.inner.wake.notify_waiters();
+        }
+    }
+
+    /// Presences of `user_ids`, from the cache where fresh and fetched otherwise.
+    pub async fn get(&self, user_ids: Vec<String>) -> Result<Vec<Presence>, GraphError> {
+        let wanted: HashSet<String> = user_ids.iter().cloned().collect();
+        self.inner.refresh(user_ids).await?;
+        Ok(self.inner.cached(&wanted))
+    }
+
+    /// The signed-in user's presence as last fetched.
+    pub fn mine(&self) -> Option<Presence> {
+        self.inner.mine()
+    }
+
+    /// Set the user's preferred status, shown instead of the one Teams derives
+    /// from their activity until `expiration` (Graph's default when `None`).
+    pub async fn set_preferred(&self, status: PreferredStatus, expiration: Option<Duration>) -> Result<(), GraphError> {
+        let (availability, activity) = status.graph_values();
+        let mut body = json!({ "availability": availability, "activity": activity });
+        if let Some(expiration) = expiration {
+            body["expirationDuration"] = json!(iso_duration(expiration));
+        }
+        self.inner.post_mine("setUserPreferredPresence", body).await
+    }
+
+    /// Go back to the status Teams derives from the user's activity.
+    pub async fn clear_preferred(&self) -> Result<(), GraphError> {
+        self.inner.post_mine("clearUserPreferredPresence", json!({})).await
+    }
+
+    /// Set an application presence session (`setPresence`). `session_id` must
+    /// be this app's client ID.
+    pub async fn set_session(
+        &self,
+        session_id: String,
+        availability: String,
+        activity: String,
+        expiration: Option<Duration>,
+    ) -> Result<(), GraphError> {
+        let mut body = json!({
+            "sessionId": session_id,
+            "availability": availability,
+            "activity": activity,
+        });
+        if let Some(expiration) = expiration {
+            body["expirationDuration"] = json!(iso_duration(expiration));
+        }
+        self.inner.post_mine("setPresence", body).await
+    }
+
+    /// Set (or with an empty `message`, clear) the status message.
+    /// `expires_at` is an RFC 3339 timestamp.
+    pub async fn set_status_message(&self, message: String, expires_at: Option<String>) -> Result<(), GraphError> {
+        let mut status_message = json!({
+            "message": { "content": message, "contentType": "text" },
+        });
+        if let Some(expires_at) = expires_at {
+            let expiry = chrono::DateTime::parse_from_rfc3339(&expires_at)
+                .map_err(|e| GraphError::new("invalid_expiry", format!("Invalid expiry {}: {}", expires_at, e)))?
+                .with_timezone(&chrono::Utc);
+            status_message["expiryDateTime"] = json!({
+                "dateTime": expiry.format("%Y-%m-%dT%H:%M:%S").to_string(),
+                "timeZone": "UTC",
+            });
+        }
+        self.inner
+            .post_mine("setStatusMessage", json!({ "statusMessage": status_message }))
+            .await
+    }
+}
+
+impl Inner {
+    async fn run(self: Arc<Self>, generation: u64) {
+        while self.generation.load(Ordering::SeqCst) == generation {
+            let woken = self.wake.notified();
+            if !self.paused.load(Ordering::SeqCst) {
+                let ids: Vec<String> = {
+                    let state = self.state.lock().unwrap();
+                    state.tracked.iter().cloned().chain(state.user_id.clone()).collect()
+                };
+                if let Err(e) = self.refresh(ids).await {
+                    eprintln!("[presence] Refresh failed: {}", e);
+                }
+            }
+            tokio::select! {
+                _ = tokio::time::sleep(PRESENCE_TTL) => {}
+                _ = woken => {}
+            }
+        }
+    }
+
+    /// Fetch whichever of `user_ids` are missing or stale, then announce changes.
+    async fn refresh(&self, user_ids: Vec<String>) -> Result<(), GraphError> {
+        let stale: Vec<String> = {
+            let state = self.state.lock().unwrap();
+            let unique: HashSet<String> = user_ids.into_iter().collect();
+            unique
+                .into_iter()
+                .filter(|id| state.cache.get(id).is_none_or(|c| c.fetched.elapsed() >= PRESENCE_TTL))
+                .collect()
+        };
+        if stale.is_empty() {
+            return Ok(());
+        }
+
+        let fetched = self.fetch(&stale).await?;
+        let now = Instant::now();
+        let (changed, mine) = {
+            let mut state = self.state.lock().unwrap();
+            let mut changed = Vec::new();
+            for presence in fetched {
+                let previous = state.cache.insert(
+                    presence.id.clone(),
+                    Cached {
+                        presence: presence.clone(),
+                        fetched: now,
+                    },
+                );
+                if previous.is_none_or(|p| p.presence != presence) {
+                    changed.push(presence);
+                }
+            }
+            let mine = state
+                .user_id
+                .as_ref()
+                .and_then(|me| changed.iter().find(|p| &p.id == me))
+                .cloned();
+            (changed, mine)
+        };
+
+        if !changed.is_empty() {
+            let _ = self.app.emit("presence:updated", &changed);
+        }
+        if let Some(mine) = mine {
+            tray::update_presence(&self.app, PreferredStatus::from_availability(&mine.availability));
+            let _ = self.app.emit("presence:mine", &mine);
+        }
+        Ok(())
+    }
+
+    async fn fetch(&self, user_ids: &[String]) -> Result<Vec<Presence>, GraphError> {
+        const PATH: &str = "/communications/getPresencesByUserId";
+        let chunks: Vec<&[String]> = user_ids.chunks(IDS_PER_REQUEST).collect();
+        if let [only] = chunks.as_slice() {
+            let page: Page<Presence> = self.graph.post(PATH, &json!({ "ids": only })).await?;
+            return Ok(page.value);
+        }
+
+        let requests = chunks
+            .iter()
+            .enumerate()
+            .map(|(i, ids)| BatchRequest {
+                method: Method::POST.to_string(),
+                body: Some(json!({ "ids": ids })),
+                ..BatchRequest::get(i.to_string(), PATH)
+            })
+            .collect();
+        let mut presences = Vec::new();
+        for response in self.graph.batch(requests).await? {
+            let page: Page<Presence> = serde_json::from_value(response.into_result()?)
+                .map_err(|e| GraphError::new("invalid_response", format!("Unexpected presence response: {}", e)))?;
+            presences.extend(page.value);
+        }
+        Ok(presences)
+    }
+
+    fn cached(&self, user_ids: &HashSet<String>) -> Vec<Presence> {
+        let state = self.state.lock().unwrap();
+        user_ids
+            .iter()
+            .filter_map(|id| state.cache.get(id))
+            .map(|c| c.presence.clone())
+            .collect()
+    }
+
+    fn mine(&self) -> Option<Presence> {
+        let state = self.state.lock().unwrap();
+        let me = state.user_id.as_ref()?;
+        state.cache.get(me).map(|c| c.presence.clone())
+    }
+
+    /// POST to one of the signed-in user's presence actions, then refetch
+    /// their presence so the change shows up immediately.
+    async fn post_mine(&self, action: &str, body: Value) -> Result<(), GraphError> {
+        let me = self
+            .state
+            .lock()
+            .unwrap()
+            .user_id
+            .clone()
+            .ok_or_else(|| GraphError::new("not_started", "Presence service isn't running"))?;
+        let path = format!("/users/{}/presence/{}", urlencoding::encode(&me), action);
+        self.graph.send(Method::POST, &path, Some(&body)).await?;
+
+        self.state.lock().unwrap().cache.remove(&me);
+        self.refresh(vec![me]).await
+    }
+}
+
+/// ISO 8601 duration in minutes, as Graph expects for `expirationDuration`.
+fn iso_duration(duration: Duration) -> String {
+    format!("PT{}M", duration.as_secs().div_ceil(60).max(1))
+}
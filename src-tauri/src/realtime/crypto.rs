@@ -0,0 +1,110 @@
+use std::path::Path;
+
+use aes::cipher::{block_padding::Pkcs7, BlockDecryptMut, KeyIvInit};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use hmac::{Hmac, Mac};
+use rsa::pkcs1::DecodeRsaPrivateKey;
+use rsa::pkcs8::DecodePrivateKey;
+use rsa::{Oaep, RsaPrivateKey};
+use serde::Deserialize;
+use serde_json::Value;
+
+type Aes256CbcDec = cbc::Decryptor<aes::Aes256>;
+
+/// `encryptedContent` of a rich change notification.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EncryptedContent {
+    pub data: String,
+    pub data_signature: String,
+    pub data_key: String,
+    pub encryption_certificate_id: String,
+}
+
+/// Certificate sent to Graph with each subscription and the private key used
+/// to unwrap the per-notification symmetric key.
+pub struct EncryptionKeys {
+    /// Base64 DER of the X.509 certificate, as Graph expects it.
+    pub certificate: String,
+    pub certificate_id: String,
+    private_key: RsaPrivateKey,
+}
+
+impl EncryptionKeys {
+    /// Load a PEM certificate and its PKCS#8 or PKCS#1 PEM private key.
+    pub fn load(certificate_path: &Path, key_path: &Path, certificate_id: &str) -> Result<Self, String> {
+        let cert_pem = std::fs::read_to_string(certificate_path)
+            .map_err(|e| format!("Failed to read certificate: {}", e))?;
+        let key_pem = std::fs::read_to_string(key_path)
+            .map_err(|e| format!("Failed to read private key: {}", e))?;
+        Self::from_pem(&cert_pem, &key_pem, certificate_id)
+    }
+
+    pub fn from_pem(cert_pem: &str, key_pem: &str, certificate_id: &str) -> Result<Self, String> {
+        let certificate: String = cert_pem
+            .lines()
+            .skip_while(|l| !l.starts_with("-----BEGIN CERTIFICATE"))
+            .skip(1)
+            .take_while(|l| !l.starts_with("-----END CERTIFICATE"))
+            .map(str::trim)
+            .collect();
+        if certificate.is_empty() {
+            return Err("No PEM certificate found".to_string());
+        }
+
+        let private_key = RsaPrivateKey::from_pkcs8_pem(key_pem)
+            .or_else(|_| RsaPrivateKey::from_pkcs1_pem(key_pem))
+            .map_err(|e| format!("Failed to parse private key: {}", e))?;
+
+        Ok(Self {
+            certificate,
+            certificate_id: certificate_id.to_string(),
+            private_key,
+        })
+    }
+
+    /// Decrypt a rich notification's resource data.
+    ///
+    /// The symmetric key is RSA-OAEP (SHA-1) wrapped with our certificate; the
+    /// payload is AES-256-CBC with the key's first 16 bytes as IV, signed with
+    /// HMAC-SHA256 under the same key.
+    pub fn decrypt(&self, content: &EncryptedContent) -> Result<Value, String> {
+        if content.encryption_certificate_id != self.certificate_id {
+            return Err(format!(
+                "Notification encrypted for unknown certificate '{}'",
+                content.encryption_certificate_id
+            ));
+        }
+
+        let wrapped_key = decode(&content.data_key, "dataKey")?;
+        let key = self
+            .private_key
+            .decrypt(Oaep::new::<sha1::Sha1>(), &wrapped_key)
+            .map_err(|e| format!("Failed to unwrap data key: {}", e))?;
+        if key.len() != 32 {
+            return Err("Unexpected data key length".to_string());
+        }
+
+        let data = decode(&content.data, "data")?;
+        let signature = decode(&content.data_signature, "dataSignature")?;
+        let mut mac = Hmac::<sha2::Sha256>::new_from_slice(&key)
+            .map_err(|e| format!("Invalid HMAC key: {}", e))?;
+        mac.update(&data);
+        mac.verify_slice(&signature)
+            .map_err(|_| "Notification signature mismatch".to_string())?;
+
+        let plaintext = Aes256CbcDec::new_from_slices(&key, &key[..16])
+            .map_err(|e| format!("Invalid AES key: {}", e))?
+            .decrypt_padded_vec_mut::<Pkcs7>(&data)
+            .map_err(|e| format!("Failed to decrypt notification: {}", e))?;
+
+        serde_json::from_slice(&plaintext)
+            .map_err(|e| format!("Decrypted notification is not JSON: {}", e))
+    }
+}
+
+fn decode(value: &str, field: &str) -> Result<Vec<u8>, String> {
+    STANDARD
+        .decode(value)
+        .map_err(|e| format!("Invalid base64 in {}: {}", field, e))
+}
@@ -0,0 +1,553 @@
+//! Graph change-notification subscriptions.
+//!
+//! Subscribes to chat and channel message changes so new messages arrive as
+//! they happen instead of on the next poll. Notifications come in through a
+//! [`Relay`]; each batch is checked (client state, validation tokens),
+//! decrypted when it carries resource data, forwarded to the webview as
+//! `realtime:notification` and used to wake the background sync.
+
+mod crypto;
+mod relay;
+mod validation;
+
+#[cfg(test)]
+mod tests;
+
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use reqwest::Method;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tauri::{AppHandle, Emitter};
+use tokio::sync::{mpsc, watch};
+
+use crate::graph::{GraphClient, GraphError};
+use crate::settings;
+use crate::sync::SyncEngine;
+
+use crypto::{EncryptedContent, EncryptionKeys};
+use relay::{Delivery, HttpRelay, Relay};
+use validation::TokenValidator;
+
+const CONFIG_KEY: &str = "realtime";
+
+/// Chat message subscriptions with resource data may live at most 60 minutes.
+const SUBSCRIPTION_LIFETIME: Duration = Duration::from_secs(55 * 60);
+/// Renew this long before a subscription expires.
+const RENEW_MARGIN: Duration = Duration::from_secs(5 * 60);
+const RENEW_RETRY: Duration = Duration::from_secs(60);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct RealtimeConfig {
+    pub enabled: bool,
+    /// Local listener port the tunnel or relay forwards to.
+    pub port: u16,
+    /// HTTPS URL of the tunnel or relay forwarding to the local listener.
+    /// Graph can't reach localhost, so without one the sync keeps polling.
+    pub public_url: Option<String>,
+    /// PEM certificate and key for rich notifications. Without them
+    /// notifications carry no message content and only trigger a sync.
+    pub certificate_path: Option<String>,
+    pub private_key_path: Option<String>,
+    pub certificate_id: String,
+}
+
+impl Default for RealtimeConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            port: 47823,
+            public_url: None,
+            certificate_path: None,
+            private_key_path: None,
+            certificate_id: "teamy-notifications".to_string(),
+        }
+    }
+}
+
+impl RealtimeConfig {
+    pub fn load(app: &AppHandle) -> Self {
+        settings::load(app, CONFIG_KEY).unwrap_or_default()
+    }
+
+    pub fn save(&self, app: &AppHandle) -> Result<(), String> {
+        settings::save(app, CONFIG_KEY, self)
+    }
+
+    /// The public URL, if it's one Graph will accept as a notification endpoint.
+    fn reachable_url(&self) -> Option<&str> {
+        self.public_url.as_deref().filter(|u| u.starts_with("https://"))
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Subscription {
+    pub id: String,
+    pub resource: String,
+    pub expiration_date_time: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RealtimeStatus {
+    pub running: bool,
+    pub notification_url: Option<String>,
+    /// Whether notifications include (encrypted) message content.
+    pub rich: bool,
+    pub subscriptions: Vec<Subscription>,
+    /// Local address the public URL has to forward to.
+    pub listen_address: Option<String>,
+    pub error: Option<String>,
+}
+
+/// One decoded change notification, emitted as `realtime:notification`.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChangeNotification {
+    pub subscription_id: String,
+    pub change_type: String,
+    pub resource: String,
+    /// Decrypted resource (a chatMessage) for rich notifications.
+    pub message: Option<Value>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct IncomingItem {
+    subscription_id: String,
+    client_state: Option<String>,
+    change_type: Option<String>,
+    resource: Option<String>,
+    lifecycle_event: Option<String>,
+    encrypted_content: Option<EncryptedContent>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct IncomingBatch {
+    value: Vec<IncomingItem>,
+    #[serde(default)]
+    validation_tokens: Vec<String>,
+}
+
+/// What the receiver and renewal tasks need from a running session.
+#[derive(Clone)]
+struct SessionContext {
+    notification_url: String,
+    lifecycle_url: String,
+    client_state: String,
+    keys: Option<Arc<EncryptionKeys>>,
+    subscriptions: Arc<Mutex<HashMap<String, Subscription>>>,
+}
+
+struct Session {
+    relay: Box<dyn Relay>,
+    listen_address: String,
+    context: SessionContext,
+    stop: watch::Sender<bool>,
+}
+
+/// Creates, renews and tears down Graph subscriptions. Held in managed state.
+#[derive(Clone)]
+pub struct SubscriptionManager {
+    inner: Arc<Inner>,
+}
+
+struct Inner {
+    app: AppHandle,
+    graph: GraphClient,
+    sync: SyncEngine,
+    session: tokio::sync::Mutex<Option<Session>>,
+    last_error: Mutex<Option<String>>,
+}
+
+impl SubscriptionManager {
+    pub fn new(app: AppHandle, graph: GraphClient, sync: SyncEngine) -> Self {
+        Self {
+            inner: Arc::new(Inner {
+                app,
+                graph,
+                sync,
+                session: tokio::sync::Mutex::new(None),
+                last_error: Mutex::new(None),
+            }),
+        }
+    }
+
+    /// Start the relay and subscribe to chats and the watched channels.
+    /// Does nothing when real-time updates are disabled in settings, or when
+    /// no public URL is configured; the delta sync keeps polling either way.
+    pub async fn start(&self, app_id: String) -> Result<RealtimeStatus, String> {
+        self.stop().await;
+
+        let config = RealtimeConfig::load(&self.inner.app);
+        if !config.enabled {
+            *self.inner.last_error.lock().unwrap() = None;
+            return Ok(self.status().await);
+        }
+        if config.reachable_url().is_none() {
+            *self.inner.last_error.lock().unwrap() =
+                Some("Needs an https:// public URL that forwards to this app; polling until one is set".to_string());
+            let status = self.status().await;
+            let _ = self.inner.app.emit("realtime:status", &status);
+            return Ok(status);
+        }
+
+        let result = self.start_session(&config, app_id).await;
+        *self.inner.last_error.lock().unwrap() = result.as_ref().err().cloned();
+        let status = self.status().await;
+        let _ = self.inner.app.emit("realtime:status", &status);
+        result.map(|_| status)
+    }
+
+    /// Delete all subscriptions and shut the relay down.
+    pub async fn stop(&self) {
+        let Some(session) = self.inner.session.lock().await.take() else {
+            return;
+        };
+        let _ = session.stop.send(true);
+        session.relay.shutdown();
+
+        let ids: Vec<String> = session
+            .context
+            .subscriptions
+            .lock()
+            .unwrap()
+            .keys()
+            .cloned()
+            .collect();
+        for id in ids {
+            let path = format!("/subscriptions/{}", id);
+            if let Err(e) = self.inner.graph.send(Method::DELETE, &path, None).await {
+                eprintln!("[realtime] Failed to delete subscription {}: {}", id, e);
+            }
+        }
+        let _ = self.inner.app.emit("realtime:status", self.status().await);
+    }
+
+    pub async fn status(&self) -> RealtimeStatus {
+        let session = self.inner.session.lock().await;
+        let error = self.inner.last_error.lock().unwrap().clone();
+        match session.as_ref() {
+            Some(session) => RealtimeStatus {
+                running: true,
+                notification_url: Some(session.context.notification_url.clone()),
+                rich: session.context.keys.is_some(),
+                subscriptions: session
+                    .context
+                    .subscriptions
+                    .lock()
+                    .unwrap()
+                    .values()
+                    .cloned()
+                    .collect(),
+                listen_address: Some(session.listen_address.clone()),
+                error,
+            },
+            None => RealtimeStatus {
+                running: false,
+                notification_url: None,
+                rich: false,
+                subscriptions: Vec::new(),
+                listen_address: None,
+                error,
+            },
+        }
+    }
+
+    async fn start_session(&self, config: &RealtimeConfig, app_id: String) -> Result<(), String> {
+        let keys = match (&config.certificate_path, &config.private_key_path) {
+            (Some(cert), Some(key)) => Some(Arc::new(EncryptionKeys::load(
+                Path::new(cert),
+                Path::new(key),
+                &config.certificate_id,
+            )?)),
+            _ => None,
+        };
+
+        let public_url = config
+            .reachable_url()
+            .ok_or("Real-time updates need an https:// public URL")?
+            .to_string();
+
+        let (inbox, deliveries) = mpsc::unbounded_channel();
+        let relay = HttpRelay::start(config.port, public_url, inbox).await?;
+        let context = SessionContext {
+            notification_url: relay.notification_url(),
+            lifecycle_url: relay.lifecycle_url(),
+            client_state: random_secret(),
+            keys,
+            subscriptions: Arc::new(Mutex::new(HashMap::new())),
+        };
+
+        let mut resources = vec!["/chats/getAllMessages".to_string()];
+        resources.extend(
+            self.inner
+                .sync
+                .watched_channels()
+                .iter()
+                .map(|c| format!("/teams/{}/channels/{}/messages", c.team_id, c.channel_id)),
+        );
+
+        for resource in resources {
+            match self.inner.create(&context, &resource).await {
+                Ok(sub) => {
+                    context.subscriptions.lock().unwrap().insert(sub.id.clone(), sub);
+                }
+                // Keep going: one channel we can't subscribe to shouldn't block the rest
+                Err(e) => eprintln!("[realtime] Failed to subscribe to {}: {}", resource, e),
+            }
+        }
+        if context.subscriptions.lock().unwrap().is_empty() {
+            relay.shutdown();
+            return Err("Graph accepted none of the subscriptions".to_string());
+        }
+
+        let (stop, stopped) = watch::channel(false);
+        let validator = Arc::new(TokenValidator::new(app_id));
+        tauri::async_runtime::spawn(self.inner.clone().receive(
+            context.clone(),
+            deliveries,
+            validator,
+            stopped.clone(),
+        ));
+        tauri::async_runtime::spawn(self.inner.clone().renew_loop(context.clone(), stopped));
+
+        *self.inner.session.lock().await = Some(Session {
+            listen_address: relay.local_addr().to_string(),
+            relay: Box::new(relay),
+            context,
+            stop,
+        });
+        Ok(())
+    }
+}
+
+impl Inner {
+    async fn create(&self, ctx: &SessionContext, resource: &str) -> Result<Subscription, GraphError> {
+        let mut body = json!({
+            "changeType": "created,updated,deleted",
+            "notificationUrl": ctx.notification_url,
+            "lifecycleNotificationUrl": ctx.lifecycle_url,
+            "resource": resource,
+            "expirationDateTime": expiration(),
+            "clientState": ctx.client_state,
+        });
+        if let Some(keys) = &ctx.keys {
+            body["includeResourceData"] = json!(true);
+            body["encryptionCertificate"] = json!(keys.certificate);
+            body["encryptionCertificateId"] = json!(keys.certificate_id);
+        }
+        self.graph.post("/subscriptions", &body).await
+    }
+
+    /// Extend `id`, recreating it if Graph has already dropped it.
+    async fn renew(&self, ctx: &SessionContext, id: &str) -> bool {
+        let path = format!("/subscriptions/{}", id);
+        let patch = json!({ "expirationDateTime": expiration() });
+        let renewed = match self.graph.send(Method::PATCH, &path, Some(&patch)).await {
+            Ok(value) => serde_json::from_value::<Subscription>(value).map_err(|e| {
+                GraphError::new("invalid_response", format!("Unexpected subscription: {}", e))
+            }),
+            Err(e) if e.status == Some(404) => {
+                let resource = ctx.subscriptions.lock().unwrap().get(id).map(|s| s.resource.clone());
+                match resource {
+                    Some(resource) => self.create(ctx, &resource).await,
+                    None => return false,
+                }
+            }
+            Err(e) => Err(e),
+        };
+
+        let ok = match renewed {
+            Ok(sub) => {
+                let mut subs = ctx.subscriptions.lock().unwrap();
+                subs.remove(id);
+                subs.insert(sub.id.clone(), sub);
+                true
+            }
+            Err(e) => {
+                eprintln!("[realtime] Failed to renew subscription {}: {}", id, e);
+                false
+            }
+        };
+        let _ = self.app.emit("realtime:subscriptions", self.subscriptions(ctx));
+        ok
+    }
+
+    fn subscriptions(&self, ctx: &SessionContext) -> Vec<Subscription> {
+        ctx.subscriptions.lock().unwrap().values().cloned().collect()
+    }
+
+    async fn renew_loop(self: Arc<Self>, ctx: SessionContext, mut stopped: watch::Receiver<bool>) {
+        loop {
+            let next = ctx
+                .subscriptions
+                .lock()
+                .unwrap()
+                .values()
+                .map(|s| (s.id.clone(), until(&s.expiration_date_time).saturating_sub(RENEW_MARGIN)))
+                .min_by_key(|(_, wait)| *wait);
+            let Some((id, wait)) = next else {
+                return;
+            };
+
+            tokio::select! {
+                _ = tokio::time::sleep(wait) => {}
+                _ = stopped.changed() => return,
+            }
+            if !self.renew(&ctx, &id).await {
+                // Don't hammer Graph while it keeps refusing; the subscription
+                // may still be alive until its expiry
+                tokio::select! {
+                    _ = tokio::time::sleep(RENEW_RETRY) => {}
+                    _ = stopped.changed() => return,
+                }
+            }
+        }
+    }
+
+    async fn receive(
+        self: Arc<Self>,
+        ctx: SessionContext,
+        mut deliveries: mpsc::UnboundedReceiver<Delivery>,
+        validator: Arc<TokenValidator>,
+        mut stopped: watch::Receiver<bool>,
+    ) {
+        loop {
+            let delivery = tokio::select! {
+                delivery = deliveries.recv() => match delivery {
+                    Some(delivery) => delivery,
+                    None => return,
+                },
+                _ = stopped.changed() => return,
+            };
+
+            let result = match delivery {
+                Delivery::Notifications(body) => self.handle_notifications(&ctx, &validator, body).await,
+                Delivery::Lifecycle(body) => self.handle_lifecycle(&ctx, body).await,
+            };
+            if let Err(e) = result {
+                eprintln!("[realtime] Dropped notification batch: {}", e);
+            }
+        }
+    }
+
+    async fn handle_notifications(
+        &self,
+        ctx: &SessionContext,
+        validator: &TokenValidator,
+        body: Value,
+    ) -> Result<(), String> {
+        let notifications = accept_notifications(ctx, validator, body).await?;
+
+        // The delta sync does the actual merging; just make it run now
+        let accepted = !notifications.is_empty();
+        for notification in notifications {
+            let _ = self.app.emit("realtime:notification", notification);
+        }
+        if accepted {
+            self.sync.poll_now();
+        }
+        Ok(())
+    }
+
+    async fn handle_lifecycle(&self, ctx: &SessionContext, body: Value) -> Result<(), String> {
+        let batch: IncomingBatch =
+            serde_json::from_value(body).map_err(|e| format!("Unexpected payload: {}", e))?;
+
+        for item in batch.value {
+            if !ctx.is_ours(&item) {
+                continue;
+            }
+            match item.lifecycle_event.as_deref() {
+                Some("reauthorizationRequired") | Some("subscriptionRemoved") => {
+                    self.renew(ctx, &item.subscription_id).await;
+                }
+                // Graph couldn't deliver some notifications — catch up by polling
+                Some("missed") => self.sync.poll_now(),
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+}
+
+impl SessionContext {
+    fn is_ours(&self, item: &IncomingItem) -> bool {
+        item.client_state.as_deref() == Some(self.client_state.as_str())
+            && self.subscriptions.lock().unwrap().contains_key(&item.subscription_id)
+    }
+}
+
+/// Check a change-notification batch and decode the notifications meant for
+/// this session.
+async fn accept_notifications(
+    ctx: &SessionContext,
+    validator: &TokenValidator,
+    body: Value,
+) -> Result<Vec<ChangeNotification>, String> {
+    let batch: IncomingBatch = serde_json::from_value(body).map_err(|e| format!("Unexpected payload: {}", e))?;
+
+    // Resource data is only trusted when every validation token checks out
+    let has_resource_data = batch.value.iter().any(|n| n.encrypted_content.is_some());
+    if has_resource_data {
+        if batch.validation_tokens.is_empty() {
+            return Err("Rich notification without validation tokens".to_string());
+        }
+        for token in &batch.validation_tokens {
+            validator.validate(token).await?;
+        }
+    }
+
+    let mut accepted = Vec::new();
+    for item in batch.value {
+        if !ctx.is_ours(&item) {
+            eprintln!("[realtime] Ignoring notification with unknown subscription or client state");
+            continue;
+        }
+
+        let message = match (&item.encrypted_content, &ctx.keys) {
+            (Some(content), Some(keys)) => match keys.decrypt(content) {
+                Ok(message) => Some(message),
+                Err(e) => {
+                    eprintln!("[realtime] {}", e);
+                    None
+                }
+            },
+            _ => None,
+        };
+
+        accepted.push(ChangeNotification {
+            subscription_id: item.subscription_id,
+            change_type: item.change_type.unwrap_or_default(),
+            resource: item.resource.unwrap_or_default(),
+            message,
+        });
+    }
+    Ok(accepted)
+}
+
+fn expiration() -> String {
+    let lifetime = chrono::Duration::from_std(SUBSCRIPTION_LIFETIME).unwrap_or_default();
+    (chrono::Utc::now() + lifetime).to_rfc3339_opts(chrono::SecondsFormat::Secs, true)
+}
+
+/// Time left until an RFC 3339 timestamp (zero if it's past or unparseable).
+fn until(timestamp: &str) -> Duration {
+    chrono::DateTime::parse_from_rfc3339(timestamp)
+        .ok()
+        .and_then(|at| (at.with_timezone(&chrono::Utc) - chrono::Utc::now()).to_std().ok())
+        .unwrap_or_default()
+}
+
+/// `clientState` secret Graph echoes back with every notification.
+fn random_secret() -> String {
+    URL_SAFE_NO_PAD.encode(rand::random::<[u8; 32]>())
+}
@@ -0,0 +1,219 @@
+use std::net::SocketAddr;
+
+use serde_json::Value;
+use tokio::io::{AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::{mpsc, watch};
+
+/// Cap on a single notification request; Graph batches stay well under this.
+const MAX_BODY_BYTES: usize = 4 * 1024 * 1024;
+
+/// A POST delivered to one of the relay's endpoints.
+#[derive(Debug)]
+pub enum Delivery {
+    /// Body of a change-notification request (`{ "value": [...], "validationTokens": [...] }`).
+    Notifications(Value),
+    /// Body of a lifecycle-notification request.
+    Lifecycle(Value),
+}
+
+/// Where Graph sends change notifications and how they reach us.
+///
+/// A relay owns the transport (local listener, tunnel, hosted forwarder, or an
+/// in-process fake) and hands each request body to the subscription manager.
+pub trait Relay: Send + Sync {
+    /// Public URL Graph should POST change notifications to.
+    fn notification_url(&self) -> String;
+    /// Public URL for lifecycle notifications.
+    fn lifecycle_url(&self) -> String;
+    /// Stop accepting requests.
+    fn shutdown(&self);
+}
+
+/// Local HTTP listener, reached by Graph through `public_url`: a tunnel
+/// (ngrok, dev tunnels, cloudflared, ...) or relay that forwards to it.
+pub struct HttpRelay {
+    local_addr: SocketAddr,
+    public_url: String,
+    shutdown: watch::Sender<bool>,
+}
+
+impl HttpRelay {
+    /// Bind to `127.0.0.1:port` (0 picks a free port) and start serving.
+    pub async fn start(
+        port: u16,
+        public_url: String,
+        inbox: mpsc::UnboundedSender<Delivery>,
+    ) -> Result<Self, String> {
+        let listener = TcpListener::bind(("127.0.0.1", port))
+            .await
+            .map_err(|e| format!("Failed to bind relay on port {}: {}", port, e))?;
+        let local_addr = listener
+            .local_addr()
+            .map_err(|e| format!("Failed to read relay address: {}", e))?;
+        let (shutdown, mut stop) = watch::channel(false);
+
+        tauri::async_runtime::spawn(async move {
+            loop {
+                tokio::select! {
+                    accepted = listener.accept() => {
+                        if let Ok((stream, _)) = accepted {
+                            let inbox = inbox.clone();
+                            tauri::async_runtime::spawn(async move {
+                                if let Err(e) = serve(stream, inbox).await {
+                                    eprintln!("[realtime] Relay request failed: {}", e);
+                                }
+                            });
+                        }
+                    }
+                    _ = stop.changed() => break,
+                }
+            }
+        });
+
+        Ok(Self {
+            local_addr,
+            public_url: public_url.trim_end_matches('/').to_string(),
+            shutdown,
+        })
+    }
+
+    /// Where the listener actually is, for whatever forwards to it.
+    pub fn local_addr(&self) -> SocketAddr {
+        self.local_addr
+    }
+}
+
+impl Relay for HttpRelay {
+    fn notification_url(&self) -> String {
+        format!("{}/notifications", self.public_url)
+    }
+
+    fn lifecycle_url(&self) -> String {
+        format!("{}/lifecycle", self.public_url)
+    }
+
+    fn shutdown(&self) {
+        let _ = self.shutdown.send(true);
+    }
+}
+
+struct Request {
+    method: String,
+    path: String,
+    query: String,
+    body: Vec<u8>,
+}
+
+/// Handle one HTTP/1.1 request: echo `validationToken` handshakes, accept
+/// notification bodies with `202`.
+async fn serve(stream: TcpStream, inbox: mpsc::UnboundedSender<Delivery>) -> Result<(), String> {
+    let mut stream = BufReader::new(stream);
+    let request = match read_request(&mut stream).await {
+        Ok(request) => request,
+        Err(e) => {
+            respond(stream.get_mut(), 400, "text/plain", "Bad Request").await?;
+            return Err(e);
+        }
+    };
+
+    if request.method != "POST" {
+        return respond(stream.get_mut(), 405, "text/plain", "Method Not Allowed").await;
+    }
+
+    // Subscription creation handshake: echo the token as plain text within 10s
+    if let Some(token) = query_param(&request.query, "validationToken") {
+        return respond(stream.get_mut(), 200, "text/plain", &token).await;
+    }
+
+    let body: Value = match serde_json::from_slice(&request.body) {
+        Ok(body) => body,
+        Err(_) => return respond(stream.get_mut(), 400, "text/plain", "Invalid JSON").await,
+    };
+    let delivery = match request.path.as_str() {
+        "/notifications" => Delivery::Notifications(body),
+        "/lifecycle" => Delivery::Lifecycle(body),
+        _ => return respond(stream.get_mut(), 404, "text/plain", "Not Found").await,
+    };
+
+    // Acknowledge first — Graph retries (and eventually drops) slow endpoints
+    respond(stream.get_mut(), 202, "text/plain", "").await?;
+    let _ = inbox.send(delivery);
+    Ok(())
+}
+
+async fn read_request(stream: &mut BufReader<TcpStream>) -> Result<Request, String> {
+    let mut head = Vec::new();
+    let mut byte = [0u8; 1];
+    while !head.ends_with(b"\r\n\r\n") {
+        if head.len() > 16 * 1024 {
+            return Err("Request headers too large".to_string());
+        }
+        let n = stream.read(&mut byte).await.map_err(|e| e.to_string())?;
+        if n == 0 {
+            return Err("Connection closed mid-request".to_string());
+        }
+        head.push(byte[0]);
+    }
+
+    let head = String::from_utf8_lossy(&head);
+    let mut lines = head.split("\r\n");
+    let mut request_line = lines.next().unwrap_or("").split_whitespace();
+    let method = request_line.next().unwrap_or("").to_string();
+    let target = request_line.next().unwrap_or("/");
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+
+    let content_length = lines
+        .filter_map(|l| l.split_once(':'))
+        .find(|(name, _)| name.trim().eq_ignore_ascii_case("content-length"))
+        .and_then(|(_, value)| value.trim().parse::<usize>().ok())
+        .unwrap_or(0);
+    if content_length > MAX_BODY_BYTES {
+        return Err("Request body too large".to_string());
+    }
+
+    let mut body = vec![0u8; content_length];
+    stream.read_exact(&mut body).await.map_err(|e| e.to_string())?;
+
+    Ok(Request {
+        method,
+        path: path.to_string(),
+        query: query.to_string(),
+        body,
+    })
+}
+
+async fn respond(stream: &mut TcpStream, status: u16, content_type: &str, body: &str) -> Result<(), String> {
+    let reason = match status {
+        200 => "OK",
+        202 => "Accepted",
+        400 => "Bad Request",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        _ => "",
+    };
+    let response = format!(
+        "HTTP/1.1 {} {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        reason,
+        content_type,
+        body.len(),
+        body
+    );
+    stream
+        .write_all(response.as_bytes())
+        .await
+        .map_err(|e| e.to_string())
+}
+
+fn query_param(query: &str, name: &str) -> Option<String> {
+    query
+        .split('&')
+        .filter_map(|pair| pair.split_once('='))
+        .find(|(key, _)| *key == name)
+        .and_then(|(_, value)| {
+            urlencoding::decode(&value.replace('+', " "))
+                .ok()
+                .map(|decoded| decoded.into_owned())
+        })
+}
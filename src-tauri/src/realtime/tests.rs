@@ -0,0 +1,332 @@
+//! Notification handling end to end, minus Graph: deliveries come through an
+//! in-process [`Relay`], validation tokens are checked against a JWKS served
+//! from localhost, and rich content is encrypted the way Graph does it.
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use aes::cipher::{block_padding::Pkcs7, BlockEncryptMut, KeyIvInit};
+use base64::engine::general_purpose::{STANDARD, URL_SAFE_NO_PAD};
+use base64::Engine;
+use hmac::{Hmac, Mac};
+use rsa::pkcs1v15::SigningKey;
+use rsa::pkcs8::{EncodePrivateKey, LineEnding};
+use rsa::signature::{SignatureEncoding, Signer};
+use rsa::traits::PublicKeyParts;
+use rsa::{Oaep, RsaPrivateKey};
+use serde_json::{json, Value};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+use tokio::sync::mpsc;
+
+use super::crypto::EncryptionKeys;
+use super::relay::{Delivery, HttpRelay, Relay};
+use super::validation::TokenValidator;
+use super::{accept_notifications, SessionContext, Subscription};
+
+const APP_ID: &str = "11111111-2222-3333-4444-555555555555";
+const GRAPH_APP_ID: &str = "0bf30f3b-4a52-48df-9a82-234910c4a086";
+const CLIENT_STATE: &str = "client-state-secret";
+const SUBSCRIPTION_ID: &str = "sub-1";
+
+/// Stands in for a hosted relay: whatever Graph would POST goes straight
+/// into the subscription manager's inbox.
+struct FakeRelay {
+    inbox: mpsc::UnboundedSender<Delivery>,
+}
+
+impl FakeRelay {
+    fn new() -> (Self, mpsc::UnboundedReceiver<Delivery>) {
+        let (inbox, deliveries) = mpsc::unbounded_channel();
+        (Self { inbox }, deliveries)
+    }
+
+    fn post(&self, body: Value) {
+        self.inbox.send(Delivery::Notifications(body)).unwrap();
+    }
+}
+
+impl Relay for FakeRelay {
+    fn notification_url(&self) -> String {
+        "https://relay.test/notifications".to_string()
+    }
+
+    fn lifecycle_url(&self) -> String {
+        "https://relay.test/lifecycle".to_string()
+    }
+
+    fn shutdown(&self) {}
+}
+
+fn session(relay: &FakeRelay, keys: Option<EncryptionKeys>) -> SessionContext {
+    let subscription = Subscription {
+        id: SUBSCRIPTION_ID.to_string(),
+        resource: "/chats/getAllMessages".to_string(),
+        expiration_date_time: "2030-01-01T00:00:00Z".to_string(),
+    };
+    SessionContext {
+        notification_url: relay.notification_url(),
+        lifecycle_url: relay.lifecycle_url(),
+        client_state: CLIENT_STATE.to_string(),
+        keys: keys.map(Arc::new),
+        subscriptions: Arc::new(Mutex::new(HashMap::from([(SUBSCRIPTION_ID.to_string(), subscription)]))),
+    }
+}
+
+/// Pass `body` through the relay and handle what comes out the other end.
+async fn deliver(
+    relay: &FakeRelay,
+    deliveries: &mut mpsc::UnboundedReceiver<Delivery>,
+    ctx: &SessionContext,
+    validator: &TokenValidator,
+    body: Value,
+) -> Result<Vec<super::ChangeNotification>, String> {
+    relay.post(body);
+    match deliveries.recv().await {
+        Some(Delivery::Notifications(body)) => accept_notifications(ctx, validator, body).await,
+        other => panic!("unexpected delivery {:?}", other),
+    }
+}
+
+fn rsa_key() -> RsaPrivateKey {
+    // Small keys keep the tests quick; nothing here depends on the size
+    RsaPrivateKey::new(&mut rand::thread_rng(), 1024).unwrap()
+}
+
+fn encryption_keys(key: &RsaPrivateKey) -> EncryptionKeys {
+    let key_pem = key.to_pkcs8_pem(LineEnding::LF).unwrap();
+    let cert_pem = "-----BEGIN CERTIFICATE-----\nTUlJQg==\n-----END CERTIFICATE-----\n";
+    EncryptionKeys::from_pem(cert_pem, &key_pem, "teamy-notifications").unwrap()
+}
+
+/// `encryptedContent` as Graph builds it for `resource`.
+fn encrypt(key: &RsaPrivateKey, resource: &Value) -> Value {
+    let data_key: [u8; 32] = rand::random();
+    let data = cbc::Encryptor::<aes::Aes256>::new_from_slices(&data_key, &data_key[..16])
+        .unwrap()
+        .encrypt_padded_vec_mut::<Pkcs7>(resource.to_string().as_bytes());
+    let mut mac = Hmac::<sha2::Sha256>::new_from_slice(&data_key).unwrap();
+    mac.update(&data);
+    let wrapped = key
+        .to_public_key()
+        .encrypt(&mut rand::thread_rng(), Oaep::new::<sha1::Sha1>(), &data_key)
+        .unwrap();
+
+    json!({
+        "data": STANDARD.encode(&data),
+        "dataSignature": STANDARD.encode(mac.finalize().into_bytes()),
+        "dataKey": STANDARD.encode(wrapped),
+        "encryptionCertificateId": "teamy-notifications",
+    })
+}
+
+fn notification(client_state: &str) -> Value {
+    json!({
+        "subscriptionId": SUBSCRIPTION_ID,
+        "clientState": client_state,
+        "changeType": "created",
+        "resource": "chats('19:abc')/messages('1')",
+    })
+}
+
+fn now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs()
+}
+
+fn claims() -> Value {
+    json!({
+        "aud": APP_ID,
+        "iss": "https://login.microsoftonline.com/tenant/v2.0",
+        "azp": GRAPH_APP_ID,
+        "exp": now() + 3600,
+        "nbf": now() - 60,
+    })
+}
+
+fn sign(key: &RsaPrivateKey, kid: &str, claims: &Value) -> String {
+    let header = URL_SAFE_NO_PAD.encode(json!({ "alg": "RS256", "kid": kid }).to_string());
+    let claims = URL_SAFE_NO_PAD.encode(claims.to_string());
+    let signed = format!("{}.{}", header, claims);
+    let signature = SigningKey::<sha2::Sha256>::new(key.clone()).sign(signed.as_bytes());
+    format!("{}.{}", signed, URL_SAFE_NO_PAD.encode(signature.to_bytes()))
+}
+
+/// Serve `keys` as a JWKS on localhost, counting fetches.
+async fn jwks_server(keys: &[(&str, &RsaPrivateKey)]) -> (String, Arc<AtomicUsize>) {
+    let body = json!({
+        "keys": keys
+            .iter()
+            .map(|(kid, key)| json!({
+                "kty": "RSA",
+                "kid": kid,
+                "n": URL_SAFE_NO_PAD.encode(key.n().to_bytes_be()),
+                "e": URL_SAFE_NO_PAD.encode(key.e().to_bytes_be()),
+            }))
+            .collect::<Vec<_>>(),
+    })
+    .to_string();
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}/keys", listener.local_addr().unwrap());
+    let fetches = Arc::new(AtomicUsize::new(0));
+
+    let counter = fetches.clone();
+    tokio::spawn(async move {
+        while let Ok((mut stream, _)) = listener.accept().await {
+            counter.fetch_add(1, Ordering::SeqCst);
+            let mut request = [0u8; 4096];
+            let _ = stream.read(&mut request).await;
+            let response = format!(
+                "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                body.len(),
+                body
+            );
+            let _ = stream.write_all(response.as_bytes()).await;
+        }
+    });
+    (url, fetches)
+}
+
+#[tokio::test]
+async fn relay_echoes_validation_token() {
+    let (inbox, mut deliveries) = mpsc::unbounded_channel();
+    let relay = HttpRelay::start(0, "https://relay.test".to_string(), inbox).await.unwrap();
+    let url = format!("http://{}/notifications?validationToken=Validation%3A+Testing+client", relay.local_addr());
+
+    let response = reqwest::Client::new().post(&url).send().await.unwrap();
+
+    assert_eq!(response.status(), 200);
+    assert_eq!(response.text().await.unwrap(), "Validation: Testing client");
+    assert!(deliveries.try_recv().is_err());
+    relay.shutdown();
+}
+
+#[tokio::test]
+async fn relay_acknowledges_and_forwards_notifications() {
+    let (inbox, mut deliveries) = mpsc::unbounded_channel();
+    let relay = HttpRelay::start(0, "https://relay.test/".to_string(), inbox).await.unwrap();
+    assert_eq!(relay.notification_url(), "https://relay.test/notifications");
+    let url = format!("http://{}/notifications", relay.local_addr());
+    let body = json!({ "value": [notification(CLIENT_STATE)] });
+
+    let response = reqwest::Client::new().post(&url).json(&body).send().await.unwrap();
+
+    assert_eq!(response.status(), 202);
+    match deliveries.recv().await {
+        Some(Delivery::Notifications(received)) => assert_eq!(received, body),
+        other => panic!("unexpected delivery {:?}", other),
+    }
+    relay.shutdown();
+}
+
+#[tokio::test]
+async fn rejects_unknown_client_state_and_subscription() {
+    let (relay, mut deliveries) = FakeRelay::new();
+    let ctx = session(&relay, None);
+    let validator = TokenValidator::new(APP_ID.to_string());
+    let mut stranger = notification(CLIENT_STATE);
+    stranger["subscriptionId"] = json!("someone-elses");
+    let body = json!({ "value": [notification("forged"), stranger, notification(CLIENT_STATE)] });
+
+    let accepted = deliver(&relay, &mut deliveries, &ctx, &validator, body).await.unwrap();
+
+    assert_eq!(accepted.len(), 1);
+    assert_eq!(accepted[0].subscription_id, SUBSCRIPTION_ID);
+    assert!(accepted[0].message.is_none());
+}
+
+#[tokio::test]
+async fn decrypts_rich_notifications() {
+    let key = rsa_key();
+    let (relay, mut deliveries) = FakeRelay::new();
+    let ctx = session(&relay, Some(encryption_keys(&key)));
+    let (jwks_url, _) = jwks_server(&[("kid-1", &key)]).await;
+    let validator = TokenValidator::with_jwks_url(APP_ID.to_string(), jwks_url);
+
+    let message = json!({ "id": "1", "body": { "content": "hello" } });
+    let mut item = notification(CLIENT_STATE);
+    item["encryptedContent"] = encrypt(&key, &message);
+    let body = json!({ "value": [item], "validationTokens": [sign(&key, "kid-1", &claims())] });
+
+    let accepted = deliver(&relay, &mut deliveries, &ctx, &validator, body).await.unwrap();
+
+    assert_eq!(accepted.len(), 1);
+    assert_eq!(accepted[0].message.as_ref(), Some(&message));
+}
+
+#[tokio::test]
+async fn drops_content_with_a_bad_signature() {
+    let key = rsa_key();
+    let (relay, mut deliveries) = FakeRelay::new();
+    let ctx = session(&relay, Some(encryption_keys(&key)));
+    let (jwks_url, _) = jwks_server(&[("kid-1", &key)]).await;
+    let validator = TokenValidator::with_jwks_url(APP_ID.to_string(), jwks_url);
+
+    let mut content = encrypt(&key, &json!({ "id": "1" }));
+    content["dataSignature"] = json!(STANDARD.encode([0u8; 32]));
+    let mut item = notification(CLIENT_STATE);
+    item["encryptedContent"] = content;
+    let body = json!({ "value": [item], "validationTokens": [sign(&key, "kid-1", &claims())] });
+
+    let accepted = deliver(&relay, &mut deliveries, &ctx, &validator, body).await.unwrap();
+
+    // Still worth a sync, but the content isn't trusted
+    assert_eq!(accepted.len(), 1);
+    assert!(accepted[0].message.is_none());
+}
+
+#[tokio::test]
+async fn requires_validation_tokens_for_rich_notifications() {
+    let key = rsa_key();
+    let (relay, mut deliveries) = FakeRelay::new();
+    let ctx = session(&relay, Some(encryption_keys(&key)));
+    let validator = TokenValidator::new(APP_ID.to_string());
+    let mut item = notification(CLIENT_STATE);
+    item["encryptedContent"] = encrypt(&key, &json!({ "id": "1" }));
+
+    let result = deliver(&relay, &mut deliveries, &ctx, &validator, json!({ "value": [item] })).await;
+
+    assert!(result.is_err());
+}
+
+#[tokio::test]
+async fn validates_tokens_against_the_published_keys() {
+    let key = rsa_key();
+    let (jwks_url, fetches) = jwks_server(&[("kid-1", &key)]).await;
+    let validator = TokenValidator::with_jwks_url(APP_ID.to_string(), jwks_url);
+
+    validator.validate(&sign(&key, "kid-1", &claims())).await.unwrap();
+    validator.validate(&sign(&key, "kid-1", &claims())).await.unwrap();
+    assert_eq!(fetches.load(Ordering::SeqCst), 1);
+
+    let mut other_app = claims();
+    other_app["aud"] = json!("someone-else");
+    assert!(validator.validate(&sign(&key, "kid-1", &other_app)).await.is_err());
+
+    let mut not_graph = claims();
+    not_graph["azp"] = json!(APP_ID);
+    assert!(validator.validate(&sign(&key, "kid-1", &not_graph)).await.is_err());
+
+    let mut expired = claims();
+    expired["exp"] = json!(now() - 3600);
+    assert!(validator.validate(&sign(&key, "kid-1", &expired)).await.is_err());
+
+    // Right kid, wrong key
+    let forged = sign(&rsa_key(), "kid-1", &claims());
+    assert!(validator.validate(&forged).await.is_err());
+}
+
+#[tokio::test]
+async fn rate_limits_refetches_for_unknown_keys() {
+    let key = rsa_key();
+    let (jwks_url, fetches) = jwks_server(&[("kid-1", &key)]).await;
+    let validator = TokenValidator::with_jwks_url(APP_ID.to_string(), jwks_url);
+    validator.validate(&sign(&key, "kid-1", &claims())).await.unwrap();
+
+    for _ in 0..3 {
+        assert!(validator.validate(&sign(&key, "kid-unknown", &claims())).await.is_err());
+    }
+
+    assert_eq!(fetches.load(Ordering::SeqCst), 1);
+}
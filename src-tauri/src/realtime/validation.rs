@@ -0,0 +1,188 @@
+use std::collections::HashMap;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use rsa::pkcs1v15::{Signature, VerifyingKey};
+use rsa::signature::Verifier;
+use rsa::{BigUint, RsaPublicKey};
+use serde::Deserialize;
+use tokio::sync::Mutex;
+
+const JWKS_URL: &str = "https://login.microsoftonline.com/common/discovery/v2.0/keys";
+/// App ID Graph uses to sign change-notification validation tokens.
+const GRAPH_NOTIFICATION_APP_ID: &str = "0bf30f3b-4a52-48df-9a82-234910c4a086";
+const JWKS_TTL: Duration = Duration::from_secs(24 * 60 * 60);
+/// An unknown `kid` triggers a refetch at most this often, so forged tokens
+/// can't make us hammer the key endpoint.
+const JWKS_REFETCH_INTERVAL: Duration = Duration::from_secs(5 * 60);
+/// Allowed clock skew when checking `nbf` / `exp`.
+const LEEWAY_SECS: u64 = 300;
+
+#[derive(Deserialize)]
+struct Header {
+    alg: String,
+    kid: String,
+}
+
+#[derive(Deserialize)]
+struct Claims {
+    aud: String,
+    iss: String,
+    azp: Option<String>,
+    appid: Option<String>,
+    exp: u64,
+    nbf: Option<u64>,
+}
+
+#[derive(Deserialize)]
+struct Jwks {
+    keys: Vec<Jwk>,
+}
+
+#[derive(Deserialize)]
+struct Jwk {
+    kid: String,
+    n: String,
+    e: String,
+}
+
+#[derive(Default)]
+struct KeyCache {
+    keys: HashMap<String, RsaPublicKey>,
+    /// When `keys` was last fetched successfully.
+    fetched: Option<Instant>,
+    /// The last fetch attempt, successful or not.
+    attempted: Option<Instant>,
+}
+
+/// Checks the `validationTokens` that accompany rich notifications, proving
+/// they were sent by Graph for our app.
+pub struct TokenValidator {
+    app_id: String,
+    jwks_url: String,
+    http: reqwest::Client,
+    keys: Mutex<KeyCache>,
+}
+
+impl TokenValidator {
+    pub fn new(app_id: String) -> Self {
+        Self::with_jwks_url(app_id, JWKS_URL.to_string())
+    }
+
+    pub fn with_jwks_url(app_id: String, jwks_url: String) -> Self {
+        Self {
+            app_id,
+            jwks_url,
+            http: reqwest::Client::new(),
+            keys: Mutex::new(KeyCache::default()),
+        }
+    }
+
+    pub async fn validate(&self, token: &str) -> Result<(), String> {
+        let mut parts = token.split('.');
+        let (Some(header), Some(claims), Some(signature), None) =
+            (parts.next(), parts.next(), parts.next(), parts.next())
+        else {
+            return Err("Malformed validation token".to_string());
+        };
+
+        let header: Header = decode_part(header)?;
+        if header.alg != "RS256" {
+            return Err(format!("Unsupported token algorithm {}", header.alg));
+        }
+
+        let key = self.signing_key(&header.kid).await?;
+        let signature = URL_SAFE_NO_PAD
+            .decode(signature)
+            .map_err(|_| "Malformed token signature".to_string())?;
+        let signature =
+            Signature::try_from(signature.as_slice()).map_err(|e| format!("Bad signature: {}", e))?;
+        let (signed, _) = token.rsplit_once('.').unwrap_or_default();
+        VerifyingKey::<sha2::Sha256>::new(key)
+            .verify(signed.as_bytes(), &signature)
+            .map_err(|_| "Validation token signature is invalid".to_string())?;
+
+        let claims: Claims = decode_part(claims)?;
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        if claims.exp + LEEWAY_SECS < now || claims.nbf.is_some_and(|nbf| nbf > now + LEEWAY_SECS) {
+            return Err("Validation token has expired".to_string());
+        }
+        if claims.aud != self.app_id {
+            return Err("Validation token is for a different app".to_string());
+        }
+        let caller = claims.azp.or(claims.appid).unwrap_or_default();
+        if caller != GRAPH_NOTIFICATION_APP_ID {
+            return Err("Validation token was not issued to Microsoft Graph".to_string());
+        }
+        if !(claims.iss.starts_with("https://sts.windows.net/")
+            || claims.iss.starts_with("https://login.microsoftonline.com/"))
+        {
+            return Err("Validation token has an unexpected issuer".to_string());
+        }
+
+        Ok(())
+    }
+
+    async fn signing_key(&self, kid: &str) -> Result<RsaPublicKey, String> {
+        let mut cache = self.keys.lock().await;
+
+        let expired = cache.fetched.is_none_or(|fetched| fetched.elapsed() >= JWKS_TTL);
+        // Unknown kid usually means the keys rotated
+        let wanted = expired || !cache.keys.contains_key(kid);
+        let allowed = cache
+            .attempted
+            .is_none_or(|attempted| attempted.elapsed() >= JWKS_REFETCH_INTERVAL);
+        if wanted && allowed {
+            cache.attempted = Some(Instant::now());
+            match self.fetch_keys().await {
+                Ok(keys) => {
+                    cache.keys = keys;
+                    cache.fetched = cache.attempted;
+                }
+                // Keys outlive their cache entry; a stale one still verifies
+                Err(e) if !cache.keys.contains_key(kid) => return Err(e),
+                Err(e) => eprintln!("[realtime] {}", e),
+            }
+        }
+
+        cache
+            .keys
+            .get(kid)
+            .cloned()
+            .ok_or_else(|| format!("Unknown token signing key {}", kid))
+    }
+
+    async fn fetch_keys(&self) -> Result<HashMap<String, RsaPublicKey>, String> {
+        let jwks: Jwks = self
+            .http
+            .get(&self.jwks_url)
+            .send()
+            .await
+            .and_then(|r| r.error_for_status())
+            .map_err(|e| format!("Failed to fetch signing keys: {}", e))?
+            .json()
+            .await
+            .map_err(|e| format!("Failed to parse signing keys: {}", e))?;
+
+        Ok(jwks
+            .keys
+            .into_iter()
+            .filter_map(|k| {
+                let n = URL_SAFE_NO_PAD.decode(&k.n).ok()?;
+                let e = URL_SAFE_NO_PAD.decode(&k.e).ok()?;
+                let key = RsaPublicKey::new(BigUint::from_bytes_be(&n), BigUint::from_bytes_be(&e)).ok()?;
+                Some((k.kid, key))
+            })
+            .collect())
+    }
+}
+
+fn decode_part<T: serde::de::DeserializeOwned>(part: &str) -> Result<T, String> {
+    let bytes = URL_SAFE_NO_PAD
+        .decode(part.trim_end_matches('='))
+        .map_err(|_| "Malformed validation token".to_string())?;
+    serde_json::from_slice(&bytes).map_err(|e| format!("Malformed validation token: {}", e))
+}
@@ -0,0 +1,28 @@
+//! The `settings.json` store shared with the frontend. Each feature keeps its
+//! settings under its own key.
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use tauri::AppHandle;
+use tauri_plugin_store::StoreExt;
+
+const STORE: &str = "settings.json";
+
+/// The value saved under `key`; `None` when there is none, it no longer
+/// deserializes or the store can't be opened.
+pub fn load<T: DeserializeOwned>(app: &AppHandle, key: &str) -> Option<T> {
+    let value = app.store(STORE).ok()?.get(key)?;
+    serde_json::from_value(value).ok()
+}
+
+/// Save `value` under `key` and write the store to disk.
+pub fn save<T: Serialize + ?Sized>(app: &AppHandle, key: &str, value: &T) -> Result<(), String> {
+    let value = serde_json::to_value(value).map_err(|e| format!("Failed to serialize {}: {}", key, e))?;
+    let store = app
+        .store(STORE)
+        .map_err(|e| format!("Failed to open settings: {}", e))?;
+    store.set(key, value);
+    store
+        .save()
+        .map_err(|e| format!("Failed to save settings: {}", e))
+}
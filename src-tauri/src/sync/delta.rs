@@ -0,0 +1,80 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tauri::AppHandle;
+use tauri_plugin_store::StoreExt;
+
+const STORE_FILE: &str = "sync-state.json";
+const STORE_KEY: &str = "deltaLinks";
+
+#[derive(Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Persisted {
+    user_id: String,
+    links: HashMap<String, String>,
+}
+
+/// Delta links per feed, persisted so a restart resumes where we left off
+/// instead of re-reading history. Links belong to one user and are dropped
+/// when someone else signs in.
+pub struct DeltaLinks {
+    app: AppHandle,
+    user_id: String,
+    links: HashMap<String, String>,
+}
+
+impl DeltaLinks {
+    pub fn load(app: &AppHandle, user_id: &str) -> Self {
+        let persisted: Persisted = app
+            .store(STORE_FILE)
+            .ok()
+            .and_then(|store| store.get(STORE_KEY))
+            .and_then(|value| serde_json::from_value(value).ok())
+            .unwrap_or_default();
+
+        let links = if persisted.user_id == user_id {
+            persisted.links
+        } else {
+            HashMap::new()
+        };
+
+        Self {
+            app: app.clone(),
+            user_id: user_id.to_string(),
+            links,
+        }
+    }
+
+    pub fn get(&self, feed: &str) -> Option<&str> {
+        self.links.get(feed).map(|s| s.as_str())
+    }
+
+    pub fn set(&mut self, feed: &str, link: String) {
+        if self.links.get(feed) != Some(&link) {
+            self.links.insert(feed.to_string(), link);
+            self.save();
+        }
+    }
+
+    pub fn remove(&mut self, feed: &str) {
+        if self.links.remove(feed).is_some() {
+            self.save();
+        }
+    }
+
+    fn save(&self) {
+        let Ok(store) = self.app.store(STORE_FILE) else {
+            return;
+        };
+        let persisted = Persisted {
+            user_id: self.user_id.clone(),
+            links: self.links.clone(),
+        };
+        let value = serde_json::to_value(&persisted).unwrap_or(Value::Null);
+        store.set(STORE_KEY, value);
+        if let Err(e) = store.save() {
+            eprintln!("[sync] Failed to persist delta links: {}", e);
+        }
+    }
+}
@@ -0,0 +1,56 @@
+use std::time::Duration;
+
+/// Polling interval that speeds up on activity, relaxes when idle and backs off on errors.
+#[derive(Debug, Clone)]
+pub struct AdaptiveInterval {
+    min: Duration,
+    base: Duration,
+    max: Duration,
+    current: Duration,
+    failures: u32,
+}
+
+impl AdaptiveInterval {
+    /// Idle polls stretch the interval by this factor, up to `max`.
+    const IDLE_GROWTH: f64 = 1.5;
+
+    pub fn new(min: Duration, base: Duration, max: Duration) -> Self {
+        Self {
+            min,
+            base,
+            max,
+            current: base,
+            failures: 0,
+        }
+    }
+
+    pub fn current(&self) -> Duration {
+        self.current
+    }
+
+    /// Record a successful poll. `active` means something changed.
+    pub fn record_success(&mut self, active: bool) {
+        self.failures = 0;
+        self.current = if active {
+            self.min
+        } else {
+            self.current
+                .max(self.min)
+                .mul_f64(Self::IDLE_GROWTH)
+                .clamp(self.min, self.max)
+        };
+    }
+
+    /// Record a failed poll; doubles the delay from `base` per consecutive failure.
+    pub fn record_failure(&mut self) {
+        self.failures = self.failures.saturating_add(1);
+        let factor = 2u32.saturating_pow(self.failures.min(16));
+        self.current = self.base.saturating_mul(factor).min(self.max);
+    }
+
+    /// Drop back to the base interval (e.g. when the user returns to the app).
+    pub fn reset(&mut self) {
+        self.failures = 0;
+        self.current = self.base;
+    }
+}
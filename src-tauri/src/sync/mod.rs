@@ -0,0 +1,911 @@
+//! Background sync of chats and channels.
+//!
+//! Runs on the Tauri async runtime rather than in the webview, so unread
+//! counts, the tray badge and notifications keep working while the window is
+//! hidden or throttled. Results are pushed to the frontend as `sync:*` events.
+
+mod delta;
+mod interval;
+mod unread;
+
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use futures_util::{stream, StreamExt};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tauri::{AppHandle, Emitter, Manager};
+use tauri_plugin_store::StoreExt;
+use tokio::sync::Notify;
+
+use crate::cache::Cache;
+use crate::graph::types::{Chat, ChatMessage, Page};
+use crate::graph::{GraphClient, GraphError, MAX_BATCH_SIZE};
+use crate::notifications::{MessageRef, Notice, NotificationManager, NotificationTarget, Sound};
+use crate::tray;
+
+use delta::DeltaLinks;
+use interval::AdaptiveInterval;
+use unread::{parse_time, LatestMessage, UnreadTracker};
+
+/// Channel deltas run concurrently so their page requests coalesce into full
+/// `$batch` calls (see [`GraphClient::delta_batched`]); the Graph client's
+/// throttle still applies.
+const CHANNEL_PEEK_CONCURRENCY: usize = MAX_BATCH_SIZE;
+
+/// Delta feed key for messages across all chats.
+const CHAT_FEED: &str = "chats";
+
+#[derive(Debug, Clone)]
+pub struct SyncConfig {
+    /// Starting interval for the chat list poll; each run gets its own copy.
+    pub chat_interval: AdaptiveInterval,
+    /// Starting interval for channel deltas.
+    pub channel_interval: AdaptiveInterval,
+    /// Starting interval for the chat message delta.
+    pub message_interval: AdaptiveInterval,
+    /// How far back the first delta round of a feed reaches.
+    pub initial_window: Duration,
+}
+
+impl Default for SyncConfig {
+    fn default() -> Self {
+        Self {
+            chat_interval: AdaptiveInterval::new(
+                Duration::from_secs(5),
+                Duration::from_secs(15),
+                Duration::from_secs(120),
+            ),
+            channel_interval: AdaptiveInterval::new(
+                Duration::from_secs(10),
+                Duration::from_secs(20),
+                Duration::from_secs(300),
+            ),
+            message_interval: AdaptiveInterval::new(
+                Duration::from_secs(3),
+                Duration::from_secs(5),
+                Duration::from_secs(60),
+            ),
+            initial_window: Duration::from_secs(24 * 60 * 60),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChannelRef {
+    pub team_id: String,
+    pub channel_id: String,
+}
+
+impl ChannelRef {
+    /// Unread key, matching the frontend's `<teamId>:<channelId>` convention.
+    pub fn key(&self) -> String {
+        format!("{}:{}", self.team_id, self.channel_id)
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChatsSynced {
+    pub chats: Vec<Chat>,
+    /// Whether any chat's latest message differs from the previous poll.
+    pub changed: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChannelLatest {
+    pub team_id: String,
+    pub channel_id: String,
+    pub message_id: String,
+    pub created_date_time: String,
+    pub from_user_id: Option<String>,
+}
+
+/// New, edited and deleted messages in one conversation from a delta round.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MessageChanges {
+    /// Chat ID or `<teamId>:<channelId>`.
+    pub key: String,
+    pub chat_id: Option<String>,
+    pub team_id: Option<String>,
+    pub channel_id: Option<String>,
+    /// Added or edited messages.
+    pub messages: Vec<ChatMessage>,
+    /// IDs of deleted messages.
+    pub removed: Vec<String>,
+}
+
+impl MessageChanges {
+    fn new(
+        key: String,
+        chat_id: Option<String>,
+        channel: Option<&ChannelRef>,
+        changes: Vec<ChatMessage>,
+    ) -> Self {
+        let (removed, messages): (Vec<_>, Vec<_>) =
+            changes.into_iter().partition(|m| m.is_removed());
+        Self {
+            key,
+            chat_id,
+            team_id: channel.map(|c| c.team_id.clone()),
+            channel_id: channel.map(|c| c.channel_id.clone()),
+            messages,
+            removed: removed.into_iter().map(|m| m.id).collect(),
+        }
+    }
+}
+
+/// Emitted as `sync:status` whenever it changes.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SyncStatus {
+    pub running: bool,
+    /// Whether chat messages arrive via `sync:messages`; when `false` the
+    /// webview has to poll open chats itself.
+    pub chat_messages: bool,
+    /// Polling is suspended while offline or signed out (see `crate::connectivity`).
+    pub paused: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeltaUnavailable {
+    pub feed: &'static str,
+    pub error: GraphError,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UnreadSnapshot {
+    pub total: u32,
+    /// How many of those conversations mention the user.
+    pub mentions: u32,
+    pub counts: HashMap<String, u32>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Sender {
+    pub id: String,
+    pub name: String,
+}
+
+/// A newly observed unread message. Matches the plugin `PluginMessage` shape.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NewMessage {
+    pub id: String,
+    pub chat_id: String,
+    pub chat_type: String,
+    pub sender: Sender,
+    pub content: String,
+    pub preview: String,
+    pub created_at: String,
+    pub team_id: Option<String>,
+    pub channel_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SyncErrorEvent {
+    pub source: &'static str,
+    pub error: GraphError,
+    pub retry_in_ms: u64,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Source {
+    Chats,
+    Channels,
+    Messages,
+}
+
+impl Source {
+    fn name(self) -> &'static str {
+        match self {
+            Source::Chats => "chats",
+            Source::Channels => "channels",
+            Source::Messages => "messages",
+        }
+    }
+}
+
+#[derive(Default)]
+struct SyncState {
+    user_id: Option<String>,
+    channels: Vec<ChannelRef>,
+    active_key: Option<String>,
+    unread: UnreadTracker,
+    /// Latest message ID per chat from the previous poll.
+    latest_ids: HashMap<String, String>,
+    /// Creation time of the newest message seen per channel.
+    channel_latest: HashMap<String, String>,
+    deltas: Option<DeltaLinks>,
+    /// Set when the tenant doesn't support the all-chats message delta.
+    chat_feed_disabled: bool,
+    /// Conversations seen at least once; the first sighting never notifies.
+    primed: HashSet<String>,
+}
+
+/// Background chat/channel poller. Cheap to clone; held in managed state.
+#[derive(Clone)]
+pub struct SyncEngine {
+    inner: Arc<Inner>,
+}
+
+struct Inner {
+    app: AppHandle,
+    graph: GraphClient,
+    cache: Cache,
+    notifications: NotificationManager,
+    config: SyncConfig,
+    state: Mutex<SyncState>,
+    /// One per [`Source`], so a wake-up that arrives mid-poll isn't lost.
+    wake: [Notify; 3],
+    /// Bumped on every start/stop so loops from a previous run exit.
+    generation: AtomicU64,
+    running: AtomicBool,
+    paused: AtomicBool,
+}
+
+impl SyncEngine {
+    pub fn new(
+        app: AppHandle,
+        graph: GraphClient,
+        cache: Cache,
+        notifications: NotificationManager,
+        config: SyncConfig,
+    ) -> Self {
+        Self {
+            inner: Arc::new(Inner {
+                app,
+                graph,
+                cache,
+                notifications,
+                config,
+                state: Mutex::new(SyncState::default()),
+                wake: [Notify::new(), Notify::new(), Notify::new()],
+                generation: AtomicU64::new(0),
+                paused: AtomicBool::new(false),
+                running: AtomicBool::new(false),
+            }),
+        }
+    }
+
+    /// Start (or restart) syncing on behalf of `user_id`.
+    pub fn start(&self, user_id: String) {
+        {
+            let mut state = self.inner.state.lock().unwrap();
+            if state.user_id.as_deref() != Some(user_id.as_str()) {
+                *state = SyncState {
+                    channels: std::mem::take(&mut state.channels),
+                    active_key: state.active_key.take(),
+                    ..SyncState::default()
+                };
+            }
+            if state.deltas.is_none() {
+                state.deltas = Some(DeltaLinks::load(&self.inner.app, &user_id));
+            }
+            state.user_id = Some(user_id);
+            load_read_markers(&self.inner.app, &mut state.unread);
+            if let Ok(markers) = self.inner.cache.read_markers() {
+                for (key, marker) in markers {
+                    state.unread.mark_read(&key, &marker.timestamp);
+                }
+            }
+        }
+
+        let generation = self.inner.generation.fetch_add(1, Ordering::SeqCst) + 1;
+        self.inner.running.store(true, Ordering::SeqCst);
+        self.inner.end_waits();
+        self.inner.publish_status();
+
+        for source in [Source::Chats, Source::Channels, Source::Messages] {
+            let inner = self.inner.clone();
+            tauri::async_runtime::spawn(async move {
+                inner.run(source, generation).await;
+            });
+        }
+    }
+
+    pub fn stop(&self) {
+        self.inner.generation.fetch_add(1, Ordering::SeqCst);
+        self.inner.running.store(false, Ordering::SeqCst);
+        self.inner.end_waits();
+        self.inner.publish_status();
+    }
+
+    pub fn status(&self) -> SyncStatus {
+        self.inner.status()
+    }
+
+    pub fn set_watched_channels(&self, channels: Vec<ChannelRef>) {
+        let added = {
+            let mut state = self.inner.state.lock().unwrap();
+            let added = channels.iter().any(|c| !state.channels.contains(c));
+            state.channels = channels;
+            added
+        };
+        if added {
+            self.poll_now();
+        }
+    }
+
+    pub fn watched_channels(&self) -> Vec<ChannelRef> {
+        self.inner.state.lock().unwrap().channels.clone()
+    }
+
+    /// The conversation currently open in the UI; it doesn't notify while the window has focus.
+    pub fn set_active_conversation(&self, key: Option<String>) {
+        self.inner.state.lock().unwrap().active_key = key;
+    }
+
+    pub fn mark_read(&self, key: &str, timestamp: &str) {
+        self.inner.state.lock().unwrap().unread.mark_read(key, timestamp);
+        self.inner.notifications.dismiss(key);
+        if let Err(e) = self.inner.cache.set_read_marker(key, timestamp, "local") {
+            eprintln!("[sync] {}", e);
+        }
+        self.inner.publish_unread();
+    }
+
+    /// The user the sync is running for, if started.
+    pub fn user_id(&self) -> Option<String> {
+        self.inner.state.lock().unwrap().user_id.clone()
+    }
+
+    pub fn unread(&self) -> UnreadSnapshot {
+        self.inner.unread_snapshot()
+    }
+
+    /// Interrupt the current wait and poll immediately.
+    pub fn poll_now(&self) {
+        self.inner.wake_all();
+    }
+
+    /// Suspend or resume polling without losing state. Resuming polls
+    /// immediately.
+    pub fn set_paused(&self, paused: bool) {
+        if self.inner.paused.swap(paused, Ordering::SeqCst) == paused {
+            return;
+        }
+        if !paused {
+            self.inner.wake_all();
+        }
+        self.inner.publish_status();
+    }
+}
+
+impl Inner {
+    /// Wake every polling loop: now if it's waiting, else as soon as it next waits.
+    fn wake_all(&self) {
+        for wake in &self.wake {
+            wake.notify_one();
+        }
+    }
+
+    /// Interrupt loops that are waiting so they notice a new generation and
+    /// exit; ones mid-poll check the generation when the request returns.
+    fn end_waits(&self) {
+        for wake in &self.wake {
+            wake.notify_waiters();
+        }
+    }
+
+    async fn run(self: Arc<Self>, source: Source, generation: u64) {
+        let wake = &self.wake[source as usize];
+        let mut interval = match source {
+            Source::Chats => self.config.chat_interval.clone(),
+            Source::Channels => self.config.channel_interval.clone(),
+            Source::Messages => self.config.message_interval.clone(),
+        };
+
+        while self.generation.load(Ordering::SeqCst) == generation {
+            let resumed = wake.notified();
+            if self.paused.load(Ordering::SeqCst) {
+                resumed.await;
+                continue;
+            }
+
+            let result = match source {
+                Source::Chats => self.poll_chats().await,
+                Source::Channels => self.poll_channels().await,
+                Source::Messages => self.poll_chat_messages().await,
+            };
+
+            // Stopped or restarted while the request was in flight
+            if self.generation.load(Ordering::SeqCst) != generation {
+                break;
+            }
+
+            match result {
+                Ok(active) => interval.record_success(active),
+                Err(error) => {
+                    interval.record_failure();
+                    let _ = self.app.emit(
+                        "sync:error",
+                        SyncErrorEvent {
+                            source: source.name(),
+                            error,
+                            retry_in_ms: interval.current().as_millis() as u64,
+                        },
+                    );
+                }
+            }
+
+            tokio::select! {
+                _ = tokio::time::sleep(interval.current()) => {}
+                _ = wake.notified() => interval.reset(),
+            }
+        }
+    }
+
+    /// Returns whether anything changed since the previous poll.
+    async fn poll_chats(&self) -> Result<bool, GraphError> {
+        let page: Page<Chat> = self
+            .graph
+            .get("/me/chats?$expand=lastMessagePreview,members&$top=50")
+            .await?;
+        let chats: Vec<Chat> = page
+            .value
+            .into_iter()
+            .filter(|c| !c.viewpoint.as_ref().is_some_and(|v| v.is_hidden))
+            .collect();
+
+        let mut changed = false;
+        let mut fresh = Vec::new();
+        {
+            let mut state = self.state.lock().unwrap();
+            let Some(me) = state.user_id.clone() else {
+                return Ok(false);
+            };
+
+            for chat in &chats {
+                let Some(preview) = chat.last_message_preview.as_ref() else {
+                    continue;
+                };
+                let from = preview.from.as_ref();
+                let latest = LatestMessage {
+                    id: &preview.id,
+                    created: &preview.created_date_time,
+                    from_user_id: from.and_then(|f| f.user_id()),
+                };
+                let server_read = chat
+                    .viewpoint
+                    .as_ref()
+                    .and_then(|v| v.last_message_read_date_time.as_deref());
+
+                if state.latest_ids.get(&chat.id) != Some(&preview.id) {
+                    changed = true;
+                    state.latest_ids.insert(chat.id.clone(), preview.id.clone());
+                }
+
+                let is_new = state.unread.observe(&chat.id, &latest, server_read, &me);
+                let primed = !state.primed.insert(chat.id.clone());
+                if is_new && primed && !preview.is_deleted {
+                    let body = preview.body.as_ref().map(|b| b.plain_text()).unwrap_or_default();
+                    fresh.push(NewMessage {
+                        id: preview.id.clone(),
+                        chat_id: chat.id.clone(),
+                        chat_type: chat.chat_type.clone(),
+                        sender: Sender {
+                            id: latest.from_user_id.unwrap_or_default().to_string(),
+                            name: from
+                                .and_then(|f| f.display_name())
+                                .unwrap_or("Someone")
+                                .to_string(),
+                        },
+                        preview: preview_text(&body),
+                        content: body,
+                        created_at: preview.created_date_time.clone(),
+                        team_id: None,
+                        channel_id: None,
+                    });
+                }
+            }
+        }
+
+        if self.cache.is_available() {
+            let values: Vec<Value> = chats.iter().filter_map(|c| serde_json::to_value(c).ok()).collect();
+            if let Err(e) = self.cache.put_chats(&values) {
+                eprintln!("[sync] {}", e);
+            }
+        }
+        let _ = self.app.emit("sync:chats", ChatsSynced { chats, changed });
+        self.publish_unread();
+        self.announce(fresh);
+
+        Ok(changed)
+    }
+
+    async fn poll_channels(&self) -> Result<bool, GraphError> {
+        let channels = self.state.lock().unwrap().channels.clone();
+        if channels.is_empty() {
+            return Ok(false);
+        }
+
+        let filter = self.initial_filter();
+        let results: Vec<(ChannelRef, Result<Vec<ChatMessage>, GraphError>)> =
+            stream::iter(channels)
+                .map(|channel| {
+                    let filter = filter.clone();
+                    async move {
+                        let initial = format!(
+                            "/teams/{}/channels/{}/messages/delta?$filter={}",
+                            urlencoding::encode(&channel.team_id),
+                            urlencoding::encode(&channel.channel_id),
+                            filter
+                        );
+                        let feed = format!("channel:{}", channel.key());
+                        let changes = self.run_delta(&feed, &initial, true).await;
+                        (channel, changes)
+                    }
+                })
+                .buffer_unordered(CHANNEL_PEEK_CONCURRENCY)
+                .collect()
+                .await;
+
+        let mut changed = false;
+        let mut first_error = None;
+        let mut succeeded = false;
+        let mut fresh = Vec::new();
+
+        for (channel, changes) in results {
+            let changes = match changes {
+                Ok(changes) => changes,
+                Err(e) => {
+                    first_error.get_or_insert(e);
+                    continue;
+                }
+            };
+            succeeded = true;
+            if changes.is_empty() {
+                continue;
+            }
+            changed = true;
+
+            let key = channel.key();
+            let newest = changes
+                .iter()
+                .filter(|m| m.message_type == "message" && !m.is_removed())
+                .max_by_key(|m| parse_time(&m.created_date_time))
+                .cloned();
+            self.emit_messages(MessageChanges::new(
+                key.clone(),
+                None,
+                Some(&channel),
+                changes,
+            ));
+
+            // Edits to older messages show up in the delta too; only a newer
+            // message moves the channel's unread state
+            let Some(msg) = newest else {
+                continue;
+            };
+            let from_user_id = msg.from.as_ref().and_then(|f| f.user_id()).map(str::to_string);
+            {
+                let mut state = self.state.lock().unwrap();
+                let Some(me) = state.user_id.clone() else {
+                    return Ok(false);
+                };
+                let is_newer = state
+                    .channel_latest
+                    .get(&key)
+                    .is_none_or(|seen| parse_time(&msg.created_date_time) > parse_time(seen));
+                if !is_newer {
+                    continue;
+                }
+                state
+                    .channel_latest
+                    .insert(key.clone(), msg.created_date_time.clone());
+
+                let latest = LatestMessage {
+                    id: &msg.id,
+                    created: &msg.created_date_time,
+                    from_user_id: from_user_id.as_deref(),
+                };
+                let is_new = state.unread.observe(&key, &latest, None, &me);
+                let primed = !state.primed.insert(key.clone());
+                if is_new && primed {
+                    let body = msg.body.as_ref().map(|b| b.plain_text()).unwrap_or_default();
+                    fresh.push(NewMessage {
+                        id: msg.id.clone(),
+                        chat_id: key.clone(),
+                        chat_type: "channel".to_string(),
+                        sender: Sender {
+                            id: from_user_id.clone().unwrap_or_default(),
+                            name: msg
+                                .from
+                                .as_ref()
+                                .and_then(|f| f.display_name())
+                                .unwrap_or("Someone")
+                                .to_string(),
+                        },
+                        preview: preview_text(&body),
+                        content: body,
+                        created_at: msg.created_date_time.clone(),
+                        team_id: Some(channel.team_id.clone()),
+                        channel_id: Some(channel.channel_id.clone()),
+                    });
+                }
+            }
+
+            let _ = self.app.emit(
+                "sync:channel-latest",
+                ChannelLatest {
+                    team_id: channel.team_id,
+                    channel_id: channel.channel_id,
+                    message_id: msg.id,
+                    created_date_time: msg.created_date_time,
+                    from_user_id,
+                },
+            );
+        }
+
+        self.publish_unread();
+        self.announce(fresh);
+
+        // Only back off when every channel failed; one bad channel shouldn't slow the rest
+        match first_error {
+            Some(e) if !succeeded => Err(e),
+            _ => Ok(changed),
+        }
+    }
+
+    /// Pull new, edited and deleted chat messages across all chats.
+    async fn poll_chat_messages(&self) -> Result<bool, GraphError> {
+        if self.state.lock().unwrap().chat_feed_disabled {
+            return Ok(false);
+        }
+
+        let initial = format!("/chats/getAllMessages/delta?$filter={}", self.initial_filter());
+        let changes = match self.run_delta(CHAT_FEED, &initial, false).await {
+            Ok(changes) => changes,
+            // Not licensed or not permitted for this tenant — leave chat messages to
+            // the webview's poller. Anything else is retried with backoff.
+            Err(e) if matches!(e.status, Some(402 | 403)) => {
+                self.state.lock().unwrap().chat_feed_disabled = true;
+                let _ = self.app.emit(
+                    "sync:delta-unavailable",
+                    DeltaUnavailable {
+                        feed: CHAT_FEED,
+                        error: e,
+                    },
+                );
+                self.publish_status();
+                return Ok(false);
+            }
+            Err(e) => return Err(e),
+        };
+
+        let mut by_chat: HashMap<String, Vec<ChatMessage>> = HashMap::new();
+        for message in changes {
+            if let Some(chat_id) = message.chat_id.clone() {
+                by_chat.entry(chat_id).or_default().push(message);
+            }
+        }
+
+        let changed = !by_chat.is_empty();
+        for (chat_id, messages) in by_chat {
+            self.emit_messages(MessageChanges::new(chat_id.clone(), Some(chat_id), None, messages));
+        }
+        Ok(changed)
+    }
+
+    /// Run one delta round for `feed`, resuming from its saved link when there
+    /// is one and starting over from `initial` when the link has expired. With
+    /// `batched`, page requests go out through `$batch` together with those of
+    /// concurrent rounds instead of one request each.
+    async fn run_delta(&self, feed: &str, initial: &str, batched: bool) -> Result<Vec<ChatMessage>, GraphError> {
+        let saved = self
+            .state
+            .lock()
+            .unwrap()
+            .deltas
+            .as_ref()
+            .and_then(|d| d.get(feed))
+            .map(str::to_string);
+
+        let delta = |link: String| async move {
+            if batched {
+                self.graph.delta_batched::<ChatMessage>(&link).await
+            } else {
+                self.graph.delta::<ChatMessage>(&link).await
+            }
+        };
+        let delta = match saved {
+            Some(link) => match delta(link).await {
+                Err(e) if e.requires_resync() => {
+                    if let Some(deltas) = self.state.lock().unwrap().deltas.as_mut() {
+                        deltas.remove(feed);
+                    }
+                    delta(initial.to_string()).await
+                }
+                other => other,
+            },
+            None => delta(initial.to_string()).await,
+        }?;
+
+        if let Some(deltas) = self.state.lock().unwrap().deltas.as_mut() {
+            deltas.set(feed, delta.delta_link);
+        }
+        Ok(delta.items)
+    }
+
+    /// `$filter` value (percent-encoded) limiting the first delta round of a
+    /// feed to the initial window, so it doesn't replay the entire history.
+    fn initial_filter(&self) -> String {
+        let window = chrono::Duration::from_std(self.config.initial_window).unwrap_or_default();
+        let since = (chrono::Utc::now() - window).to_rfc3339_opts(chrono::SecondsFormat::Secs, true);
+        urlencoding::encode(&format!("lastModifiedDateTime gt {}", since)).into_owned()
+    }
+
+    fn emit_messages(&self, changes: MessageChanges) {
+        if self.cache.is_available() {
+            let values: Vec<Value> = changes
+                .messages
+                .iter()
+                .filter_map(|m| serde_json::to_value(m).ok())
+                .collect();
+            let stored = self
+                .cache
+                .put_messages(&changes.key, &values)
+                .and_then(|_| self.cache.remove_messages(&changes.key, &changes.removed));
+            if let Err(e) = stored {
+                eprintln!("[sync] {}", e);
+            }
+        }
+        let _ = self.app.emit("sync:messages", changes);
+    }
+
+    fn status(&self) -> SyncStatus {
+        let running = self.running.load(Ordering::SeqCst);
+        SyncStatus {
+            running,
+            chat_messages: running && !self.state.lock().unwrap().chat_feed_disabled,
+            paused: self.paused.load(Ordering::SeqCst),
+        }
+    }
+
+    fn publish_status(&self) {
+        let _ = self.app.emit("sync:status", self.status());
+    }
+
+    fn unread_snapshot(&self) -> UnreadSnapshot {
+        let state = self.state.lock().unwrap();
+        UnreadSnapshot {
+            total: state.unread.total(),
+            mentions: state.unread.mentions(),
+            counts: state.unread.counts(),
+        }
+    }
+
+    fn publish_unread(&self) {
+        let snapshot = self.unread_snapshot();
+        if let Err(e) = tray::update_unread_badge(&self.app, snapshot.total, snapshot.mentions) {
+            eprintln!("[sync] Failed to update tray badge: {}", e);
+        }
+        let _ = self.app.emit("sync:unread", snapshot);
+    }
+
+    /// Emit `sync:new-message` for each message and raise a native notification
+    /// unless the user is already looking at that conversation.
+    fn announce(&self, messages: Vec<NewMessage>) {
+        if messages.is_empty() {
+            return;
+        }
+
+        let focused = self
+            .app
+            .get_webview_window("main")
+            .map(|w| w.is_visible().unwrap_or(false) && w.is_focused().unwrap_or(false))
+            .unwrap_or(false);
+        let (active, me) = {
+            let state = self.state.lock().unwrap();
+            (state.active_key.clone(), state.user_id.clone())
+        };
+
+        let mut mentioned = false;
+        for message in messages {
+            let viewing = focused && active.as_deref() == Some(message.chat_id.as_str());
+            if !viewing {
+                let message_ref = self.message_ref(&message, me.as_deref());
+                let (mentions_me, direct) = (message_ref.mentions_me, message.chat_type == "oneOnOne");
+                if mentions_me {
+                    self.state.lock().unwrap().unread.note_mention(&message.chat_id);
+                    mentioned = true;
+                }
+                let notice = Notice {
+                    title: message.sender.name.clone(),
+                    body: message.preview.clone(),
+                    target: NotificationTarget::new(
+                        &message.chat_id,
+                        message.team_id.as_deref(),
+                        message.channel_id.as_deref(),
+                    ),
+                    message: Some(message_ref),
+                    sound: Sound::default(),
+                    urgent: false,
+                };
+                // Held-back notifications (quiet hours, mutes) don't bounce either
+                if self.notifications.post(notice).is_none() {
+                    tray::request_attention(&self.app, mentions_me, direct);
+                }
+            }
+            let _ = self.app.emit("sync:new-message", message);
+        }
+        // Mentions are only known once the messages are cached, after the
+        // counts went out
+        if mentioned {
+            self.publish_unread();
+        }
+    }
+
+    /// What the notification policy needs to know about a message. Mentions
+    /// and the sender's email come from the cache, so they're best-effort.
+    fn message_ref(&self, message: &NewMessage, me: Option<&str>) -> MessageRef {
+        let mut sender_email = None;
+        let mut mentions_me = false;
+        if self.cache.is_available() {
+            sender_email = self
+                .cache
+                .members(&message.chat_id)
+                .unwrap_or_default()
+                .into_iter()
+                .find(|m| m.get("userId").and_then(|v| v.as_str()) == Some(message.sender.id.as_str()))
+                .and_then(|m| m.get("email")?.as_str().map(str::to_string));
+            mentions_me = me.is_some_and(|me| {
+                self.cache
+                    .message(&message.chat_id, &message.id)
+                    .ok()
+                    .flatten()
+                    .and_then(|value| serde_json::from_value::<ChatMessage>(value).ok())
+                    .is_some_and(|m| m.mentions_user(me))
+            });
+        }
+
+        MessageRef {
+            id: message.id.clone(),
+            created_at: message.created_at.clone(),
+            sender_id: message.sender.id.clone(),
+            sender_email,
+            chat_type: message.chat_type.clone(),
+            text: message.content.clone(),
+            mentions_me,
+        }
+    }
+}
+
+/// Seed read markers from the frontend's persisted unread state so counts are
+/// right even if the webview never loads.
+fn load_read_markers(app: &AppHandle, unread: &mut UnreadTracker) {
+    let Ok(store) = app.store("unread-state.json") else {
+        return;
+    };
+    let Some(Value::Object(markers)) = store
+        .get("readState")
+        .and_then(|state| state.get("readTimestamps").cloned())
+    else {
+        return;
+    };
+
+    for (key, marker) in markers {
+        if let Some(timestamp) = marker.get("timestamp").and_then(|t| t.as_str()) {
+            unread.mark_read(&key, timestamp);
+        }
+    }
+}
+
+fn preview_text(text: &str) -> String {
+    const MAX_CHARS: usize = 140;
+    if text.chars().count() <= MAX_CHARS {
+        return text.to_string();
+    }
+    let truncated: String = text.chars().take(MAX_CHARS - 1).collect();
+    format!("{}…", truncated.trim_end())
+}
@@ -0,0 +1,147 @@
+use std::collections::{HashMap, HashSet};
+
+use chrono::{DateTime, Utc};
+
+/// The newest message seen in a chat or channel.
+pub struct LatestMessage<'a> {
+    pub id: &'a str,
+    pub created: &'a str,
+    pub from_user_id: Option<&'a str>,
+}
+
+/// Tracks unread state per conversation key (`<chatId>` or `<teamId>:<channelId>`).
+///
+/// Mirrors the frontend's `useUnreadStore`: a conversation is unread when its
+/// latest message from someone else is newer than the effective last-read
+/// time (the later of our local marker and the server's viewpoint).
+#[derive(Debug, Default)]
+pub struct UnreadTracker {
+    read_markers: HashMap<String, String>,
+    last_seen: HashMap<String, String>,
+    counts: HashMap<String, u32>,
+    /// Unread conversations whose unread messages mention the user.
+    mentioned: HashSet<String>,
+}
+
+impl UnreadTracker {
+    /// Record that the user has read `key` up to `timestamp` (ISO 8601).
+    pub fn mark_read(&mut self, key: &str, timestamp: &str) {
+        let newer = self
+            .read_markers
+            .get(key)
+            .is_none_or(|existing| parse_time(timestamp) > parse_time(existing));
+        if newer {
+            self.read_markers.insert(key.to_string(), timestamp.to_string());
+        }
+        self.counts.insert(key.to_string(), 0);
+        self.mentioned.remove(key);
+    }
+
+    /// Record that an unread message in `key` mentions the user; it counts
+    /// until the conversation is read.
+    pub fn note_mention(&mut self, key: &str) {
+        if self.counts.get(key).is_some_and(|&c| c > 0) {
+            self.mentioned.insert(key.to_string());
+        }
+    }
+
+    /// Update `key` from its latest message. Returns `true` when the message is
+    /// a new unread one that hasn't been seen before.
+    pub fn observe(
+        &mut self,
+        key: &str,
+        latest: &LatestMessage<'_>,
+        server_last_read: Option<&str>,
+        me: &str,
+    ) -> bool {
+        let local = self.read_markers.get(key).and_then(|s| parse_time(s));
+        let last_read = local.max(server_last_read.and_then(parse_time));
+
+        let from_self = latest.from_user_id == Some(me);
+        if from_self || parse_time(latest.created) <= last_read {
+            self.counts.insert(key.to_string(), 0);
+            self.mentioned.remove(key);
+            self.last_seen.insert(key.to_string(), latest.id.to_string());
+            return false;
+        }
+
+        let is_new = self.last_seen.get(key).map(|s| s.as_str()) != Some(latest.id);
+        let count = self.counts.entry(key.to_string()).or_insert(0);
+        if is_new {
+            *count += 1;
+            self.last_seen.insert(key.to_string(), latest.id.to_string());
+        }
+        if *count == 0 {
+            *count = 1;
+        }
+        is_new
+    }
+
+    /// Number of conversations with unread messages (what the badge shows).
+    pub fn total(&self) -> u32 {
+        self.counts.values().filter(|&&c| c > 0).count() as u32
+    }
+
+    /// Number of unread conversations that mention the user.
+    pub fn mentions(&self) -> u32 {
+        self.mentioned.len() as u32
+    }
+
+    pub fn counts(&self) -> HashMap<String, u32> {
+        self.counts.clone()
+    }
+}
+
+/// Graph timestamps vary in precision (`...:00Z` vs `...:00.123Z`), so they
+/// can't be compared as strings. Unparseable ones sort before everything.
+pub(super) fn parse_time(timestamp: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(timestamp).ok().map(|t| t.with_timezone(&Utc))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message<'a>(id: &'a str, created: &'a str) -> LatestMessage<'a> {
+        LatestMessage {
+            id,
+            created,
+            from_user_id: Some("someone"),
+        }
+    }
+
+    #[test]
+    fn compares_timestamps_of_different_precision() {
+        let mut tracker = UnreadTracker::default();
+        tracker.mark_read("chat", "2024-05-01T10:00:00Z");
+
+        // As strings, ".500Z" sorts before "Z"
+        assert!(tracker.observe("chat", &message("1", "2024-05-01T10:00:00.500Z"), None, "me"));
+        assert_eq!(tracker.total(), 1);
+
+        tracker.mark_read("chat", "2024-05-01T10:00:00.9Z");
+        assert!(!tracker.observe("chat", &message("1", "2024-05-01T10:00:00.500Z"), None, "me"));
+        assert_eq!(tracker.total(), 0);
+    }
+
+    #[test]
+    fn keeps_the_later_read_marker() {
+        let mut tracker = UnreadTracker::default();
+        tracker.mark_read("chat", "2024-05-01T10:00:00.250Z");
+        tracker.mark_read("chat", "2024-05-01T10:00:00Z");
+
+        assert!(!tracker.observe("chat", &message("1", "2024-05-01T10:00:00.100Z"), None, "me"));
+    }
+
+    #[test]
+    fn uses_the_later_of_local_and_server_read_times() {
+        let mut tracker = UnreadTracker::default();
+        tracker.mark_read("chat", "2024-05-01T09:00:00Z");
+
+        // 10:00Z and 09:30Z on the server's clock
+        let latest = message("1", "2024-05-01T10:00:00Z");
+        assert!(!tracker.observe("chat", &latest, Some("2024-05-01T11:00:00.000+01:00"), "me"));
+        let latest = message("2", "2024-05-01T10:00:00Z");
+        assert!(tracker.observe("chat", &latest, Some("2024-05-01T10:30:00.000+01:00"), "me"));
+    }
+}
@@ -0,0 +1,223 @@
+//! Unread count drawn onto the tray icon at runtime: a pill in the top-right
+//! corner holding 1–99 or "99+", with a thin transparent gap cut around it so
+//! it reads against any menu bar. The icon is rendered at the display's pixel
+//! density rather than scaled by the OS, which keeps the digits crisp.
+
+use tauri::image::Image;
+
+/// Logical size of a tray icon; the bundled icon is drawn at 2x.
+const ICON_POINTS: f64 = 22.0;
+const MIN_SIZE: u32 = 16;
+const MAX_SIZE: u32 = 88;
+
+const UNREAD_COLOR: [f64; 3] = [0x5b as f64, 0x5f as f64, 0xc7 as f64];
+const MENTION_COLOR: [f64; 3] = [0xd1 as f64, 0x34 as f64, 0x38 as f64];
+const TEXT_COLOR: [f64; 3] = [255.0, 255.0, 255.0];
+
+/// Pill height relative to the icon.
+const BADGE_HEIGHT: f64 = 0.6;
+
+/// 3×5 glyphs for `0`–`9` and `+`; each row's low three bits, left to right.
+const GLYPHS: [[u8; 5]; 11] = [
+    [0b111, 0b101, 0b101, 0b101, 0b111],
+    [0b010, 0b110, 0b010, 0b010, 0b111],
+    [0b111, 0b001, 0b111, 0b100, 0b111],
+    [0b111, 0b001, 0b011, 0b001, 0b111],
+    [0b101, 0b101, 0b111, 0b001, 0b001],
+    [0b111, 0b100, 0b111, 0b001, 0b111],
+    [0b111, 0b100, 0b111, 0b101, 0b111],
+    [0b111, 0b001, 0b010, 0b010, 0b010],
+    [0b111, 0b101, 0b111, 0b101, 0b111],
+    [0b111, 0b101, 0b111, 0b001, 0b111],
+    [0b000, 0b010, 0b111, 0b010, 0b000],
+];
+
+/// `base` with `count` drawn on it, sized for a display with `scale` pixels
+/// per point. `mention` picks the mention color.
+pub fn render(base: &Image<'_>, count: u32, mention: bool, scale: f64) -> Image<'static> {
+    let size = ((ICON_POINTS * scale).round() as u32).clamp(MIN_SIZE, MAX_SIZE);
+    let mut pixels = resize(base.rgba(), base.width(), base.height(), size);
+    draw(&mut pixels, size, count, mention, (size as f64 * BADGE_HEIGHT).round());
+    Image::new_owned(unpremultiply(&pixels), size, size)
+}
+
+/// Just the badge, filling an icon of `size` pixels (Windows taskbar
+/// overlays).
+#[cfg_attr(not(windows), allow(dead_code))]
+pub fn overlay(count: u32, mention: bool, size: u32) -> Image<'static> {
+    let mut pixels = vec![[0.0; 4]; (size * size) as usize];
+    draw(&mut pixels, size, count, mention, size as f64);
+    Image::new_owned(unpremultiply(&pixels), size, size)
+}
+
+/// Draw a pill `height` pixels tall into the top-right corner of a square
+/// icon of premultiplied pixels, with `count` in it.
+fn draw(pixels: &mut [[f64; 4]], size: u32, count: u32, mention: bool, height: f64) {
+    let glyphs = glyphs(&label(count));
+
+    // Five glyph rows plus a unit of padding above and below, narrowed if
+    // the label wouldn't fit across
+    let columns = glyphs.len() as f64 * 4.0 - 1.0;
+    let unit = (height / 7.0).min(size as f64 / (columns + 1.0)).floor().max(1.0);
+    let text_width = columns * unit;
+    let width = (text_width + height * 0.6).max(height).min(size as f64);
+    let radius = height / 2.0;
+    let (left, right) = (size as f64 - width, size as f64);
+    let center_y = radius;
+    let gap = (unit / 2.0).max(1.0);
+
+    let color = color(mention);
+    for y in 0..size {
+        for x in 0..size {
+            let (px, py) = (x as f64 + 0.5, y as f64 + 0.5);
+            // Distance to the pill's outline (negative inside)
+            let nearest_x = px.clamp(left + radius, right - radius);
+            let distance = ((px - nearest_x).powi(2) + (py - center_y).powi(2)).sqrt() - radius;
+
+            let pixel = &mut pixels[(y * size + x) as usize];
+            let cut = (distance - gap + 0.5).clamp(0.0, 1.0);
+            for channel in pixel.iter_mut() {
+                *channel *= cut;
+            }
+            let coverage = (0.5 - distance).clamp(0.0, 1.0);
+            blend(pixel, color, coverage);
+        }
+    }
+
+    let origin_x = ((left + right) / 2.0 - text_width / 2.0).round() as i64;
+    let origin_y = (center_y - 2.5 * unit).round() as i64;
+    let unit = unit as i64;
+    for (index, glyph) in glyphs.iter().enumerate() {
+        let glyph_x = origin_x + index as i64 * 4 * unit;
+        for (row, bits) in GLYPHS[*glyph].iter().enumerate() {
+            for column in 0..3 {
+                if bits & (0b100 >> column) == 0 {
+                    continue;
+                }
+                for dy in 0..unit {
+                    for dx in 0..unit {
+                        let x = glyph_x + column * unit + dx;
+                        let y = origin_y + row as i64 * unit + dy;
+                        if (0..size as i64).contains(&x) && (0..size as i64).contains(&y) {
+                            blend(&mut pixels[(y as u32 * size + x as u32) as usize], TEXT_COLOR, 1.0);
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// What the pill says: the count, or "99+" once it needs three digits.
+fn label(count: u32) -> String {
+    if count > 99 {
+        "99+".to_string()
+    } else {
+        count.to_string()
+    }
+}
+
+/// Indices into [`GLYPHS`] spelling out `label`.
+fn glyphs(label: &str) -> Vec<usize> {
+    label.chars().map(|c| c.to_digit(10).map_or(10, |d| d as usize)).collect()
+}
+
+fn color(mention: bool) -> [f64; 3] {
+    if mention {
+        MENTION_COLOR
+    } else {
+        UNREAD_COLOR
+    }
+}
+
+/// Bilinear resize of straight RGBA into premultiplied float pixels.
+fn resize(rgba: &[u8], width: u32, height: u32, size: u32) -> Vec<[f64; 4]> {
+    let source: Vec<[f64; 4]> = rgba
+        .chunks_exact(4)
+        .map(|p| {
+            let alpha = p[3] as f64 / 255.0;
+            [p[0] as f64 * alpha, p[1] as f64 * alpha, p[2] as f64 * alpha, p[3] as f64]
+        })
+        .collect();
+    let at = |x: i64, y: i64| source[(y.clamp(0, height as i64 - 1) * width as i64 + x.clamp(0, width as i64 - 1)) as usize];
+
+    let (scale_x, scale_y) = (width as f64 / size as f64, height as f64 / size as f64);
+    let mut out = Vec::with_capacity((size * size) as usize);
+    for y in 0..size {
+        let sy = (y as f64 + 0.5) * scale_y - 0.5;
+        let (y0, fy) = (sy.floor() as i64, sy - sy.floor());
+        for x in 0..size {
+            let sx = (x as f64 + 0.5) * scale_x - 0.5;
+            let (x0, fx) = (sx.floor() as i64, sx - sx.floor());
+            let (a, b, c, d) = (at(x0, y0), at(x0 + 1, y0), at(x0, y0 + 1), at(x0 + 1, y0 + 1));
+            let mut pixel = [0.0; 4];
+            for i in 0..4 {
+                pixel[i] = (a[i] * (1.0 - fx) + b[i] * fx) * (1.0 - fy) + (c[i] * (1.0 - fx) + d[i] * fx) * fy;
+            }
+            out.push(pixel);
+        }
+    }
+    out
+}
+
+/// Paint `color` over a premultiplied pixel with the given coverage.
+fn blend(pixel: &mut [f64; 4], color: [f64; 3], coverage: f64) {
+    for i in 0..3 {
+        pixel[i] = color[i] * coverage + pixel[i] * (1.0 - coverage);
+    }
+    pixel[3] = 255.0 * coverage + pixel[3] * (1.0 - coverage);
+}
+
+fn unpremultiply(pixels: &[[f64; 4]]) -> Vec<u8> {
+    pixels
+        .iter()
+        .flat_map(|p| {
+            let alpha = p[3] / 255.0;
+            let channel = |c: f64| if alpha > 0.0 { (c / alpha).round().clamp(0.0, 255.0) as u8 } else { 0 };
+            [channel(p[0]), channel(p[1]), channel(p[2]), p[3].round().clamp(0.0, 255.0) as u8]
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn labels_clamp_past_two_digits() {
+        assert_eq!(label(1), "1");
+        assert_eq!(label(42), "42");
+        assert_eq!(label(99), "99");
+        assert_eq!(label(100), "99+");
+        assert_eq!(label(u32::MAX), "99+");
+    }
+
+    #[test]
+    fn labels_map_to_glyphs() {
+        assert_eq!(glyphs("7"), [7]);
+        assert_eq!(glyphs("10"), [1, 0]);
+        assert_eq!(glyphs("99+"), [9, 9, 10]);
+    }
+
+    #[test]
+    fn mentions_pick_the_mention_color() {
+        assert_eq!(color(true), MENTION_COLOR);
+        assert_eq!(color(false), UNREAD_COLOR);
+    }
+
+    #[test]
+    fn pill_is_filled_with_the_picked_color() {
+        const SIZE: u32 = 32;
+        // Left of the digit, inside the pill
+        let fill = |mention| {
+            let mut pixels = vec![[0.0; 4]; (SIZE * SIZE) as usize];
+            draw(&mut pixels, SIZE, 1, mention, SIZE as f64);
+            pixels[(16 * SIZE + 4) as usize]
+        };
+        for mention in [false, true] {
+            let [r, g, b, a] = fill(mention);
+            assert_eq!([r, g, b], color(mention));
+            assert_eq!(a, 255.0);
+        }
+    }
+}
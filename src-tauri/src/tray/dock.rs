@@ -0,0 +1,95 @@
+//! The app's own entry in the dock (macOS), taskbar (Windows) or launcher
+//! (Linux): the unread count as a badge, and a bounce or flash when an
+//! @mention or direct message comes in while the window is in the background.
+//!
+//! Stored in `settings.json` under `dockSettings`.
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager, UserAttentionType};
+
+use crate::settings;
+
+const SETTINGS_KEY: &str = "dockSettings";
+
+/// Windows draws taskbar overlays at 16×16 logical pixels.
+#[cfg(windows)]
+const OVERLAY_POINTS: f64 = 16.0;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct DockSettings {
+    /// Show the unread count on the dock or taskbar icon.
+    pub badge_count: bool,
+    /// Bounce the dock icon or flash the taskbar button for @mentions.
+    pub attention_on_mention: bool,
+    /// The same for messages in 1:1 chats.
+    pub attention_on_direct: bool,
+}
+
+impl Default for DockSettings {
+    fn default() -> Self {
+        Self {
+            badge_count: true,
+            attention_on_mention: true,
+            attention_on_direct: true,
+        }
+    }
+}
+
+impl DockSettings {
+    pub fn load(app: &AppHandle) -> Self {
+        settings::load(app, SETTINGS_KEY).unwrap_or_default()
+    }
+
+    pub fn save(&self, app: &AppHandle) -> Result<(), String> {
+        settings::save(app, SETTINGS_KEY, self)
+    }
+}
+
+/// Show `count` on the app icon, or clear it when zero or turned off. On
+/// Windows, which has no badge, it's drawn as a taskbar overlay instead.
+pub fn update_badge(app: &AppHandle, count: u32, mentions: u32) -> Result<(), String> {
+    let Some(window) = app.get_webview_window("main") else {
+        return Ok(());
+    };
+    let count = if DockSettings::load(app).badge_count { count } else { 0 };
+
+    #[cfg(windows)]
+    {
+        let overlay = (count > 0).then(|| {
+            let scale = window.scale_factor().unwrap_or(1.0);
+            super::badge::overlay(count, mentions > 0, (OVERLAY_POINTS * scale).round() as u32)
+        });
+        window
+            .set_overlay_icon(overlay)
+            .map_err(|e| format!("Failed to set taskbar overlay: {}", e))
+    }
+    #[cfg(not(windows))]
+    {
+        let _ = mentions;
+        window
+            .set_badge_count((count > 0).then_some(count as i64))
+            .map_err(|e| format!("Failed to set badge count: {}", e))
+    }
+}
+
+/// Ask for the user's attention about a new message, if the settings want it
+/// for this kind and the window isn't already in front.
+pub fn request_attention(app: &AppHandle, mentions_me: bool, direct: bool) {
+    let settings = DockSettings::load(app);
+    let wanted = (mentions_me && settings.attention_on_mention) || (direct && settings.attention_on_direct);
+    if !wanted {
+        return;
+    }
+    let Some(window) = app.get_webview_window("main") else {
+        return;
+    };
+    if window.is_focused().unwrap_or(false) {
+        return;
+    }
+    // Bounces the dock icon once on macOS; flashes the taskbar button until
+    // the window is activated on Windows
+    if let Err(e) = window.request_user_attention(Some(UserAttentionType::Informational)) {
+        eprintln!("[tray] Failed to request attention: {}", e);
+    }
+}
@@ -0,0 +1,267 @@
+mod badge;
+mod dock;
+mod recent;
+
+use std::sync::Mutex;
+
+use tauri::{
+    command,
+    image::Image,
+    menu::{CheckMenuItem, Menu, MenuItem, PredefinedMenuItem, Submenu},
+    tray::{MouseButton, MouseButtonState, TrayIconBuilder, TrayIconEvent},
+    AppHandle, Emitter, Manager, Wry,
+};
+
+use crate::presence::{PreferredStatus, PresenceService};
+
+pub use dock::{request_attention, DockSettings};
+pub use recent::UnreadChat;
+
+use recent::UnreadSection;
+
+/// What the tooltip currently reflects; both inputs update independently.
+struct TrayStatus {
+    unread: u32,
+    /// How many of the unread conversations mention the user.
+    mentions: u32,
+    /// Short connectivity note such as "Offline", `None` when all is well.
+    connectivity: Option<&'static str>,
+}
+
+static STATUS: Mutex<TrayStatus> = Mutex::new(TrayStatus {
+    unread: 0,
+    mentions: 0,
+    connectivity: None,
+});
+
+/// The check items of the "Status" submenu, kept so they can follow the
+/// user's presence.
+struct StatusItems(Vec<(PreferredStatus, CheckMenuItem<Wry>)>);
+
+fn load_tray_icon() -> Image<'static> {
+    Image::from_bytes(include_bytes!("../../icons/tray-icon.png"))
+        .expect("Failed to load tray icon")
+}
+
+/// Pixels per point of the display the tray is most likely on.
+fn display_scale(app: &AppHandle) -> f64 {
+    app.primary_monitor()
+        .ok()
+        .flatten()
+        .map_or(1.0, |monitor| monitor.scale_factor())
+}
+
+pub fn create_tray(app: &tauri::App) -> Result<(), Box<dyn std::error::Error>> {
+    let show = MenuItem::with_id(app, "show", "Show Teamy", true, None::<&str>)?;
+    let hide = MenuItem::with_id(app, "hide", "Hide", true, None::<&str>)?;
+    let separator = PredefinedMenuItem::separator(app)?;
+    let status_items = PreferredStatus::ALL
+        .into_iter()
+        .map(|status| {
+            let id = format!("presence:{}", status.id());
+            CheckMenuItem::with_id(app, id, status.label(), true, false, None::<&str>).map(|item| (status, item))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let reset_status = MenuItem::with_id(app, "presence:reset", "Reset status", true, None::<&str>)?;
+    let status_separator = PredefinedMenuItem::separator(app)?;
+    let status = Submenu::with_id(app, "presence", "Status", true)?;
+    for (_, item) in &status_items {
+        status.append(item)?;
+    }
+    status.append(&status_separator)?;
+    status.append(&reset_status)?;
+    app.manage(StatusItems(status_items));
+    let separator_status = PredefinedMenuItem::separator(app)?;
+    let settings = MenuItem::with_id(app, "settings", "Settings...", true, None::<&str>)?;
+    let separator2 = PredefinedMenuItem::separator(app)?;
+    let quit = MenuItem::with_id(app, "quit", "Quit Teamy", true, None::<&str>)?;
+
+    let menu = Menu::with_items(
+        app,
+        &[&show, &hide, &separator, &status, &separator_status, &settings, &separator2, &quit],
+    )?;
+    app.manage(UnreadSection::new(menu.clone()));
+
+    TrayIconBuilder::with_id("main-tray")
+        .icon(load_tray_icon())
+        .icon_as_template(true)
+        .tooltip("Teamy")
+        .menu(&menu)
+        .show_menu_on_left_click(false)
+        .on_menu_event(|app, event| match event.id.as_ref() {
+            "show" => {
+                if let Some(window) = app.get_webview_window("main") {
+                    let _ = window.show();
+                    let _ = window.set_focus();
+                }
+            }
+            "hide" => {
+                if let Some(window) = app.get_webview_window("main") {
+                    let _ = window.hide();
+                }
+            }
+            "settings" => {
+                if let Some(window) = app.get_webview_window("main") {
+                    let _ = window.show();
+                    let _ = window.set_focus();
+                    // Emit event so frontend can navigate to settings
+                    let _ = window.emit("navigate", "/settings");
+                }
+            }
+            "quit" => {
+                app.exit(0);
+            }
+            id if id.starts_with("presence:") => set_status_from_menu(app, &id["presence:".len()..]),
+            id if id.starts_with(recent::ID_PREFIX) => recent::on_menu_event(app, &id[recent::ID_PREFIX.len()..]),
+            _ => {}
+        })
+        .on_tray_icon_event(|tray, event| {
+            if let TrayIconEvent::Click {
+                button: MouseButton::Left,
+                button_state: MouseButtonState::Up,
+                ..
+            } = event
+            {
+                let app = tray.app_handle();
+                if let Some(window) = app.get_webview_window("main") {
+                    let _ = window.show();
+                    let _ = window.set_focus();
+                }
+            }
+        })
+        .build(app)?;
+
+    Ok(())
+}
+
+#[command]
+pub async fn set_tray_unread_count(app: AppHandle, count: u32, mentions: Option<u32>) -> Result<(), String> {
+    update_unread_badge(&app, count, mentions.unwrap_or(0))
+}
+
+/// List the unread conversations, most recent first, at the top of the tray
+/// menu; an empty list removes the section.
+#[command]
+pub async fn set_tray_unread_chats(app: AppHandle, chats: Vec<UnreadChat>) -> Result<(), String> {
+    let section = app
+        .try_state::<UnreadSection>()
+        .ok_or_else(|| "Tray menu not found".to_string())?;
+    section.update(&app, &chats)
+}
+
+#[command]
+pub fn get_dock_settings(app: AppHandle) -> DockSettings {
+    DockSettings::load(&app)
+}
+
+#[command]
+pub fn set_dock_settings(app: AppHandle, settings: DockSettings) -> Result<(), String> {
+    settings.save(&app)?;
+    let (unread, mentions) = {
+        let status = STATUS.lock().unwrap();
+        (status.unread, status.mentions)
+    };
+    dock::update_badge(&app, unread, mentions)
+}
+
+/// Draw `count` unread conversations onto the tray icon, in the mention color
+/// when `mentions` of them mention the user, note both in the tooltip, and
+/// show the count on the dock or taskbar icon.
+pub fn update_unread_badge(app: &AppHandle, count: u32, mentions: u32) -> Result<(), String> {
+    let mentions = mentions.min(count);
+    // The tray shouldn't go stale over a dock that can't be badged
+    if let Err(e) = dock::update_badge(app, count, mentions) {
+        eprintln!("[tray] Failed to update dock badge: {}", e);
+    }
+
+    let tray = app
+        .tray_by_id("main-tray")
+        .ok_or_else(|| "Tray icon not found".to_string())?;
+
+    let tooltip = {
+        let mut status = STATUS.lock().unwrap();
+        status.unread = count;
+        status.mentions = mentions;
+        tooltip(&status)
+    };
+    tray.set_tooltip(Some(&tooltip))
+        .map_err(|e| format!("Failed to set tooltip: {}", e))?;
+
+    if count == 0 {
+        tray.set_icon(Some(load_tray_icon()))
+            .map_err(|e| format!("Failed to set tray icon: {}", e))?;
+        tray.set_icon_as_template(true)
+            .map_err(|e| format!("Failed to set template: {}", e))?;
+    } else {
+        // Badge icon is non-template so the badge stays colored
+        let icon = badge::render(&load_tray_icon(), count, mentions > 0, display_scale(app));
+        tray.set_icon_as_template(false)
+            .map_err(|e| format!("Failed to set template: {}", e))?;
+        tray.set_icon(Some(icon))
+            .map_err(|e| format!("Failed to set tray icon: {}", e))?;
+    }
+
+    Ok(())
+}
+
+/// Show a connectivity note (e.g. "Offline") in the tooltip, or clear it with `None`.
+pub fn update_connectivity(app: &AppHandle, note: Option<&'static str>) -> Result<(), String> {
+    let tray = app
+        .tray_by_id("main-tray")
+        .ok_or_else(|| "Tray icon not found".to_string())?;
+    let tooltip = {
+        let mut status = STATUS.lock().unwrap();
+        status.connectivity = note;
+        tooltip(&status)
+    };
+    tray.set_tooltip(Some(&tooltip))
+        .map_err(|e| format!("Failed to set tooltip: {}", e))
+}
+
+fn tooltip(status: &TrayStatus) -> String {
+    let unread = match status.mentions {
+        0 => format!("{} unread", status.unread),
+        1 => format!("{} unread (1 mention)", status.unread),
+        mentions => format!("{} unread ({} mentions)", status.unread, mentions),
+    };
+    match (status.connectivity, status.unread) {
+        (None, 0) => "Teamy".to_string(),
+        (None, _) => format!("Teamy — {}", unread),
+        (Some(note), 0) => format!("Teamy — {}", note),
+        (Some(note), _) => format!("Teamy — {} · {}", note, unread),
+    }
+}
+
+/// Tick the status matching the user's presence, or none if it isn't one of
+/// the offered choices (e.g. "In a meeting").
+pub fn update_presence(app: &AppHandle, current: Option<PreferredStatus>) {
+    let Some(items) = app.try_state::<StatusItems>() else {
+        return;
+    };
+    for (status, item) in &items.0 {
+        let _ = item.set_checked(Some(*status) == current);
+    }
+}
+
+fn set_status_from_menu(app: &AppHandle, id: &str) {
+    let status = PreferredStatus::from_id(id);
+    if status.is_none() && id != "reset" {
+        return;
+    }
+    // Check items toggle themselves; show the choice until Graph confirms it
+    update_presence(app, status);
+
+    let app = app.clone();
+    let presence = app.state::<PresenceService>().inner().clone();
+    tauri::async_runtime::spawn(async move {
+        let result = match status {
+            Some(status) => presence.set_preferred(status, None).await,
+            None => presence.clear_preferred().await,
+        };
+        if let Err(e) = result {
+            eprintln!("[tray] Failed to set status: {}", e);
+            let current = presence.mine().and_then(|p| PreferredStatus::from_availability(&p.availability));
+            update_presence(&app, current);
+        }
+    });
+}
@@ -0,0 +1,253 @@
+//! The unread section at the top of the tray menu: the most recent unread
+//! chats and channels, each opening its conversation, and "Mark all as read".
+//! The webview pushes the list through `set_tray_unread_chats` whenever its
+//! unread store changes.
+
+use std::sync::Mutex;
+
+use serde::Deserialize;
+use tauri::{
+    menu::{IsMenuItem, Menu, MenuItem, MenuItemKind, PredefinedMenuItem},
+    AppHandle, Emitter, Manager, Wry,
+};
+
+use crate::notifications::{self, NotificationTarget};
+
+/// How many conversations the menu lists.
+const MAX_ITEMS: usize = 5;
+/// Longer labels are cut off with an ellipsis.
+const MAX_LABEL_CHARS: usize = 60;
+
+pub const ID_PREFIX: &str = "unread:";
+const MARK_ALL_ID: &str = "mark-all";
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UnreadChat {
+    pub chat_id: String,
+    /// Set both for channels.
+    pub team_id: Option<String>,
+    pub channel_id: Option<String>,
+    /// Chat or channel name.
+    pub title: String,
+    /// Who sent the latest message.
+    #[serde(default)]
+    pub sender: Option<String>,
+    #[serde(default)]
+    pub preview: Option<String>,
+}
+
+/// The tray menu and the items currently in its unread section.
+pub struct UnreadSection {
+    menu: Menu<Wry>,
+    state: Mutex<SectionState>,
+}
+
+#[derive(Default)]
+struct SectionState {
+    /// The section's items, its trailing separator included.
+    items: Vec<MenuItemKind<Wry>>,
+    /// Every unread conversation, most recent first; only the first few are
+    /// listed but "Mark all as read" covers them all.
+    targets: Vec<NotificationTarget>,
+}
+
+impl UnreadSection {
+    pub fn new(menu: Menu<Wry>) -> Self {
+        Self {
+            menu,
+            state: Mutex::new(SectionState::default()),
+        }
+    }
+
+    /// Replace the section with `chats`, most recent first; empty hides it.
+    pub fn update(&self, app: &AppHandle, chats: &[UnreadChat]) -> Result<(), String> {
+        let mut state = self.state.lock().unwrap();
+        for item in state.items.drain(..) {
+            self.menu
+                .remove(&item)
+                .map_err(|e| format!("Failed to update tray menu: {}", e))?;
+        }
+        state.targets = chats
+            .iter()
+            .map(|chat| NotificationTarget::new(&chat.chat_id, chat.team_id.as_deref(), chat.channel_id.as_deref()))
+            .collect();
+        if chats.is_empty() {
+            return Ok(());
+        }
+
+        let mut items = Vec::new();
+        for entry in entries(chats, &state.targets) {
+            items.push(MenuItemKind::MenuItem(menu_item(app, entry.id, entry.text, entry.enabled)?));
+        }
+        let separator = PredefinedMenuItem::separator(app).map_err(|e| format!("Failed to update tray menu: {}", e))?;
+        items.push(MenuItemKind::Predefined(separator));
+
+        let refs: Vec<&dyn IsMenuItem<Wry>> = items.iter().map(|item| item as &dyn IsMenuItem<Wry>).collect();
+        self.menu
+            .insert_items(&refs, 0)
+            .map_err(|e| format!("Failed to update tray menu: {}", e))?;
+        state.items = items;
+        Ok(())
+    }
+}
+
+#[derive(Debug, PartialEq)]
+struct Entry {
+    id: String,
+    text: String,
+    enabled: bool,
+}
+
+/// The section's items for `chats` and their `targets`: the most recent few,
+/// a count of the rest, and "Mark all as read".
+fn entries(chats: &[UnreadChat], targets: &[NotificationTarget]) -> Vec<Entry> {
+    let entry = |id: String, text: String, enabled| Entry {
+        id: format!("{}{}", ID_PREFIX, id),
+        text,
+        enabled,
+    };
+    let mut entries: Vec<Entry> = chats
+        .iter()
+        .zip(targets)
+        .take(MAX_ITEMS)
+        .map(|(chat, target)| entry(target.key(), label(chat), true))
+        .collect();
+    if chats.len() > MAX_ITEMS {
+        let more = format!("{} more unread", chats.len() - MAX_ITEMS);
+        entries.push(entry("more".to_string(), more, false));
+    }
+    entries.push(entry(MARK_ALL_ID.to_string(), "Mark all as read".to_string(), true));
+    entries
+}
+
+fn menu_item(app: &AppHandle, id: String, text: String, enabled: bool) -> Result<MenuItem<Wry>, String> {
+    MenuItem::with_id(app, id, text, enabled, None::<&str>).map_err(|e| format!("Failed to update tray menu: {}", e))
+}
+
+/// Open the conversation behind a section item, or mark everything read.
+pub fn on_menu_event(app: &AppHandle, id: &str) {
+    let Some(section) = app.try_state::<UnreadSection>() else {
+        return;
+    };
+    if id == MARK_ALL_ID {
+        let targets = section.state.lock().unwrap().targets.clone();
+        for target in &targets {
+            notifications::mark_read(app, target);
+        }
+        // Don't wait for the webview to catch up
+        if let Err(e) = section.update(app, &[]) {
+            eprintln!("[tray] {}", e);
+        }
+        return;
+    }
+
+    let target = section.state.lock().unwrap().targets.iter().find(|t| t.key() == id).cloned();
+    let (Some(target), Some(window)) = (target, app.get_webview_window("main")) else {
+        return;
+    };
+    let _ = window.show();
+    let _ = window.set_focus();
+    let _ = window.emit("navigate", target.route());
+}
+
+/// "Title — Sender: preview" on one line, trimmed to fit a menu.
+fn label(chat: &UnreadChat) -> String {
+    let sender = chat.sender.as_deref().map(str::trim).filter(|s| !s.is_empty() && *s != chat.title);
+    let preview = chat
+        .preview
+        .as_deref()
+        .map(|p| p.split_whitespace().collect::<Vec<_>>().join(" "))
+        .filter(|p| !p.is_empty());
+    let text = match (sender, preview) {
+        (Some(sender), Some(preview)) => format!("{} — {}: {}", chat.title, sender, preview),
+        (None, Some(preview)) => format!("{} — {}", chat.title, preview),
+        (Some(sender), None) => format!("{} — {}", chat.title, sender),
+        (None, None) => chat.title.clone(),
+    };
+
+    let mut label: String = text.chars().take(MAX_LABEL_CHARS).collect();
+    if text.chars().count() > MAX_LABEL_CHARS {
+        label.pop();
+        label.push('…');
+    }
+    // A single `&` marks a keyboard shortcut in menu labels
+    label.replace('&', "&&")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chat(id: &str, sender: Option<&str>, preview: Option<&str>) -> UnreadChat {
+        UnreadChat {
+            chat_id: id.to_string(),
+            team_id: None,
+            channel_id: None,
+            title: format!("Chat {}", id),
+            sender: sender.map(str::to_string),
+            preview: preview.map(str::to_string),
+        }
+    }
+
+    fn targets(chats: &[UnreadChat]) -> Vec<NotificationTarget> {
+        chats
+            .iter()
+            .map(|chat| NotificationTarget::new(&chat.chat_id, chat.team_id.as_deref(), chat.channel_id.as_deref()))
+            .collect()
+    }
+
+    #[test]
+    fn lists_the_most_recent_chats_in_order() {
+        let mut chats = vec![chat("1", None, None), chat("2", None, None)];
+        chats[1].team_id = Some("team".to_string());
+        chats[1].channel_id = Some("general".to_string());
+
+        let entries = entries(&chats, &targets(&chats));
+        let ids: Vec<&str> = entries.iter().map(|e| e.id.as_str()).collect();
+        assert_eq!(ids, ["unread:1", "unread:team:general", "unread:mark-all"]);
+        assert_eq!(entries[0].text, "Chat 1");
+        assert!(entries.iter().all(|e| e.enabled));
+    }
+
+    #[test]
+    fn counts_the_chats_that_dont_fit() {
+        let chats: Vec<UnreadChat> = (1..=MAX_ITEMS + 2).map(|i| chat(&i.to_string(), None, None)).collect();
+        let entries = entries(&chats, &targets(&chats));
+        assert_eq!(entries.len(), MAX_ITEMS + 2);
+        assert_eq!(entries[MAX_ITEMS - 1].id, format!("unread:{}", MAX_ITEMS));
+        assert_eq!(
+            entries[MAX_ITEMS],
+            Entry {
+                id: "unread:more".to_string(),
+                text: "2 more unread".to_string(),
+                enabled: false,
+            }
+        );
+        assert_eq!(entries[MAX_ITEMS + 1].text, "Mark all as read");
+    }
+
+    #[test]
+    fn labels_show_sender_and_preview() {
+        assert_eq!(label(&chat("1", Some("Alice"), Some("Lunch?"))), "Chat 1 — Alice: Lunch?");
+        assert_eq!(label(&chat("1", None, Some("Lunch?"))), "Chat 1 — Lunch?");
+        assert_eq!(label(&chat("1", Some("Alice"), None)), "Chat 1 — Alice");
+        // 1:1 chats are named after the sender
+        assert_eq!(label(&chat("1", Some("Chat 1"), Some("Lunch?"))), "Chat 1 — Lunch?");
+        assert_eq!(label(&chat("1", Some(" "), Some(" \n "))), "Chat 1");
+        assert_eq!(label(&chat("1", None, Some("see\n  you\tthere"))), "Chat 1 — see you there");
+        assert_eq!(label(&chat("1", None, Some("Q&A"))), "Chat 1 — Q&&A");
+    }
+
+    #[test]
+    fn long_labels_are_cut_with_an_ellipsis() {
+        let preview = "é".repeat(100);
+        let text = label(&chat("1", None, Some(&preview)));
+        assert_eq!(text.chars().count(), MAX_LABEL_CHARS);
+        assert!(text.starts_with("Chat 1 — éé"));
+        assert!(text.ends_with("é…"));
+
+        let fits = "é".repeat(MAX_LABEL_CHARS - "Chat 1 — ".chars().count());
+        assert_eq!(label(&chat("1", None, Some(&fits))), format!("Chat 1 — {}", fits));
+    }
+}
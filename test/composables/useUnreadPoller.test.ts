@@ -153,7 +153,7 @@ describe('useUnreadPoller', () => {
       expect(mockUpdateChannelUnread).not.toHaveBeenCalled()
     })
 
-    it('caps at MAX_WATCHED_CHANNELS (15)', async () => {
+    it('polls every watched channel (no cap)', async () => {
       const poller = useUnreadPoller()
       const channels = Array.from({ length: 20 }, (_, i) => ({
         teamId: `t${i}`,
@@ -163,12 +163,11 @@ describe('useUnreadPoller', () => {
       mockPeekChannelLatestMessage.mockResolvedValue(null)
 
       const promise = poller.pollChannels()
-      // 15 channels × 500ms stagger
-      await vi.advanceTimersByTimeAsync(15 * 500)
+      // 20 channels × 500ms stagger
+      await vi.advanceTimersByTimeAsync(20 * 500)
       await promise
 
-      // Only first 15 should be polled
-      expect(mockPeekChannelLatestMessage).toHaveBeenCalledTimes(15)
+      expect(mockPeekChannelLatestMessage).toHaveBeenCalledTimes(20)
     })
 
     it('continues polling remaining channels when one fails', async () => {
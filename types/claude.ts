@@ -143,3 +143,34 @@ export interface ClaudeContext {
   recentMessages?: Array<{ sender: string; content: string }>
   installedPlugins?: Array<{ id: string; name: string; description: string }>
 }
+
+// --- Filesystem Edit Approval Types ---
+
+export type EditPolicy = 'auto-approve' | 'always-ask' | 'read-only'
+
+export interface EditProposal {
+  id: string
+  path: string
+  diff: string
+  is_new_file: boolean
+}
+
+// --- Filesystem Read Types ---
+
+export interface FileMetadata {
+  size: number
+  modified: number | null
+  encoding: 'utf-8' | 'utf-8-bom' | 'utf-16le' | 'utf-16be' | 'unknown' | 'binary'
+  line_count: number | null
+}
+
+export interface FileContent {
+  content: string
+  format: 'text' | 'hex' | 'base64' | 'none'
+  truncated: boolean
+  /** The only line in `content` is cut short at the size limit. */
+  partial_line: boolean
+  start_line: number | null
+  end_line: number | null
+  metadata: FileMetadata
+}
@@ -0,0 +1,11 @@
+export type ConnectivityState = 'online' | 'degraded' | 'offline' | 'auth_expired'
+
+export interface ConnectivityStatus {
+  state: ConnectivityState
+  /** When the current state was entered. */
+  since: string
+  graphReachable: boolean
+  loginReachable: boolean
+  latencyMs: number | null
+  detail: string | null
+}
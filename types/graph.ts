@@ -96,6 +96,9 @@ export interface Presence {
   } | null
 }
 
+/** Statuses the user can pick for themselves (maps to setUserPreferredPresence). */
+export type PreferredPresence = 'Available' | 'Busy' | 'DoNotDisturb' | 'AppearAway'
+
 // --- Chat ---
 
 export type ChatType = 'oneOnOne' | 'group' | 'meeting'
@@ -227,6 +230,25 @@ export interface Person {
 
 // --- Send message payload ---
 
+// --- JSON batching ---
+
+export interface GraphBatchRequest {
+  id: string
+  method: string
+  /** Relative to the version root, e.g. `/me/chats/{id}`. */
+  url: string
+  headers?: Record<string, string>
+  body?: unknown
+  dependsOn?: string[]
+}
+
+export interface GraphBatchResponse<T = any> {
+  id: string
+  status: number
+  headers?: Record<string, string>
+  body: T
+}
+
 export interface SendMessagePayload {
   body: {
     contentType: 'text' | 'html'
@@ -299,6 +321,10 @@ export interface UsePresenceReturn {
   startPolling: (userIds: string[]) => void
   stopPolling: () => void
   getPresence: (userId: string) => Presence | undefined
+  myPresence: Ref<Presence | null>
+  setMyPresence: (status: PreferredPresence, expirationMinutes?: number) => Promise<void>
+  clearMyPresence: () => Promise<void>
+  setStatusMessage: (message: string, expiresAt?: string | null) => Promise<void>
 }
 
 // Vue ref type for use in interfaces
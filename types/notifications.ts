@@ -0,0 +1,101 @@
+export interface QuietHours {
+  enabled: boolean
+  /** `HH:MM`, local time. */
+  start: string
+  end: string
+}
+
+export interface NotificationPolicy {
+  /** Monday first. */
+  quietHours: QuietHours[]
+  /** RFC 3339; everything except bypasses is held back until then. */
+  muteUntil: string | null
+  followPresence: boolean
+  vipSenders: string[]
+  mentionsBypass: boolean
+}
+
+export type RuleAction = 'notify' | 'silent' | 'badgeOnly' | 'sound' | 'escalate'
+
+export interface RuleMatch {
+  senders?: string[]
+  chatTypes?: Array<'oneOnOne' | 'group' | 'meeting' | 'channel'>
+  /** Chat IDs, channel IDs or `<teamId>:<channelId>` keys. */
+  conversations?: string[]
+  keywords?: string[]
+  /** Regular expression over the message text. */
+  pattern?: string | null
+  mentionsMe?: boolean | null
+  /** 0 = Monday. */
+  days?: number[]
+  start?: string | null
+  end?: string | null
+}
+
+/** `default` is the platform's sound; anything else is played by the app. */
+export type NotificationSound = 'default' | 'silent' | { bundled: string } | { file: string }
+
+export interface SoundSettings {
+  /** Names usable as `{ bundled: name }`. */
+  bundled: string[]
+  /** Conversation key to its sound. */
+  chats: Record<string, NotificationSound>
+}
+
+export interface NotificationRule {
+  id: string
+  name?: string
+  enabled?: boolean
+  when?: RuleMatch
+  action: RuleAction
+  /** What the `sound` action plays. */
+  sound?: NotificationSound | null
+}
+
+export interface SampleMessage {
+  senderId?: string
+  senderName: string
+  senderEmail?: string | null
+  chatType: string
+  chatId?: string
+  teamId?: string | null
+  channelId?: string | null
+  text: string
+  mentionsMe: boolean
+  /** RFC 3339; now when missing. */
+  time?: string | null
+}
+
+export interface RuleDryRun {
+  ruleId: string | null
+  action: RuleAction
+}
+
+export type SuppressReason = 'chat_muted' | 'muted_until' | 'quiet_hours' | 'presence' | 'rule'
+
+export interface NotificationRecord {
+  id: number
+  createdAt: string
+  /** Chat ID or `<teamId>:<channelId>`. */
+  conversation: string
+  chatId: string | null
+  teamId: string | null
+  channelId: string | null
+  messageId: string | null
+  title: string
+  body: string
+  shown: boolean
+  reason: SuppressReason | null
+  ruleId: string | null
+  seen: boolean
+}
+
+export interface HistoryQuery {
+  conversation?: string
+  /** Only shown (`true`) or only held back (`false`) notifications. */
+  shown?: boolean
+  unseenOnly?: boolean
+  /** Entries older than this ID, for paging. */
+  beforeId?: number
+  limit?: number
+}
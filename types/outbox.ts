@@ -0,0 +1,21 @@
+import type { ChatMessage, SendMessagePayload } from './graph'
+
+export type OutboxState = 'pending' | 'sending' | 'failed'
+
+export interface OutboxItem {
+  id: string
+  /** Chat ID or `<teamId>:<channelId>`. */
+  conversation: string
+  path: string
+  payload: SendMessagePayload
+  createdAt: string
+  state: OutboxState
+  attempts: number
+  nextAttemptAt: string | null
+  lastError: string | null
+}
+
+export interface OutboxSent {
+  item: OutboxItem
+  message: ChatMessage
+}
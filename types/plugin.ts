@@ -89,6 +89,8 @@ export interface PluginMessage {
   content: string
   preview: string
   createdAt: string
+  /** The app has already notified (or deliberately not notified) about this message */
+  notified?: boolean
 }
 
 // --- Plugin Manifest ---
@@ -0,0 +1,24 @@
+export interface RealtimeConfig {
+  enabled: boolean
+  port: number
+  publicUrl: string | null
+  certificatePath: string | null
+  privateKeyPath: string | null
+  certificateId: string
+}
+
+export interface RealtimeSubscription {
+  id: string
+  resource: string
+  expirationDateTime: string
+}
+
+export interface RealtimeStatus {
+  running: boolean
+  notificationUrl: string | null
+  rich: boolean
+  subscriptions: RealtimeSubscription[]
+  /** Local address the public URL has to forward to. */
+  listenAddress: string | null
+  error: string | null
+}
@@ -0,0 +1,29 @@
+export interface MessageSearchQuery {
+  /** Free text; also accepts `from:`, `in:`, `after:`, `before:` and `has:attachment`. */
+  text: string
+  /** Sender user ID or display name (prefix). */
+  sender?: string
+  /** Chat ID or `<teamId>:<channelId>`. */
+  conversation?: string
+  after?: string
+  before?: string
+  hasAttachment?: boolean
+  limit?: number
+}
+
+export interface MessageSearchHit {
+  conversation: string
+  messageId: string
+  chatId: string | null
+  teamId: string | null
+  channelId: string | null
+  replyToId: string | null
+  senderId: string | null
+  senderName: string
+  createdDateTime: string
+  hasAttachments: boolean
+  snippet: string
+  /** `[start, end)` offsets of matched text within `snippet`. */
+  highlights: [number, number][]
+  score: number
+}